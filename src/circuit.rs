@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+use sha3::Digest;
+
 use crate::{
     error::Error,
     gf2_word::{GF2Word, Value},
@@ -7,7 +10,34 @@ use crate::{
 pub type Output<T> = Vec<GF2Word<T>>;
 pub type TwoThreeDecOutput<T> = (Output<T>, Output<T>, Output<T>);
 
-pub trait Circuit<T: Value> {
+/// Fingerprint of a circuit, carried in a proof and absorbed into the
+/// Fiat-Shamir transcript so a proof cannot be checked against another circuit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitId {
+    /// Hash of `Circuit::description`
+    pub description_hash: Vec<u8>,
+    pub num_of_mul_gates: usize,
+    pub witness_len: usize,
+    pub party_output_len: usize,
+}
+
+impl CircuitId {
+    pub fn new<T: Value, D: Digest>(circuit: &impl Circuit<T>) -> Self {
+        debug_assert_eq!(
+            circuit.party_input_len(),
+            circuit.witness_len().div_ceil(T::bytes_len())
+        );
+
+        Self {
+            description_hash: D::digest(circuit.description()).to_vec(),
+            num_of_mul_gates: circuit.num_of_mul_gates(),
+            witness_len: circuit.witness_len(),
+            party_output_len: circuit.party_output_len(),
+        }
+    }
+}
+
+pub trait Circuit<T: Value>: Sync {
     fn compute(&self, input: &[u8]) -> Vec<GF2Word<T>>;
 
     /// Decompose this circuit into 3 branches such that the values computed in
@@ -23,7 +53,19 @@ pub trait Circuit<T: Value> {
         p: &mut Party<T>,
         p_next: &mut Party<T>,
     ) -> Result<(Output<T>, Output<T>), Error>;
+    /// A stable description of the circuit, including any public parameters
+    /// it is built with. Two circuits computing different functions must have
+    /// different descriptions.
+    fn description(&self) -> Vec<u8>;
+    /// Number of words of `T` an input share is parsed into: `witness_len`
+    /// rounded up to whole words.
     fn party_input_len(&self) -> usize;
+    /// Length in bytes of the witness, and so of every input share. This is
+    /// the length the prover and verifier size and check inputs with, and
+    /// only needs overriding when the witness is not a whole number of words.
+    fn witness_len(&self) -> usize {
+        self.party_input_len() * T::bytes_len()
+    }
     fn party_output_len(&self) -> usize;
     fn num_of_mul_gates(&self) -> usize;
 }
@@ -32,17 +74,20 @@ pub trait Circuit<T: Value> {
 mod circuit_tests {
     use std::marker::PhantomData;
 
-    use rand::{rngs::ThreadRng, thread_rng};
+    use rand::{thread_rng, SeedableRng};
     use rand_chacha::ChaCha20Rng;
     use sha3::Keccak256;
 
     use super::{Circuit, Output, TwoThreeDecOutput};
     use crate::{
+        data_structures::{Mode, Proof},
         error::Error,
+        fs::DomainSeparator,
         gadgets::{mpc_and, mpc_and_verify, mpc_xor, prepare::generic_parse},
         gf2_word::{GF2Word, Value},
         party::Party,
         prover::Prover,
+        security::SecurityParams,
         verifier::Verifier,
     };
 
@@ -52,7 +97,7 @@ mod circuit_tests {
 
     impl<T: Value> Circuit<T> for SimpleCircuit1<T> {
         fn compute(&self, input: &[u8]) -> Vec<GF2Word<T>> {
-            let x = generic_parse(input, self.party_input_len());
+            let x = generic_parse(input, self.party_input_len()).unwrap();
             vec![(x[0] ^ x[1]) & (x[2] ^ x[3]) & x[4]]
         }
 
@@ -63,9 +108,9 @@ mod circuit_tests {
             p3: &mut Party<T>,
         ) -> TwoThreeDecOutput<T> {
             // prepare
-            let x = generic_parse(&p1.view.input, 5);
-            let y = generic_parse(&p2.view.input, 5);
-            let z = generic_parse(&p3.view.input, 5);
+            let x = generic_parse(&p1.view.input, 5).unwrap();
+            let y = generic_parse(&p2.view.input, 5).unwrap();
+            let z = generic_parse(&p3.view.input, 5).unwrap();
 
             let (x1, x2, x3, x4, x5) = (x[0], x[1], x[2], x[3], x[4]);
             let (y1, y2, y3, y4, y5) = (y[0], y[1], y[2], y[3], y[4]);
@@ -86,8 +131,8 @@ mod circuit_tests {
             p: &mut Party<T>,
             p_next: &mut Party<T>,
         ) -> Result<(Output<T>, Output<T>), Error> {
-            let p_inputs = generic_parse(&p.view.input, self.party_input_len());
-            let p_next_inputs = generic_parse(&p_next.view.input, self.party_input_len());
+            let p_inputs = generic_parse(&p.view.input, self.party_input_len())?;
+            let p_next_inputs = generic_parse(&p_next.view.input, self.party_input_len())?;
 
             let (x1, x2, x3, x4, x5) = (
                 p_inputs[0],
@@ -121,6 +166,10 @@ mod circuit_tests {
             5
         }
 
+        fn description(&self) -> Vec<u8> {
+            b"(x1 ^ x2) & (x3 ^ x4) & x5".to_vec()
+        }
+
         fn party_output_len(&self) -> usize {
             1
         }
@@ -133,7 +182,7 @@ mod circuit_tests {
     #[test]
     fn test_full_run() {
         let mut rng = thread_rng();
-        const SIGMA: usize = 40;
+        let params = SecurityParams::BITS_40;
         let input: Vec<u8> = [
             5u32.to_le_bytes(),
             4u32.to_le_bytes(),
@@ -148,11 +197,236 @@ mod circuit_tests {
         let circuit = SimpleCircuit1(PhantomData);
         let output = circuit.compute(&input);
 
-        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove::<ThreadRng, SIGMA>(
-            &mut rng, &input, &circuit, &output,
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &input, &circuit, &output, &params, &dst,
+        )
+        .unwrap();
+
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+        let input: Vec<u8> = [5u32, 4, 7, 2, 9]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+
+        let circuit = SimpleCircuit1(PhantomData);
+        let output = circuit.compute(&input);
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &input, &circuit, &output, &params, &dst,
         )
         .unwrap();
 
-        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output).unwrap();
+        let other_app = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"other app", &params);
+        assert!(matches!(
+            Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                &proof, &circuit, &output, &params, &other_app
+            ),
+            Err(Error::FiatShamirOutputsMatchingError)
+        ));
+
+        // tags built for other parameters are rejected on both sides
+        let wrong_sigma = DomainSeparator::new::<ChaCha20Rng, Keccak256>(
+            b"zkboo/tests",
+            &SecurityParams::BITS_80,
+        );
+        assert!(matches!(
+            Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                &proof,
+                &circuit,
+                &output,
+                &params,
+                &wrong_sigma
+            ),
+            Err(Error::DomainSeparationError)
+        ));
+        assert!(matches!(
+            Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+                &mut rng,
+                &input,
+                &circuit,
+                &output,
+                &params,
+                &wrong_sigma
+            ),
+            Err(Error::DomainSeparationError)
+        ));
+
+        // a proof made at one security level does not verify at another
+        assert!(matches!(
+            Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                &proof,
+                &circuit,
+                &output,
+                &SecurityParams::BITS_80,
+                &wrong_sigma
+            ),
+            Err(Error::SecurityParamsError(..))
+        ));
+    }
+
+    #[test]
+    fn test_full_run_zkbpp() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+        let input: Vec<u8> = [5u32, 4, 7, 2, 9]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+
+        let circuit = SimpleCircuit1(PhantomData);
+        let output = circuit.compute(&input);
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove_with_mode(
+            &mut rng,
+            &input,
+            &circuit,
+            &output,
+            &params,
+            &dst,
+            Mode::ZKBPlusPlus,
+        )
+        .unwrap();
+
+        // only shares of the third party are sent
+        for (&trit, input) in proof.claimed_trits.iter().zip(proof.party_inputs.iter()) {
+            assert_eq!(input.is_empty(), trit != 2);
+        }
+        for (&trit, view) in proof.claimed_trits.iter().zip(proof.views.iter()) {
+            assert_eq!(view.input.is_empty(), trit != 1);
+        }
+
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_serialized_proof() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+        let input: Vec<u8> = [1u64, 2, 3, 4, 5]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+
+        let circuit = SimpleCircuit1(PhantomData);
+        let output = circuit.compute(&input);
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u64, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &input, &circuit, &output, &params, &dst,
+        )
+        .unwrap();
+
+        let bytes = proof.to_bytes().unwrap();
+        let proof = Proof::<u64, Keccak256>::from_bytes(&bytes, &params).unwrap();
+
+        Verifier::<u64, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_deterministic_proofs() {
+        let params = SecurityParams::BITS_40;
+        let input: Vec<u8> = [5u32, 4, 7, 2, 9]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+
+        let circuit = SimpleCircuit1(PhantomData);
+        let output = circuit.compute(&input);
+
+        // proofs only depend on the rng, not on how repetitions are scheduled
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        for mode in [Mode::ZKBoo, Mode::ZKBPlusPlus] {
+            let prove = || {
+                let mut rng = ChaCha20Rng::seed_from_u64(42);
+                Prover::<u32, ChaCha20Rng, Keccak256>::prove_with_mode(
+                    &mut rng, &input, &circuit, &output, &params, &dst, mode,
+                )
+                .unwrap()
+                .to_bytes()
+                .unwrap()
+            };
+
+            assert_eq!(prove(), prove());
+        }
+    }
+
+    #[test]
+    fn test_malformed_proof() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+        let input: Vec<u8> = [5u32, 4, 7, 2, 9]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+
+        let circuit = SimpleCircuit1(PhantomData);
+        let output = circuit.compute(&input);
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        assert!(matches!(
+            Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+                &mut rng,
+                &input[1..],
+                &circuit,
+                &output,
+                &params,
+                &dst
+            ),
+            Err(Error::InputLengthError(20, 19))
+        ));
+
+        let bytes = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &input, &circuit, &output, &params, &dst,
+        )
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+        let fresh_proof = || Proof::<u32, Keccak256>::from_bytes(&bytes, &params).unwrap();
+        let verify = |proof: &Proof<u32, Keccak256>, output: &Vec<GF2Word<u32>>| {
+            Verifier::<u32, ChaCha20Rng, Keccak256>::verify(proof, &circuit, output, &params, &dst)
+        };
+
+        let mut proof = fresh_proof();
+        proof.keys.pop();
+        assert!(matches!(
+            verify(&proof, &output),
+            Err(Error::ProofLengthError("keys", 138, 137))
+        ));
+
+        let mut proof = fresh_proof();
+        proof.claimed_trits[0] = 3;
+        assert!(matches!(verify(&proof, &output), Err(Error::TritError(3))));
+
+        let mut proof = fresh_proof();
+        proof.views[0].messages.pop();
+        assert!(matches!(
+            verify(&proof, &output),
+            Err(Error::ViewExhaustedError)
+        ));
+
+        let mut proof = fresh_proof();
+        proof.views[0].input.push(0);
+        assert!(matches!(
+            verify(&proof, &output),
+            Err(Error::InputLengthError(20, 21))
+        ));
+
+        let proof = fresh_proof();
+        assert!(matches!(
+            verify(&proof, &vec![]),
+            Err(Error::OutputLengthError(1, 0))
+        ));
     }
 }
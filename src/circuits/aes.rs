@@ -0,0 +1,208 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::aes::{
+        encrypt, expand_key, mpc_encrypt, mpc_encrypt_verify, mpc_expand_key,
+        mpc_expand_key_verify, Block, KeySize, MUL_GATES_PER_SBOX_LAYER,
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Proves knowledge of a key `k` such that AES(k, m) = c, for a public
+/// plaintext block `m` and a public ciphertext block `c`, where the witness is
+/// the key and `c` is the public output read as big-endian words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AesCircuit {
+    key_size: KeySize,
+    plaintext: Block,
+}
+
+impl AesCircuit {
+    pub fn new(key_size: KeySize, plaintext: &Block) -> Self {
+        Self {
+            key_size,
+            plaintext: *plaintext,
+        }
+    }
+
+    pub fn aes128(plaintext: &Block) -> Self {
+        Self::new(KeySize::Aes128, plaintext)
+    }
+
+    pub fn aes256(plaintext: &Block) -> Self {
+        Self::new(KeySize::Aes256, plaintext)
+    }
+
+    pub fn key_size(&self) -> KeySize {
+        self.key_size
+    }
+
+    pub fn plaintext(&self) -> &Block {
+        &self.plaintext
+    }
+
+    /// Public output of the circuit for a `ciphertext` c.
+    pub fn output_from_ciphertext(ciphertext: &Block) -> Output<u32> {
+        ciphertext
+            .chunks(4)
+            .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()).into())
+            .collect()
+    }
+}
+
+impl Circuit<u32> for AesCircuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.key_size.key_len());
+
+        let round_keys = expand_key(input, self.key_size);
+        Self::output_from_ciphertext(&encrypt(&round_keys, &self.plaintext))
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let key_p1 = p1.view.input.clone();
+        let key_p2 = p2.view.input.clone();
+        let key_p3 = p3.view.input.clone();
+
+        let (k1, k2, k3) = mpc_expand_key((&key_p1, &key_p2, &key_p3), self.key_size, p1, p2, p3);
+        let (c1, c2, c3) = mpc_encrypt((&k1, &k2, &k3), &self.plaintext, p1, p2, p3);
+
+        (
+            Self::output_from_ciphertext(&c1),
+            Self::output_from_ciphertext(&c2),
+            Self::output_from_ciphertext(&c3),
+        )
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let key_p = p.view.input.clone();
+        let key_p_next = p_next.view.input.clone();
+
+        let (k, k_next) = mpc_expand_key_verify((&key_p, &key_p_next), self.key_size, p, p_next)?;
+        let (c, c_next) = mpc_encrypt_verify((&k, &k_next), &self.plaintext, p, p_next)?;
+
+        Ok((
+            Self::output_from_ciphertext(&c),
+            Self::output_from_ciphertext(&c_next),
+        ))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"aes".as_slice(),
+            &(self.key_size.key_len() as u64).to_le_bytes(),
+            &self.plaintext,
+        ]
+        .concat()
+    }
+
+    /// Words of the key.
+    fn party_input_len(&self) -> usize {
+        self.key_size.key_len() / 4
+    }
+
+    fn party_output_len(&self) -> usize {
+        4
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_SBOX_LAYER * self.key_size.num_of_sbox_layers()
+    }
+}
+
+#[cfg(test)]
+mod test_aes_circuit {
+    use super::AesCircuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+        gadgets::aes::KeySize,
+    };
+
+    /// FIPS 197, appendix C
+    const PLAINTEXT: [u8; 16] = [
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee,
+        0xff,
+    ];
+    const TEST_VECTORS: [(KeySize, [u8; 16]); 2] = [
+        (
+            KeySize::Aes128,
+            [
+                0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+                0xc5, 0x5a,
+            ],
+        ),
+        (
+            KeySize::Aes256,
+            [
+                0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49,
+                0x60, 0x89,
+            ],
+        ),
+    ];
+
+    /// The key 00 01 02 .. of the given size.
+    fn key(key_size: KeySize) -> Vec<u8> {
+        (0..key_size.key_len() as u8).collect()
+    }
+
+    #[test]
+    fn test_compute() {
+        for (key_size, ciphertext) in TEST_VECTORS {
+            let circuit = AesCircuit::new(key_size, &PLAINTEXT);
+            assert_eq!(
+                circuit.compute(&key(key_size)),
+                AesCircuit::output_from_ciphertext(&ciphertext)
+            );
+        }
+    }
+
+    #[test]
+    fn test_num_of_mul_gates() {
+        // SubBytes in every round, and 10 and 13 SubWords in the key expansion
+        assert_eq!(
+            AesCircuit::aes128(&PLAINTEXT).num_of_mul_gates(),
+            32 * (10 + 10)
+        );
+        assert_eq!(
+            AesCircuit::aes256(&PLAINTEXT).num_of_mul_gates(),
+            32 * (14 + 13)
+        );
+    }
+
+    #[test]
+    fn test_full_run() {
+        for (key_size, ciphertext) in TEST_VECTORS {
+            let circuit = AesCircuit::new(key_size, &PLAINTEXT);
+            let output = AesCircuit::output_from_ciphertext(&ciphertext);
+            let proof = check_full_run(&circuit, &key(key_size), &output);
+
+            // the plaintext is part of the statement
+            assert!(matches!(
+                verify(&proof, &AesCircuit::new(key_size, &[0; 16]), &output),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let (key_size, ciphertext) = TEST_VECTORS[0];
+        let circuit = AesCircuit::new(key_size, &PLAINTEXT);
+        check_zkbpp(
+            &circuit,
+            &key(key_size),
+            &AesCircuit::output_from_ciphertext(&ciphertext),
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::{
+        blake2s::{
+            block_words, blocks, compress, init_iv, mpc_compress, mpc_compress_verify,
+            MUL_GATES_PER_ROUND, ROUNDS,
+        },
+        mpc_public, mpc_public_verify,
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Mul gates of the compression of one block.
+pub const MUL_GATES_PER_BLOCK: usize = ROUNDS * MUL_GATES_PER_ROUND;
+
+/// Proves knowledge of `x` such that BLAKE2s-256(x) = y, unkeyed, where y is
+/// the public output read as little-endian words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blake2sCircuit {
+    message_len: usize,
+}
+
+impl Blake2sCircuit {
+    pub fn new(message_len: usize) -> Self {
+        Self { message_len }
+    }
+
+    pub fn message_len(&self) -> usize {
+        self.message_len
+    }
+
+    /// Number of blocks of the message, at least one.
+    pub fn num_of_blocks(&self) -> usize {
+        self.message_len.div_ceil(64).max(1)
+    }
+
+    /// Public output of the circuit for a `digest` y.
+    pub fn output_from_digest(digest: &[u8; 32]) -> Output<u32> {
+        digest
+            .chunks(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()).into())
+            .collect()
+    }
+}
+
+impl Circuit<u32> for Blake2sCircuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.message_len);
+
+        let mut state = init_iv(32);
+        for (range, params) in blocks(self.message_len) {
+            state = compress(&state, &block_words(&input[range]), &params);
+        }
+
+        state.to_vec()
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let input_p1 = p1.view.input.clone();
+        let input_p2 = p2.view.input.clone();
+        let input_p3 = p3.view.input.clone();
+
+        let mut state = mpc_public(init_iv(32));
+        for (range, params) in blocks(self.message_len) {
+            state = mpc_compress(
+                &state,
+                (
+                    &block_words(&input_p1[range.clone()]),
+                    &block_words(&input_p2[range.clone()]),
+                    &block_words(&input_p3[range]),
+                ),
+                &params,
+                p1,
+                p2,
+                p3,
+            );
+        }
+
+        (state.0.to_vec(), state.1.to_vec(), state.2.to_vec())
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let input_p = p.view.input.clone();
+        let input_p_next = p_next.view.input.clone();
+
+        let mut state = mpc_public_verify(init_iv(32));
+        for (range, params) in blocks(self.message_len) {
+            state = mpc_compress_verify(
+                &state,
+                (
+                    &block_words(&input_p[range.clone()]),
+                    &block_words(&input_p_next[range]),
+                ),
+                &params,
+                p,
+                p_next,
+            )?;
+        }
+
+        Ok((state.0.to_vec(), state.1.to_vec()))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"blake2s".as_slice(),
+            &(self.message_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.message_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        8
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_BLOCK * self.num_of_blocks()
+    }
+}
+
+#[cfg(test)]
+mod test_blake2s_circuit {
+    use super::Blake2sCircuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+    };
+
+    /// Digests of the messages `[0, 1, .., 250, 0, 1, ..]` of the given
+    /// lengths, from the reference implementation.
+    const TEST_VECTORS: [(usize, [u8; 32]); 4] = [
+        (
+            0,
+            [
+                0x69, 0x21, 0x7a, 0x30, 0x79, 0x90, 0x80, 0x94, 0xe1, 0x11, 0x21, 0xd0, 0x42, 0x35,
+                0x4a, 0x7c, 0x1f, 0x55, 0xb6, 0x48, 0x2c, 0xa1, 0xa5, 0x1e, 0x1b, 0x25, 0x0d, 0xfd,
+                0x1e, 0xd0, 0xee, 0xf9,
+            ],
+        ),
+        (
+            64,
+            [
+                0x56, 0xf3, 0x4e, 0x8b, 0x96, 0x55, 0x7e, 0x90, 0xc1, 0xf2, 0x4b, 0x52, 0xd0, 0xc8,
+                0x9d, 0x51, 0x08, 0x6a, 0xcf, 0x1b, 0x00, 0xf6, 0x34, 0xcf, 0x1d, 0xde, 0x92, 0x33,
+                0xb8, 0xea, 0xaa, 0x3e,
+            ],
+        ),
+        (
+            65,
+            [
+                0x1b, 0x53, 0xee, 0x94, 0xaa, 0xf3, 0x4e, 0x4b, 0x15, 0x9d, 0x48, 0xde, 0x35, 0x2c,
+                0x7f, 0x06, 0x61, 0xd0, 0xa4, 0x0e, 0xdf, 0xf9, 0x5a, 0x0b, 0x16, 0x39, 0xb4, 0x09,
+                0x0e, 0x97, 0x44, 0x72,
+            ],
+        ),
+        (
+            200,
+            [
+                0x6d, 0x24, 0x4e, 0x1a, 0x06, 0xce, 0x4e, 0xf5, 0x78, 0xdd, 0x0f, 0x63, 0xaf, 0xf0,
+                0x93, 0x67, 0x06, 0x73, 0x51, 0x19, 0xca, 0x9c, 0x8d, 0x22, 0xd8, 0x6c, 0x80, 0x14,
+                0x14, 0xab, 0x97, 0x41,
+            ],
+        ),
+    ];
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_compute() {
+        for (len, digest) in TEST_VECTORS {
+            let circuit = Blake2sCircuit::new(len);
+            assert_eq!(
+                circuit.compute(&message(len)),
+                Blake2sCircuit::output_from_digest(&digest)
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_run() {
+        for (len, digest) in TEST_VECTORS {
+            let circuit = Blake2sCircuit::new(len);
+            let output = Blake2sCircuit::output_from_digest(&digest);
+            let proof = check_full_run(&circuit, &message(len), &output);
+
+            // the message length is part of the statement
+            assert!(matches!(
+                verify(&proof, &Blake2sCircuit::new(len + 1), &output),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let preimage = message(100);
+        let circuit = Blake2sCircuit::new(preimage.len());
+        check_zkbpp(&circuit, &preimage, &circuit.compute(&preimage));
+    }
+}
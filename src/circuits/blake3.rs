@@ -0,0 +1,240 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::{
+        blake2s::MUL_GATES_PER_ROUND,
+        blake3::{
+            block_words, compress, init_cv, mpc_compress, mpc_compress_verify, single_chunk_blocks,
+            BLOCK_LEN, CHUNK_LEN, ROUNDS,
+        },
+        mpc_public, mpc_public_verify,
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Mul gates of the compression of one block.
+pub const MUL_GATES_PER_BLOCK: usize = ROUNDS * MUL_GATES_PER_ROUND;
+
+/// Proves knowledge of `x` such that BLAKE3(x) = y, for the default 32 byte
+/// output of the unkeyed hash, where y is the public output read as
+/// little-endian words. The message fits in a single chunk of 1024 bytes,
+/// which is the root of the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blake3Circuit {
+    message_len: usize,
+}
+
+impl Blake3Circuit {
+    pub fn new(message_len: usize) -> Self {
+        assert!(
+            message_len <= CHUNK_LEN,
+            "only single chunk messages are supported"
+        );
+        Self { message_len }
+    }
+
+    pub fn message_len(&self) -> usize {
+        self.message_len
+    }
+
+    /// Number of blocks of the chunk, at least one.
+    pub fn num_of_blocks(&self) -> usize {
+        self.message_len.div_ceil(BLOCK_LEN).max(1)
+    }
+
+    /// Public output of the circuit for a `digest` y.
+    pub fn output_from_digest(digest: &[u8; 32]) -> Output<u32> {
+        digest
+            .chunks(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()).into())
+            .collect()
+    }
+}
+
+impl Circuit<u32> for Blake3Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.message_len);
+
+        let mut cv = init_cv();
+        for (range, params) in single_chunk_blocks(self.message_len) {
+            cv = compress(&cv, &block_words(&input[range]), &params);
+        }
+
+        cv.to_vec()
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let input_p1 = p1.view.input.clone();
+        let input_p2 = p2.view.input.clone();
+        let input_p3 = p3.view.input.clone();
+
+        let mut cv = mpc_public(init_cv());
+        for (range, params) in single_chunk_blocks(self.message_len) {
+            cv = mpc_compress(
+                &cv,
+                (
+                    &block_words(&input_p1[range.clone()]),
+                    &block_words(&input_p2[range.clone()]),
+                    &block_words(&input_p3[range]),
+                ),
+                &params,
+                p1,
+                p2,
+                p3,
+            );
+        }
+
+        (cv.0.to_vec(), cv.1.to_vec(), cv.2.to_vec())
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let input_p = p.view.input.clone();
+        let input_p_next = p_next.view.input.clone();
+
+        let mut cv = mpc_public_verify(init_cv());
+        for (range, params) in single_chunk_blocks(self.message_len) {
+            cv = mpc_compress_verify(
+                &cv,
+                (
+                    &block_words(&input_p[range.clone()]),
+                    &block_words(&input_p_next[range]),
+                ),
+                &params,
+                p,
+                p_next,
+            )?;
+        }
+
+        Ok((cv.0.to_vec(), cv.1.to_vec()))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"blake3".as_slice(),
+            &(self.message_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.message_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        8
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_BLOCK * self.num_of_blocks()
+    }
+}
+
+#[cfg(test)]
+mod test_blake3_circuit {
+    use super::Blake3Circuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+    };
+
+    /// Hashes of the messages `[0, 1, .., 250, 0, 1, ..]` of the given
+    /// lengths, from the reference test vectors.
+    const TEST_VECTORS: [(usize, [u8; 32]); 4] = [
+        (
+            0,
+            [
+                0xaf, 0x13, 0x49, 0xb9, 0xf5, 0xf9, 0xa1, 0xa6, 0xa0, 0x40, 0x4d, 0xea, 0x36, 0xdc,
+                0xc9, 0x49, 0x9b, 0xcb, 0x25, 0xc9, 0xad, 0xc1, 0x12, 0xb7, 0xcc, 0x9a, 0x93, 0xca,
+                0xe4, 0x1f, 0x32, 0x62,
+            ],
+        ),
+        (
+            1,
+            [
+                0x2d, 0x3a, 0xde, 0xdf, 0xf1, 0x1b, 0x61, 0xf1, 0x4c, 0x88, 0x6e, 0x35, 0xaf, 0xa0,
+                0x36, 0x73, 0x6d, 0xcd, 0x87, 0xa7, 0x4d, 0x27, 0xb5, 0xc1, 0x51, 0x02, 0x25, 0xd0,
+                0xf5, 0x92, 0xe2, 0x13,
+            ],
+        ),
+        (
+            1023,
+            [
+                0x10, 0x10, 0x89, 0x70, 0xee, 0xda, 0x3e, 0xb9, 0x32, 0xba, 0xac, 0x14, 0x28, 0xc7,
+                0xa2, 0x16, 0x3b, 0x0e, 0x92, 0x4c, 0x9a, 0x9e, 0x25, 0xb3, 0x5b, 0xba, 0x72, 0xb2,
+                0x8f, 0x70, 0xbd, 0x11,
+            ],
+        ),
+        (
+            1024,
+            [
+                0x42, 0x21, 0x47, 0x39, 0xf0, 0x95, 0xa4, 0x06, 0xf3, 0xfc, 0x83, 0xde, 0xb8, 0x89,
+                0x74, 0x4a, 0xc0, 0x0d, 0xf8, 0x31, 0xc1, 0x0d, 0xaa, 0x55, 0x18, 0x9b, 0x5d, 0x12,
+                0x1c, 0x85, 0x5a, 0xf7,
+            ],
+        ),
+    ];
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_compute() {
+        for (len, digest) in TEST_VECTORS {
+            let circuit = Blake3Circuit::new(len);
+            assert_eq!(
+                circuit.compute(&message(len)),
+                Blake3Circuit::output_from_digest(&digest)
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_run() {
+        for (len, digest) in TEST_VECTORS[..2].iter().copied() {
+            let circuit = Blake3Circuit::new(len);
+            let output = Blake3Circuit::output_from_digest(&digest);
+            let proof = check_full_run(&circuit, &message(len), &output);
+
+            // the message length is part of the statement
+            assert!(matches!(
+                verify(&proof, &Blake3Circuit::new(len + 1), &output),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_full_chunk() {
+        // the 16 blocks of a whole chunk, the last one being the root
+        let (len, digest) = TEST_VECTORS[3];
+        let circuit = Blake3Circuit::new(len);
+        assert_eq!(circuit.num_of_blocks(), 16);
+        check_zkbpp(
+            &circuit,
+            &message(len),
+            &Blake3Circuit::output_from_digest(&digest),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "only single chunk messages are supported")]
+    fn test_several_chunks() {
+        Blake3Circuit::new(1025);
+    }
+}
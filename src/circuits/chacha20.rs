@@ -0,0 +1,266 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    circuits::sha256::MUL_GATES_PER_BLOCK as SHA256_MUL_GATES_PER_BLOCK,
+    error::Error,
+    gadgets::{
+        chacha20::{
+            counter_fits, encrypt, key_words, mpc_encrypt, mpc_encrypt_verify, num_of_blocks,
+            Nonce, KEY_LEN, MUL_GATES_PER_BLOCK,
+        },
+        mpc_public, mpc_public_verify,
+        sha256::{
+            init_iv, mpc_sha256_block, mpc_sha256_block_verify, padding, sha256_block, State,
+        },
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Proves that a public ciphertext `c` is the ChaCha20 encryption of a secret
+/// message `m` under a secret key `k` with a public nonce, and that SHA-256(k)
+/// is a public digest `y`, so that whoever learns a key hashing to `y` can
+/// decrypt `c`. The witness is `k || m`, and the public output is `y` followed
+/// by `c`, both read as big-endian words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiableEncryptionCircuit {
+    nonce: Nonce,
+    message_len: usize,
+}
+
+impl VerifiableEncryptionCircuit {
+    pub fn new(nonce: Nonce, message_len: usize) -> Self {
+        assert!(
+            counter_fits(&nonce, message_len),
+            "the block counter would wrap around"
+        );
+        Self { nonce, message_len }
+    }
+
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    pub fn message_len(&self) -> usize {
+        self.message_len
+    }
+
+    /// Public output of the circuit for a `key_hash` y and a `ciphertext` c,
+    /// whose last word is padded with zeros.
+    pub fn output(key_hash: &[u8; 32], ciphertext: &[u8]) -> Output<u32> {
+        [key_hash.as_slice(), ciphertext]
+            .concat()
+            .chunks(4)
+            .map(|chunk| {
+                let mut bytes = [0u8; 4];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                u32::from_be_bytes(bytes).into()
+            })
+            .collect()
+    }
+
+    fn split(input: &[u8]) -> (&[u8], &[u8]) {
+        input.split_at(KEY_LEN)
+    }
+}
+
+impl Circuit<u32> for VerifiableEncryptionCircuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.witness_len());
+        let (key, message) = Self::split(input);
+
+        let iv: State = init_iv().to_vec().into();
+        let key_hash = sha256_block(&padding(key).try_into().unwrap(), &iv);
+        let ciphertext = encrypt(&key_words(key), &self.nonce, message);
+
+        Self::output(&key_hash.to_bytes(), &ciphertext)
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let input_p1 = p1.view.input.clone();
+        let input_p2 = p2.view.input.clone();
+        let input_p3 = p3.view.input.clone();
+        let (key_p1, message_p1) = Self::split(&input_p1);
+        let (key_p2, message_p2) = Self::split(&input_p2);
+        let (key_p3, message_p3) = Self::split(&input_p3);
+
+        let iv: State = init_iv().to_vec().into();
+        let (hash_1, hash_2, hash_3) = mpc_sha256_block(
+            &padding(key_p1).try_into().unwrap(),
+            &padding(key_p2).try_into().unwrap(),
+            &padding(key_p3).try_into().unwrap(),
+            &mpc_public(iv),
+            p1,
+            p2,
+            p3,
+        );
+
+        let (c1, c2, c3) = mpc_encrypt(
+            (&key_words(key_p1), &key_words(key_p2), &key_words(key_p3)),
+            &self.nonce,
+            (message_p1, message_p2, message_p3),
+            p1,
+            p2,
+            p3,
+        );
+
+        (
+            Self::output(&hash_1.to_bytes(), &c1),
+            Self::output(&hash_2.to_bytes(), &c2),
+            Self::output(&hash_3.to_bytes(), &c3),
+        )
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let input_p = p.view.input.clone();
+        let input_p_next = p_next.view.input.clone();
+        let (key_p, message_p) = Self::split(&input_p);
+        let (key_p_next, message_p_next) = Self::split(&input_p_next);
+
+        let iv: State = init_iv().to_vec().into();
+        let (hash, hash_next) = mpc_sha256_block_verify(
+            &padding(key_p).try_into().unwrap(),
+            &padding(key_p_next).try_into().unwrap(),
+            &mpc_public_verify(iv),
+            p,
+            p_next,
+        )?;
+
+        let (c, c_next) = mpc_encrypt_verify(
+            (&key_words(key_p), &key_words(key_p_next)),
+            &self.nonce,
+            (message_p, message_p_next),
+            p,
+            p_next,
+        )?;
+
+        Ok((
+            Self::output(&hash.to_bytes(), &c),
+            Self::output(&hash_next.to_bytes(), &c_next),
+        ))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"chacha20_verifiable_encryption".as_slice(),
+            &self.nonce.nonce,
+            &self.nonce.counter.to_le_bytes(),
+            &(self.message_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        KEY_LEN + self.message_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        8 + self.message_len.div_ceil(4)
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        SHA256_MUL_GATES_PER_BLOCK + MUL_GATES_PER_BLOCK * num_of_blocks(self.message_len)
+    }
+}
+
+#[cfg(test)]
+mod test_chacha20_circuit {
+    use super::VerifiableEncryptionCircuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+        gadgets::chacha20::Nonce,
+    };
+
+    /// RFC 8439, section 2.4.2
+    const NONCE: Nonce = Nonce {
+        nonce: [0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0],
+        counter: 1,
+    };
+    const PLAINTEXT: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+    const CIPHERTEXT: [u8; 114] = [
+        0x6e, 0x2e, 0x35, 0x9a, 0x25, 0x68, 0xf9, 0x80, 0x41, 0xba, 0x07, 0x28, 0xdd, 0x0d, 0x69,
+        0x81, 0xe9, 0x7e, 0x7a, 0xec, 0x1d, 0x43, 0x60, 0xc2, 0x0a, 0x27, 0xaf, 0xcc, 0xfd, 0x9f,
+        0xae, 0x0b, 0xf9, 0x1b, 0x65, 0xc5, 0x52, 0x47, 0x33, 0xab, 0x8f, 0x59, 0x3d, 0xab, 0xcd,
+        0x62, 0xb3, 0x57, 0x16, 0x39, 0xd6, 0x24, 0xe6, 0x51, 0x52, 0xab, 0x8f, 0x53, 0x0c, 0x35,
+        0x9f, 0x08, 0x61, 0xd8, 0x07, 0xca, 0x0d, 0xbf, 0x50, 0x0d, 0x6a, 0x61, 0x56, 0xa3, 0x8e,
+        0x08, 0x8a, 0x22, 0xb6, 0x5e, 0x52, 0xbc, 0x51, 0x4d, 0x16, 0xcc, 0xf8, 0x06, 0x81, 0x8c,
+        0xe9, 0x1a, 0xb7, 0x79, 0x37, 0x36, 0x5a, 0xf9, 0x0b, 0xbf, 0x74, 0xa3, 0x5b, 0xe6, 0xb4,
+        0x0b, 0x8e, 0xed, 0xf2, 0x78, 0x5e, 0x42, 0x87, 0x4d,
+    ];
+    /// SHA-256 of the key 00 01 .. 1f
+    const KEY_HASH: [u8; 32] = [
+        0x63, 0x0d, 0xcd, 0x29, 0x66, 0xc4, 0x33, 0x66, 0x91, 0x12, 0x54, 0x48, 0xbb, 0xb2, 0x5b,
+        0x4f, 0xf4, 0x12, 0xa4, 0x9c, 0x73, 0x2d, 0xb2, 0xc8, 0xab, 0xc1, 0xb8, 0x58, 0x1b, 0xd7,
+        0x10, 0xdd,
+    ];
+
+    fn witness() -> Vec<u8> {
+        [(0..32).collect::<Vec<u8>>().as_slice(), PLAINTEXT].concat()
+    }
+
+    #[test]
+    fn test_compute() {
+        let circuit = VerifiableEncryptionCircuit::new(NONCE, PLAINTEXT.len());
+        assert_eq!(
+            circuit.compute(&witness()),
+            VerifiableEncryptionCircuit::output(&KEY_HASH, &CIPHERTEXT)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the block counter would wrap around")]
+    fn test_counter_overflow() {
+        let nonce = Nonce {
+            counter: u32::MAX,
+            ..NONCE
+        };
+        // the last counter value only covers a single block
+        VerifiableEncryptionCircuit::new(nonce, 64);
+        VerifiableEncryptionCircuit::new(nonce, 65);
+    }
+
+    #[test]
+    fn test_full_run() {
+        let circuit = VerifiableEncryptionCircuit::new(NONCE, PLAINTEXT.len());
+        let output = VerifiableEncryptionCircuit::output(&KEY_HASH, &CIPHERTEXT);
+        let proof = check_full_run(&circuit, &witness(), &output);
+
+        // the nonce is part of the statement
+        let other_nonce = Nonce {
+            counter: 0,
+            ..NONCE
+        };
+        assert!(matches!(
+            verify(
+                &proof,
+                &VerifiableEncryptionCircuit::new(other_nonce, PLAINTEXT.len()),
+                &output
+            ),
+            Err(Error::CircuitMismatchError)
+        ));
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let circuit = VerifiableEncryptionCircuit::new(NONCE, PLAINTEXT.len());
+        check_zkbpp(
+            &circuit,
+            &witness(),
+            &VerifiableEncryptionCircuit::output(&KEY_HASH, &CIPHERTEXT),
+        );
+    }
+}
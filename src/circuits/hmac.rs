@@ -0,0 +1,508 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    circuits::sha256::MUL_GATES_PER_BLOCK,
+    error::Error,
+    gadgets::{
+        hmac::{
+            hmac_pads, hmac_sha256, inner_blocks, key_block, key_hash_blocks, mpc_hmac_pads,
+            mpc_hmac_pads_verify, mpc_hmac_sha256, mpc_hmac_sha256_verify, mpc_key_block,
+            mpc_key_block_verify, Pads,
+        },
+        mpc_public, mpc_public_verify,
+        sha256::State,
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Bytes of a SHA-256 digest, the size of the pseudorandom key and of each
+/// block of output keying material of HKDF.
+const HASH_LEN: usize = 32;
+
+/// Proves knowledge of a key `k` such that HMAC-SHA256(k, m) = t, for a public
+/// message `m` and a public tag `t`. The witness is the key, of any length;
+/// keys longer than a block are hashed in the MPC first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HmacSha256Circuit {
+    key_len: usize,
+    message: Vec<u8>,
+}
+
+impl HmacSha256Circuit {
+    pub fn new(key_len: usize, message: &[u8]) -> Self {
+        Self {
+            key_len,
+            message: message.to_vec(),
+        }
+    }
+
+    pub fn key_len(&self) -> usize {
+        self.key_len
+    }
+
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// Public output of the circuit for a `tag` t.
+    pub fn output_from_tag(tag: &[u8; 32]) -> Output<u32> {
+        State::from_bytes(tag).to_vec()
+    }
+}
+
+impl Circuit<u32> for HmacSha256Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.key_len);
+
+        let pads = hmac_pads(&key_block(input));
+        hmac_sha256(&pads, &self.message).to_vec()
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let key_p1 = p1.view.input.clone();
+        let key_p2 = p2.view.input.clone();
+        let key_p3 = p3.view.input.clone();
+
+        let (k1, k2, k3) = mpc_key_block((&key_p1, &key_p2, &key_p3), p1, p2, p3);
+        let pads = mpc_hmac_pads((&k1, &k2, &k3), p1, p2, p3);
+
+        let message = self.message.as_slice();
+        let (tag_1, tag_2, tag_3) = mpc_hmac_sha256(&pads, mpc_public(message), p1, p2, p3);
+
+        (tag_1.to_vec(), tag_2.to_vec(), tag_3.to_vec())
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let key_p = p.view.input.clone();
+        let key_p_next = p_next.view.input.clone();
+
+        let (k, k_next) = mpc_key_block_verify((&key_p, &key_p_next), p, p_next)?;
+        let pads = mpc_hmac_pads_verify((&k, &k_next), p, p_next)?;
+
+        let message = self.message.as_slice();
+        let (tag, tag_next) = mpc_hmac_sha256_verify(&pads, mpc_public_verify(message), p, p_next)?;
+
+        Ok((tag.to_vec(), tag_next.to_vec()))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"hmac-sha256".as_slice(),
+            &(self.key_len as u64).to_le_bytes(),
+            &(self.message.len() as u64).to_le_bytes(),
+            &self.message,
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.key_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        8
+    }
+
+    /// Blocks to hash a long key, to compress the padded key blocks, of the
+    /// inner hash and of the outer hash.
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_BLOCK
+            * (key_hash_blocks(self.key_len) + 2 + inner_blocks(self.message.len()) + 1)
+    }
+}
+
+/// Proves knowledge of input keying material `ikm` such that
+/// HKDF-SHA256(salt, ikm, info) gives the public output keying material `okm`
+/// of `output_len` bytes, for a public `salt` and `info`.
+///
+/// HKDF-Extract computes the pseudorandom key `prk = HMAC(salt, ikm)`, where
+/// the pads of the public salt are compressed in the clear, and HKDF-Expand
+/// computes `T(i) = HMAC(prk, T(i - 1) || info || i)`, whose concatenation is
+/// truncated into `okm`. Both `prk` and the `T(i)` stay secret. The output is
+/// `okm` read as big-endian words, the last one padded with zeros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HkdfSha256Circuit {
+    salt: Vec<u8>,
+    ikm_len: usize,
+    info: Vec<u8>,
+    output_len: usize,
+}
+
+impl HkdfSha256Circuit {
+    pub fn new(salt: &[u8], ikm_len: usize, info: &[u8], output_len: usize) -> Self {
+        assert!(
+            output_len > 0 && output_len <= 255 * HASH_LEN,
+            "HKDF outputs between 1 and 255 hash lengths of keying material"
+        );
+        Self {
+            salt: salt.to_vec(),
+            ikm_len,
+            info: info.to_vec(),
+            output_len,
+        }
+    }
+
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    pub fn ikm_len(&self) -> usize {
+        self.ikm_len
+    }
+
+    pub fn info(&self) -> &[u8] {
+        &self.info
+    }
+
+    pub fn output_len(&self) -> usize {
+        self.output_len
+    }
+
+    /// Number of blocks T(i) of output keying material.
+    pub fn num_of_expansions(&self) -> usize {
+        self.output_len.div_ceil(HASH_LEN)
+    }
+
+    /// Public output of the circuit for an `okm`.
+    pub fn output_from_okm(&self, okm: &[u8]) -> Output<u32> {
+        assert_eq!(okm.len(), self.output_len);
+        key_material_words(okm)
+    }
+
+    /// Pads of the public salt, which defaults to a hash length of zeros
+    /// when empty and is then the same key block as the empty key.
+    fn salt_pads(&self) -> Pads {
+        hmac_pads(&key_block(&self.salt))
+    }
+
+    /// The message T(i - 1) || info || i of the `i`-th expansion, from a
+    /// share of T(i - 1), which is empty for the first one.
+    fn expansion_message(&self, i: usize, previous: &[u8]) -> Vec<u8> {
+        [previous, &self.info, &[i as u8]].concat()
+    }
+}
+
+/// Read derived keying material as big-endian words, padding the last one
+/// with zeros, which is linear and so applies to shares too.
+pub(crate) fn key_material_words(bytes: &[u8]) -> Output<u32> {
+    bytes
+        .chunks(4)
+        .map(|chunk| {
+            let mut word = [0u8; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_be_bytes(word).into()
+        })
+        .collect()
+}
+
+impl Circuit<u32> for HkdfSha256Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.ikm_len);
+
+        let prk = hmac_sha256(&self.salt_pads(), input);
+        let pads = hmac_pads(&key_block(&prk.to_bytes()));
+
+        let mut okm = Vec::with_capacity(HASH_LEN * self.num_of_expansions());
+        let mut previous = vec![];
+        for i in 1..=self.num_of_expansions() {
+            let t = hmac_sha256(&pads, &self.expansion_message(i, &previous));
+            previous = t.to_bytes().to_vec();
+            okm.extend_from_slice(&previous);
+        }
+
+        key_material_words(&okm[..self.output_len])
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let ikm_p1 = p1.view.input.clone();
+        let ikm_p2 = p2.view.input.clone();
+        let ikm_p3 = p3.view.input.clone();
+
+        // HKDF-Extract, keyed with the public salt
+        let salt_pads = self.salt_pads();
+        let (prk_1, prk_2, prk_3) = mpc_hmac_sha256(
+            &mpc_public(salt_pads),
+            (&ikm_p1, &ikm_p2, &ikm_p3),
+            p1,
+            p2,
+            p3,
+        );
+
+        // HKDF-Expand, keyed with the secret pseudorandom key
+        let pads = mpc_hmac_pads(
+            (
+                &key_block(&prk_1.to_bytes()),
+                &key_block(&prk_2.to_bytes()),
+                &key_block(&prk_3.to_bytes()),
+            ),
+            p1,
+            p2,
+            p3,
+        );
+
+        let mut okm = (vec![], vec![], vec![]);
+        let mut previous = (vec![], vec![], vec![]);
+        for i in 1..=self.num_of_expansions() {
+            let (t1, t2, t3) = mpc_hmac_sha256(
+                &pads,
+                (
+                    &self.expansion_message(i, &previous.0),
+                    &self.expansion_message(i, &previous.1),
+                    &self.expansion_message(i, &previous.2),
+                ),
+                p1,
+                p2,
+                p3,
+            );
+            previous = (
+                t1.to_bytes().to_vec(),
+                t2.to_bytes().to_vec(),
+                t3.to_bytes().to_vec(),
+            );
+            okm.0.extend_from_slice(&previous.0);
+            okm.1.extend_from_slice(&previous.1);
+            okm.2.extend_from_slice(&previous.2);
+        }
+
+        (
+            key_material_words(&okm.0[..self.output_len]),
+            key_material_words(&okm.1[..self.output_len]),
+            key_material_words(&okm.2[..self.output_len]),
+        )
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let ikm_p = p.view.input.clone();
+        let ikm_p_next = p_next.view.input.clone();
+
+        let salt_pads = self.salt_pads();
+        let (prk, prk_next) = mpc_hmac_sha256_verify(
+            &mpc_public_verify(salt_pads),
+            (&ikm_p, &ikm_p_next),
+            p,
+            p_next,
+        )?;
+
+        let pads = mpc_hmac_pads_verify(
+            (
+                &key_block(&prk.to_bytes()),
+                &key_block(&prk_next.to_bytes()),
+            ),
+            p,
+            p_next,
+        )?;
+
+        let mut okm = (vec![], vec![]);
+        let mut previous = (vec![], vec![]);
+        for i in 1..=self.num_of_expansions() {
+            let (t, t_next) = mpc_hmac_sha256_verify(
+                &pads,
+                (
+                    &self.expansion_message(i, &previous.0),
+                    &self.expansion_message(i, &previous.1),
+                ),
+                p,
+                p_next,
+            )?;
+            previous = (t.to_bytes().to_vec(), t_next.to_bytes().to_vec());
+            okm.0.extend_from_slice(&previous.0);
+            okm.1.extend_from_slice(&previous.1);
+        }
+
+        Ok((
+            key_material_words(&okm.0[..self.output_len]),
+            key_material_words(&okm.1[..self.output_len]),
+        ))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"hkdf-sha256".as_slice(),
+            &(self.salt.len() as u64).to_le_bytes(),
+            &self.salt,
+            &(self.ikm_len as u64).to_le_bytes(),
+            &(self.info.len() as u64).to_le_bytes(),
+            &self.info,
+            &(self.output_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.ikm_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        self.output_len.div_ceil(4)
+    }
+
+    /// HKDF-Extract hashes the input keying material and then the outer
+    /// block, and HKDF-Expand compresses the padded key blocks of the
+    /// pseudorandom key before each HMAC.
+    fn num_of_mul_gates(&self) -> usize {
+        let extract = inner_blocks(self.ikm_len) + 1;
+        let expand: usize = (1..=self.num_of_expansions())
+            .map(|i| {
+                let previous_len = if i == 1 { 0 } else { HASH_LEN };
+                inner_blocks(previous_len + self.info.len() + 1) + 1
+            })
+            .sum();
+
+        MUL_GATES_PER_BLOCK * (extract + 2 + expand)
+    }
+}
+
+#[cfg(test)]
+mod test_hmac_circuit {
+    use super::{HkdfSha256Circuit, HmacSha256Circuit};
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+    };
+
+    /// RFC 4231 test cases 1, 2 and 6, the last with a key longer than a block
+    const HMAC_TEST_VECTORS: [(&[u8], &[u8], [u8; 32]); 3] = [
+        (
+            &[0x0b; 20],
+            b"Hi There",
+            [
+                0xb0, 0x34, 0x4c, 0x61, 0xd8, 0xdb, 0x38, 0x53, 0x5c, 0xa8, 0xaf, 0xce, 0xaf, 0x0b,
+                0xf1, 0x2b, 0x88, 0x1d, 0xc2, 0x00, 0xc9, 0x83, 0x3d, 0xa7, 0x26, 0xe9, 0x37, 0x6c,
+                0x2e, 0x32, 0xcf, 0xf7,
+            ],
+        ),
+        (
+            b"Jefe",
+            b"what do ya want for nothing?",
+            [
+                0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+                0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+                0x64, 0xec, 0x38, 0x43,
+            ],
+        ),
+        (
+            &[0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First",
+            [
+                0x60, 0xe4, 0x31, 0x59, 0x1e, 0xe0, 0xb6, 0x7f, 0x0d, 0x8a, 0x26, 0xaa, 0xcb, 0xf5,
+                0xb7, 0x7f, 0x8e, 0x0b, 0xc6, 0x21, 0x37, 0x28, 0xc5, 0x14, 0x05, 0x46, 0x04, 0x0f,
+                0x0e, 0xe3, 0x7f, 0x54,
+            ],
+        ),
+    ];
+
+    /// RFC 5869 test case 1, with a salt and info
+    const HKDF_SALT: [u8; 13] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+    ];
+    const HKDF_INFO: [u8; 10] = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9];
+    const HKDF_OKM: [u8; 42] = [
+        0x3c, 0xb2, 0x5f, 0x25, 0xfa, 0xac, 0xd5, 0x7a, 0x90, 0x43, 0x4f, 0x64, 0xd0, 0x36, 0x2f,
+        0x2a, 0x2d, 0x2d, 0x0a, 0x90, 0xcf, 0x1a, 0x5a, 0x4c, 0x5d, 0xb0, 0x2d, 0x56, 0xec, 0xc4,
+        0xc5, 0xbf, 0x34, 0x00, 0x72, 0x08, 0xd5, 0xb8, 0x87, 0x18, 0x58, 0x65,
+    ];
+
+    /// RFC 5869 test case 3, without salt and info
+    const HKDF_OKM_NO_SALT: [u8; 42] = [
+        0x8d, 0xa4, 0xe7, 0x75, 0xa5, 0x63, 0xc1, 0x8f, 0x71, 0x5f, 0x80, 0x2a, 0x06, 0x3c, 0x5a,
+        0x31, 0xb8, 0xa1, 0x1f, 0x5c, 0x5e, 0xe1, 0x87, 0x9e, 0xc3, 0x45, 0x4e, 0x5f, 0x3c, 0x73,
+        0x8d, 0x2d, 0x9d, 0x20, 0x13, 0x95, 0xfa, 0xa4, 0xb6, 0x1a, 0x96, 0xc8,
+    ];
+
+    #[test]
+    fn test_compute() {
+        for (key, message, tag) in HMAC_TEST_VECTORS {
+            let circuit = HmacSha256Circuit::new(key.len(), message);
+            assert_eq!(
+                circuit.compute(key),
+                HmacSha256Circuit::output_from_tag(&tag)
+            );
+        }
+
+        let circuit = HkdfSha256Circuit::new(&HKDF_SALT, 22, &HKDF_INFO, 42);
+        assert_eq!(
+            circuit.compute(&[0x0b; 22]),
+            circuit.output_from_okm(&HKDF_OKM)
+        );
+
+        let circuit = HkdfSha256Circuit::new(&[], 22, &[], 42);
+        assert_eq!(
+            circuit.compute(&[0x0b; 22]),
+            circuit.output_from_okm(&HKDF_OKM_NO_SALT)
+        );
+    }
+
+    #[test]
+    fn test_hmac_full_run() {
+        for (key, message, tag) in HMAC_TEST_VECTORS {
+            let circuit = HmacSha256Circuit::new(key.len(), message);
+            let output = HmacSha256Circuit::output_from_tag(&tag);
+            let proof = check_full_run(&circuit, key, &output);
+
+            // the message is part of the statement
+            assert!(matches!(
+                verify(
+                    &proof,
+                    &HmacSha256Circuit::new(key.len(), b"another message"),
+                    &output
+                ),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_hkdf_full_run() {
+        let circuit = HkdfSha256Circuit::new(&HKDF_SALT, 22, &HKDF_INFO, 42);
+        let output = circuit.output_from_okm(&HKDF_OKM);
+        let proof = check_full_run(&circuit, &[0x0b; 22], &output);
+
+        // the salt and the info are part of the statement
+        for other in [
+            HkdfSha256Circuit::new(&[], 22, &HKDF_INFO, 42),
+            HkdfSha256Circuit::new(&HKDF_SALT, 22, &[], 42),
+        ] {
+            assert!(matches!(
+                verify(&proof, &other, &output),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let circuit = HkdfSha256Circuit::new(&[], 22, &[], 42);
+        check_zkbpp(
+            &circuit,
+            &[0x0b; 22],
+            &circuit.output_from_okm(&HKDF_OKM_NO_SALT),
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::{
+        keccak::{absorb, empty_state, keccak_f, mpc_keccak_f, mpc_keccak_f_verify, State, ROUNDS},
+        mpc_public, mpc_public_verify,
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Mul gates of one Keccak-f[1600] permutation: one per lane in every round.
+pub const MUL_GATES_PER_PERMUTATION: usize = 25 * ROUNDS;
+
+/// Proves knowledge of `x` such that H(x) = y for H a Keccak sponge: SHA3-256,
+/// the original Keccak-256 of Ethereum, or SHAKE128/256 with any output
+/// length. The output is y read as little-endian lanes, with the bytes of a
+/// last partial lane set to 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeccakCircuit {
+    /// Bytes absorbed or squeezed per permutation
+    rate: usize,
+    /// First bits of the padding, which separate the domains of the variants
+    delimiter: u8,
+    /// Bytes of y
+    output_len: usize,
+    message_len: usize,
+}
+
+impl KeccakCircuit {
+    pub fn sha3_256(message_len: usize) -> Self {
+        Self::new(136, 0x06, 32, message_len)
+    }
+
+    /// Keccak-256 as used by Ethereum, with the padding submitted before
+    /// standardization.
+    pub fn keccak256(message_len: usize) -> Self {
+        Self::new(136, 0x01, 32, message_len)
+    }
+
+    pub fn shake128(message_len: usize, output_len: usize) -> Self {
+        Self::new(168, 0x1f, output_len, message_len)
+    }
+
+    pub fn shake256(message_len: usize, output_len: usize) -> Self {
+        Self::new(136, 0x1f, output_len, message_len)
+    }
+
+    fn new(rate: usize, delimiter: u8, output_len: usize, message_len: usize) -> Self {
+        assert!(output_len > 0);
+        Self {
+            rate,
+            delimiter,
+            output_len,
+            message_len,
+        }
+    }
+
+    pub fn message_len(&self) -> usize {
+        self.message_len
+    }
+
+    pub fn output_len(&self) -> usize {
+        self.output_len
+    }
+
+    /// Number of permutations while absorbing the padded message, which
+    /// appends at least one byte.
+    pub fn num_of_blocks(&self) -> usize {
+        (self.message_len + 1).div_ceil(self.rate)
+    }
+
+    /// Number of permutations after the first squeeze.
+    pub fn num_of_squeezes(&self) -> usize {
+        self.output_len.div_ceil(self.rate) - 1
+    }
+
+    /// Public output of the circuit for a `digest` y of `output_len` bytes.
+    pub fn output_from_digest(&self, digest: &[u8]) -> Output<u64> {
+        assert_eq!(digest.len(), self.output_len);
+        digest
+            .chunks(8)
+            .map(|chunk| {
+                let mut lane = [0u8; 8];
+                lane[..chunk.len()].copy_from_slice(chunk);
+                u64::from_le_bytes(lane).into()
+            })
+            .collect()
+    }
+
+    /// Pad `message`, or a share of it, to a multiple of the rate.
+    fn padding(&self, message: &[u8]) -> Vec<u8> {
+        let mut padded = message.to_vec();
+        padded.push(self.delimiter);
+        padded.resize(self.num_of_blocks() * self.rate, 0);
+        *padded.last_mut().unwrap() ^= 0x80;
+        padded
+    }
+
+    /// Lanes of y in `state`, where the squeezed part of the state is at the
+    /// start of the lanes.
+    fn squeeze(&self, state: &State, output: &mut Output<u64>) {
+        let lanes = (self.output_len.div_ceil(8) - output.len()).min(self.rate / 8);
+        output.extend_from_slice(&state[..lanes]);
+
+        if output.len() == self.output_len.div_ceil(8) && !self.output_len.is_multiple_of(8) {
+            // masking with a public constant is linear, so it applies to shares
+            let mask = u64::MAX >> (64 - 8 * (self.output_len % 8));
+            let last = output.last_mut().unwrap();
+            *last = *last & mask.into();
+        }
+    }
+}
+
+impl Circuit<u64> for KeccakCircuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u64>> {
+        assert_eq!(input.len(), self.message_len);
+
+        let mut state = empty_state();
+        for block in self.padding(input).chunks(self.rate) {
+            absorb(&mut state, block);
+            state = keccak_f(&state);
+        }
+
+        let mut output = Vec::with_capacity(self.party_output_len());
+        self.squeeze(&state, &mut output);
+        for _ in 0..self.num_of_squeezes() {
+            state = keccak_f(&state);
+            self.squeeze(&state, &mut output);
+        }
+
+        output
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u64>,
+        p2: &mut Party<u64>,
+        p3: &mut Party<u64>,
+    ) -> TwoThreeDecOutput<u64> {
+        let p1_bytes = self.padding(&p1.view.input);
+        let p2_bytes = self.padding(&p2.view.input);
+        let p3_bytes = self.padding(&p3.view.input);
+
+        let mut state = mpc_public(empty_state());
+
+        let iter_chunks = p1_bytes
+            .chunks(self.rate)
+            .zip(p2_bytes.chunks(self.rate))
+            .zip(p3_bytes.chunks(self.rate));
+
+        for ((p1_block, p2_block), p3_block) in iter_chunks {
+            absorb(&mut state.0, p1_block);
+            absorb(&mut state.1, p2_block);
+            absorb(&mut state.2, p3_block);
+            state = mpc_keccak_f(&state, p1, p2, p3);
+        }
+
+        let mut output = (vec![], vec![], vec![]);
+        for i in 0..=self.num_of_squeezes() {
+            if i > 0 {
+                state = mpc_keccak_f(&state, p1, p2, p3);
+            }
+            self.squeeze(&state.0, &mut output.0);
+            self.squeeze(&state.1, &mut output.1);
+            self.squeeze(&state.2, &mut output.2);
+        }
+
+        output
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u64>,
+        p_next: &mut Party<u64>,
+    ) -> Result<(Output<u64>, Output<u64>), Error> {
+        let p_bytes = self.padding(&p.view.input);
+        let p_next_bytes = self.padding(&p_next.view.input);
+
+        let mut state = mpc_public_verify(empty_state());
+
+        for (p_block, p_next_block) in p_bytes
+            .chunks(self.rate)
+            .zip(p_next_bytes.chunks(self.rate))
+        {
+            absorb(&mut state.0, p_block);
+            absorb(&mut state.1, p_next_block);
+            state = mpc_keccak_f_verify(&state, p, p_next)?;
+        }
+
+        let mut output = (vec![], vec![]);
+        for i in 0..=self.num_of_squeezes() {
+            if i > 0 {
+                state = mpc_keccak_f_verify(&state, p, p_next)?;
+            }
+            self.squeeze(&state.0, &mut output.0);
+            self.squeeze(&state.1, &mut output.1);
+        }
+
+        Ok(output)
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"keccak".as_slice(),
+            &(self.rate as u64).to_le_bytes(),
+            &[self.delimiter],
+            &(self.output_len as u64).to_le_bytes(),
+            &(self.message_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(8)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.message_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        self.output_len.div_ceil(8)
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_PERMUTATION * (self.num_of_blocks() + self.num_of_squeezes())
+    }
+}
+
+#[cfg(test)]
+mod test_keccak_circuit {
+    use sha3::{
+        digest::{ExtendableOutput, Update},
+        Digest, Keccak256, Sha3_256, Shake128, Shake256,
+    };
+
+    use super::KeccakCircuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+    };
+
+    fn shake<H: Default + Update + ExtendableOutput>(message: &[u8], output_len: usize) -> Vec<u8> {
+        let mut hasher = H::default();
+        hasher.update(message);
+        let mut output = vec![0u8; output_len];
+        hasher.finalize_xof_into(&mut output);
+        output
+    }
+
+    #[test]
+    fn test_vectors() {
+        // FIPS 202 example for "abc", and Keccak-256 of the empty message as
+        // found throughout Ethereum
+        let circuit = KeccakCircuit::sha3_256(3);
+        let digest = [
+            0x3a, 0x98, 0x5d, 0xa7, 0x4f, 0xe2, 0x25, 0xb2, 0x04, 0x5c, 0x17, 0x2d, 0x6b, 0xd3,
+            0x90, 0xbd, 0x85, 0x5f, 0x08, 0x6e, 0x3e, 0x9d, 0x52, 0x5b, 0x46, 0xbf, 0xe2, 0x45,
+            0x11, 0x43, 0x15, 0x32,
+        ];
+        assert_eq!(circuit.compute(b"abc"), circuit.output_from_digest(&digest));
+
+        let circuit = KeccakCircuit::keccak256(0);
+        let digest = [
+            0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+            0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+            0x5d, 0x85, 0xa4, 0x70,
+        ];
+        assert_eq!(circuit.compute(b""), circuit.output_from_digest(&digest));
+    }
+
+    #[test]
+    fn test_compute() {
+        // message lengths around the rates of 136 and 168 bytes
+        for message_len in [0, 1, 135, 136, 167, 168, 300] {
+            let message: Vec<u8> = (0..message_len).map(|i| i as u8).collect();
+
+            let circuit = KeccakCircuit::sha3_256(message_len);
+            assert_eq!(
+                circuit.compute(&message),
+                circuit.output_from_digest(&Sha3_256::digest(&message))
+            );
+            let circuit = KeccakCircuit::keccak256(message_len);
+            assert_eq!(
+                circuit.compute(&message),
+                circuit.output_from_digest(&Keccak256::digest(&message))
+            );
+
+            // partial lanes, and outputs longer than the rate
+            for output_len in [1, 20, 32, 200, 400] {
+                let circuit = KeccakCircuit::shake128(message_len, output_len);
+                assert_eq!(
+                    circuit.compute(&message),
+                    circuit.output_from_digest(&shake::<Shake128>(&message, output_len))
+                );
+                let circuit = KeccakCircuit::shake256(message_len, output_len);
+                assert_eq!(
+                    circuit.compute(&message),
+                    circuit.output_from_digest(&shake::<Shake256>(&message, output_len))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_full_run() {
+        let preimage = [0x3cu8; 140];
+        let circuits = [
+            KeccakCircuit::sha3_256(preimage.len()),
+            KeccakCircuit::keccak256(preimage.len()),
+            KeccakCircuit::shake128(preimage.len(), 180),
+            KeccakCircuit::shake256(preimage.len(), 20),
+        ];
+
+        for (i, circuit) in circuits.iter().enumerate() {
+            let output = circuit.compute(&preimage);
+            let proof = check_full_run(circuit, &preimage, &output);
+
+            // SHA3-256 and Keccak-256 only differ in their padding
+            let other_circuit = &circuits[(i + 1) % circuits.len()];
+            assert!(matches!(
+                verify(&proof, other_circuit, &output),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let preimage = b"abc";
+        let circuit = KeccakCircuit::keccak256(preimage.len());
+        check_zkbpp(&circuit, preimage, &circuit.compute(preimage));
+    }
+}
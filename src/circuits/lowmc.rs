@@ -0,0 +1,223 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::lowmc::{LowMc, Params},
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Proves knowledge of a key `k` such that LowMC(k, m) = c, for a public
+/// plaintext `m` and a public ciphertext `c`, as in Picnic signatures, where the
+/// witness is the key and `c` is the public output read as big-endian words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowMcCircuit {
+    lowmc: LowMc,
+    plaintext: Vec<u8>,
+}
+
+impl LowMcCircuit {
+    /// Derive the instance of `params` and fix the public `plaintext`. Blocks
+    /// and keys must be a whole number of words.
+    pub fn new(params: Params, plaintext: &[u8]) -> Self {
+        assert!(params.block_bits.is_multiple_of(32) && params.key_bits.is_multiple_of(32));
+        assert_eq!(plaintext.len(), params.block_len());
+
+        Self {
+            lowmc: LowMc::new(params),
+            plaintext: plaintext.to_vec(),
+        }
+    }
+
+    pub fn lowmc(&self) -> &LowMc {
+        &self.lowmc
+    }
+
+    pub fn plaintext(&self) -> &[u8] {
+        &self.plaintext
+    }
+
+    /// Public output of the circuit for a `ciphertext` c.
+    pub fn output_from_ciphertext(ciphertext: &[u8]) -> Output<u32> {
+        ciphertext
+            .chunks(4)
+            .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()).into())
+            .collect()
+    }
+}
+
+impl Circuit<u32> for LowMcCircuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        Self::output_from_ciphertext(&self.lowmc.encrypt(input, &self.plaintext))
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let key_p1 = p1.view.input.clone();
+        let key_p2 = p2.view.input.clone();
+        let key_p3 = p3.view.input.clone();
+
+        let (c1, c2, c3) =
+            self.lowmc
+                .mpc_encrypt((&key_p1, &key_p2, &key_p3), &self.plaintext, p1, p2, p3);
+
+        (
+            Self::output_from_ciphertext(&c1),
+            Self::output_from_ciphertext(&c2),
+            Self::output_from_ciphertext(&c3),
+        )
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let key_p = p.view.input.clone();
+        let key_p_next = p_next.view.input.clone();
+
+        let (c, c_next) =
+            self.lowmc
+                .mpc_encrypt_verify((&key_p, &key_p_next), &self.plaintext, p, p_next)?;
+
+        Ok((
+            Self::output_from_ciphertext(&c),
+            Self::output_from_ciphertext(&c_next),
+        ))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        let params = self.lowmc.params();
+        [
+            b"lowmc".as_slice(),
+            &(params.block_bits as u64).to_le_bytes(),
+            &(params.key_bits as u64).to_le_bytes(),
+            &(params.sboxes as u64).to_le_bytes(),
+            &(params.rounds as u64).to_le_bytes(),
+            &self.plaintext,
+        ]
+        .concat()
+    }
+
+    /// Words of the key.
+    fn party_input_len(&self) -> usize {
+        self.lowmc.params().key_len() / 4
+    }
+
+    fn party_output_len(&self) -> usize {
+        self.lowmc.params().block_len() / 4
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        self.lowmc.params().num_of_mul_gates()
+    }
+}
+
+#[cfg(test)]
+mod test_lowmc_circuit {
+    use super::LowMcCircuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+        gadgets::lowmc::Params,
+    };
+
+    type TestVector = (Params, &'static [u8]);
+
+    /// Ciphertexts of the plaintext ab ff 00 .. under the key 80 00 .., from the
+    /// Picnic reference implementation.
+    const TEST_VECTORS: [TestVector; 3] = [
+        (
+            Params::PICNIC_L1,
+            &[
+                0x0e, 0x30, 0x72, 0x0b, 0x9f, 0x64, 0xd5, 0xc2, 0xa7, 0x77, 0x1c, 0x8c, 0x23, 0x8d,
+                0x8f, 0x70,
+            ],
+        ),
+        (
+            Params::PICNIC_L3,
+            &[
+                0xa8, 0x5b, 0x82, 0x44, 0x34, 0x4a, 0x2e, 0x1b, 0x10, 0xa1, 0x7b, 0xab, 0x04, 0x30,
+                0x73, 0xf6, 0xbb, 0x64, 0x9a, 0xe6, 0xaf, 0x65, 0x9f, 0x6f,
+            ],
+        ),
+        (
+            Params::PICNIC_L5,
+            &[
+                0xb8, 0xf2, 0x0a, 0x88, 0x8a, 0x0a, 0x9e, 0xc4, 0xe4, 0x95, 0xf1, 0xfb, 0x43, 0x9a,
+                0xbd, 0xde, 0x18, 0xc1, 0xd3, 0xd2, 0x9c, 0xf2, 0x0d, 0xf4, 0xb1, 0x0a, 0x56, 0x7a,
+                0xa0, 0x2c, 0x72, 0x67,
+            ],
+        ),
+    ];
+
+    fn key(params: &Params) -> Vec<u8> {
+        let mut key = vec![0u8; params.key_len()];
+        key[0] = 0x80;
+        key
+    }
+
+    fn plaintext(params: &Params) -> Vec<u8> {
+        let mut plaintext = vec![0u8; params.block_len()];
+        plaintext[..2].copy_from_slice(&[0xab, 0xff]);
+        plaintext
+    }
+
+    #[test]
+    fn test_compute() {
+        for (params, ciphertext) in TEST_VECTORS {
+            let circuit = LowMcCircuit::new(params, &plaintext(&params));
+            assert_eq!(
+                circuit.compute(&key(&params)),
+                LowMcCircuit::output_from_ciphertext(ciphertext)
+            );
+        }
+    }
+
+    #[test]
+    fn test_num_of_mul_gates() {
+        // 3 ANDs on the 10 S-boxes of every round
+        assert_eq!(Params::PICNIC_L1.num_of_mul_gates(), 3 * 20);
+        assert_eq!(Params::PICNIC_L3.num_of_mul_gates(), 3 * 30);
+        assert_eq!(Params::PICNIC_L5.num_of_mul_gates(), 3 * 38);
+        assert_eq!(Params::new(128, 128, 42, 4).num_of_mul_gates(), 6 * 4);
+    }
+
+    #[test]
+    fn test_full_run() {
+        let (params, ciphertext) = TEST_VECTORS[0];
+        let circuit = LowMcCircuit::new(params, &plaintext(&params));
+        let output = LowMcCircuit::output_from_ciphertext(ciphertext);
+        let proof = check_full_run(&circuit, &key(&params), &output);
+
+        // the plaintext is part of the statement
+        assert!(matches!(
+            verify(&proof, &LowMcCircuit::new(params, &[0; 16]), &output),
+            Err(Error::CircuitMismatchError)
+        ));
+    }
+
+    #[test]
+    fn test_full_sbox_layer() {
+        // more S-boxes than lanes in a word, as in the full layers of Picnic3
+        let params = Params::new(128, 128, 42, 4);
+        let circuit = LowMcCircuit::new(params, &plaintext(&params));
+        let output = circuit.compute(&key(&params));
+        check_full_run(&circuit, &key(&params), &output);
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let (params, ciphertext) = TEST_VECTORS[0];
+        let circuit = LowMcCircuit::new(params, &plaintext(&params));
+        check_zkbpp(
+            &circuit,
+            &key(&params),
+            &LowMcCircuit::output_from_ciphertext(ciphertext),
+        );
+    }
+}
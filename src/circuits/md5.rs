@@ -0,0 +1,208 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::{
+        md5::{init_iv, md5_block, mpc_md5_block, mpc_md5_block_verify, padding, State},
+        mpc_public, mpc_public_verify,
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Mul gates of one block: one for each of the 48 steps of F, G and I, 4
+/// additions for each of the 64 steps and 4 to update the state.
+pub const MUL_GATES_PER_BLOCK: usize = 3 * 16 + 4 * 64 + 4;
+
+/// Proves knowledge of `x` such that MD5(x) = y, where y is the public output
+/// read as little-endian words, such as a password behind a legacy hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Md5Circuit {
+    message_len: usize,
+}
+
+impl Md5Circuit {
+    pub fn new(message_len: usize) -> Self {
+        Self { message_len }
+    }
+
+    pub fn message_len(&self) -> usize {
+        self.message_len
+    }
+
+    /// Number of blocks once padded, which appends at least 9 bytes.
+    pub fn num_of_blocks(&self) -> usize {
+        (self.message_len + 9).div_ceil(64)
+    }
+
+    /// Public output of the circuit for a `digest` y.
+    pub fn output_from_digest(digest: &[u8; 16]) -> Output<u32> {
+        State::from_bytes(digest).to_vec()
+    }
+}
+
+impl Circuit<u32> for Md5Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.message_len);
+
+        let mut state = init_iv();
+        for block in padding(input).chunks(16) {
+            state = md5_block(&block.try_into().unwrap(), &state);
+        }
+
+        state.to_vec()
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let p1_words = padding(&p1.view.input);
+        let p2_words = padding(&p2.view.input);
+        let p3_words = padding(&p3.view.input);
+
+        let mut state = mpc_public(init_iv());
+
+        let iter_chunks = p1_words
+            .chunks(16)
+            .zip(p2_words.chunks(16))
+            .zip(p3_words.chunks(16));
+
+        for ((p1_words, p2_words), p3_words) in iter_chunks {
+            state = mpc_md5_block(
+                &p1_words.try_into().unwrap(),
+                &p2_words.try_into().unwrap(),
+                &p3_words.try_into().unwrap(),
+                &state,
+                p1,
+                p2,
+                p3,
+            );
+        }
+
+        (state.0.to_vec(), state.1.to_vec(), state.2.to_vec())
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let p_words = padding(&p.view.input);
+        let p_next_words = padding(&p_next.view.input);
+
+        let mut state = mpc_public_verify(init_iv());
+
+        for (p_words, p_next_words) in p_words.chunks(16).zip(p_next_words.chunks(16)) {
+            state = mpc_md5_block_verify(
+                &p_words.try_into().unwrap(),
+                &p_next_words.try_into().unwrap(),
+                &state,
+                p,
+                p_next,
+            )?;
+        }
+
+        Ok((state.0.to_vec(), state.1.to_vec()))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [b"md5".as_slice(), &(self.message_len as u64).to_le_bytes()].concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.message_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        4
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_BLOCK * self.num_of_blocks()
+    }
+}
+
+#[cfg(test)]
+mod test_md5_circuit {
+    use super::Md5Circuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+    };
+
+    /// RFC 1321 test suite
+    const TEST_VECTORS: [(&[u8], [u8; 16]); 4] = [
+        (
+            b"",
+            [
+                0xd4, 0x1d, 0x8c, 0xd9, 0x8f, 0x00, 0xb2, 0x04, 0xe9, 0x80, 0x09, 0x98, 0xec, 0xf8,
+                0x42, 0x7e,
+            ],
+        ),
+        (
+            b"abc",
+            [
+                0x90, 0x01, 0x50, 0x98, 0x3c, 0xd2, 0x4f, 0xb0, 0xd6, 0x96, 0x3f, 0x7d, 0x28, 0xe1,
+                0x7f, 0x72,
+            ],
+        ),
+        (
+            b"message digest",
+            [
+                0xf9, 0x6b, 0x69, 0x7d, 0x7c, 0xb7, 0x93, 0x8d, 0x52, 0x5a, 0x2f, 0x31, 0xaa, 0xf1,
+                0x61, 0xd0,
+            ],
+        ),
+        (
+            b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+            [
+                0x57, 0xed, 0xf4, 0xa2, 0x2b, 0xe3, 0xc9, 0x55, 0xac, 0x49, 0xda, 0x2e, 0x21, 0x07,
+                0xb6, 0x7a,
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_compute() {
+        for (message, digest) in TEST_VECTORS {
+            let circuit = Md5Circuit::new(message.len());
+            assert_eq!(
+                circuit.compute(message),
+                Md5Circuit::output_from_digest(&digest)
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_run() {
+        for (preimage, digest) in TEST_VECTORS {
+            let circuit = Md5Circuit::new(preimage.len());
+            let output = Md5Circuit::output_from_digest(&digest);
+            let proof = check_full_run(&circuit, preimage, &output);
+
+            // the message length is part of the statement
+            assert!(matches!(
+                verify(&proof, &Md5Circuit::new(preimage.len() + 1), &output),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let preimage = b"correct horse battery staple";
+        let circuit = Md5Circuit::new(preimage.len());
+        let output = circuit.compute(preimage);
+        check_zkbpp(&circuit, preimage, &output);
+
+        // a wrong password does not hash to the output
+        assert_ne!(circuit.compute(b"correct horse battery stapl3"), output);
+    }
+}
@@ -0,0 +1,18 @@
+pub mod aes;
+pub mod blake2s;
+pub mod blake3;
+pub mod chacha20;
+pub mod hmac;
+pub mod keccak;
+pub mod lowmc;
+pub mod md5;
+pub mod pbkdf2;
+pub mod ripemd160;
+pub mod sha1;
+pub mod sha256;
+pub mod sha512;
+pub mod simon;
+pub mod speck;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
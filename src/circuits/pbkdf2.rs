@@ -0,0 +1,337 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    circuits::{hmac::key_material_words, sha256::MUL_GATES_PER_BLOCK},
+    error::Error,
+    gadgets::{
+        hmac::{
+            hmac_pads, hmac_sha256, inner_blocks, key_block, key_hash_blocks, mpc_hmac_pads,
+            mpc_hmac_pads_verify, mpc_hmac_sha256, mpc_hmac_sha256_verify, mpc_key_block,
+            mpc_key_block_verify,
+        },
+        mpc_public, mpc_public_verify,
+        sha256::State,
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Bytes of a SHA-256 digest, and so of each block T(i) of the derived key.
+const HASH_LEN: usize = 32;
+
+/// Proves knowledge of a password `p` such that PBKDF2-HMAC-SHA256(p, salt, c)
+/// gives the public derived key `dk` of `output_len` bytes, for a public
+/// `salt` and iteration count `c`.
+///
+/// Each block of the derived key is T(i) = U(1) xor .. xor U(c), where
+/// U(1) = HMAC(p, salt || i) and U(j) = HMAC(p, U(j - 1)). The pads of the
+/// password are compressed once and shared by all the HMACs, each of which
+/// then costs the blocks of its inner hash and one outer block. The output is
+/// `dk` read as big-endian words, the last one padded with zeros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pbkdf2Sha256Circuit {
+    password_len: usize,
+    salt: Vec<u8>,
+    iterations: u32,
+    output_len: usize,
+}
+
+impl Pbkdf2Sha256Circuit {
+    pub fn new(password_len: usize, salt: &[u8], iterations: u32, output_len: usize) -> Self {
+        assert!(iterations > 0, "PBKDF2 runs at least one iteration");
+        assert!(output_len > 0, "PBKDF2 derives at least one byte");
+        Self {
+            password_len,
+            salt: salt.to_vec(),
+            iterations,
+            output_len,
+        }
+    }
+
+    pub fn password_len(&self) -> usize {
+        self.password_len
+    }
+
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    pub fn output_len(&self) -> usize {
+        self.output_len
+    }
+
+    /// Number of blocks T(i) of the derived key.
+    pub fn num_of_blocks(&self) -> usize {
+        self.output_len.div_ceil(HASH_LEN)
+    }
+
+    /// Public output of the circuit for a derived key `dk`.
+    pub fn output_from_key(&self, dk: &[u8]) -> Output<u32> {
+        assert_eq!(dk.len(), self.output_len);
+        key_material_words(dk)
+    }
+
+    /// The message salt || i of U(1) for the `i`-th block, the same in all
+    /// three shares.
+    fn first_message(&self, i: usize) -> Vec<u8> {
+        [self.salt.as_slice(), &(i as u32).to_be_bytes()].concat()
+    }
+}
+
+/// t := t xor u, which is linear and so applies to shares too.
+fn xor_into(t: &mut [u8], u: &State) {
+    for (t, u) in t.iter_mut().zip(u.to_bytes()) {
+        *t ^= u;
+    }
+}
+
+impl Circuit<u32> for Pbkdf2Sha256Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.password_len);
+
+        let pads = hmac_pads(&key_block(input));
+
+        let mut dk = Vec::with_capacity(HASH_LEN * self.num_of_blocks());
+        for i in 1..=self.num_of_blocks() {
+            let mut u = hmac_sha256(&pads, &self.first_message(i));
+            let mut t = u.to_bytes().to_vec();
+            for _ in 1..self.iterations {
+                u = hmac_sha256(&pads, &u.to_bytes());
+                xor_into(&mut t, &u);
+            }
+            dk.extend_from_slice(&t);
+        }
+
+        key_material_words(&dk[..self.output_len])
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let password_p1 = p1.view.input.clone();
+        let password_p2 = p2.view.input.clone();
+        let password_p3 = p3.view.input.clone();
+
+        let (k1, k2, k3) = mpc_key_block((&password_p1, &password_p2, &password_p3), p1, p2, p3);
+        let pads = mpc_hmac_pads((&k1, &k2, &k3), p1, p2, p3);
+
+        let mut dk = (vec![], vec![], vec![]);
+        for i in 1..=self.num_of_blocks() {
+            let message = self.first_message(i);
+            let (mut u1, mut u2, mut u3) =
+                mpc_hmac_sha256(&pads, mpc_public(message.as_slice()), p1, p2, p3);
+            let mut t = (
+                u1.to_bytes().to_vec(),
+                u2.to_bytes().to_vec(),
+                u3.to_bytes().to_vec(),
+            );
+
+            for _ in 1..self.iterations {
+                (u1, u2, u3) = mpc_hmac_sha256(
+                    &pads,
+                    (&u1.to_bytes(), &u2.to_bytes(), &u3.to_bytes()),
+                    p1,
+                    p2,
+                    p3,
+                );
+                xor_into(&mut t.0, &u1);
+                xor_into(&mut t.1, &u2);
+                xor_into(&mut t.2, &u3);
+            }
+
+            dk.0.extend_from_slice(&t.0);
+            dk.1.extend_from_slice(&t.1);
+            dk.2.extend_from_slice(&t.2);
+        }
+
+        (
+            key_material_words(&dk.0[..self.output_len]),
+            key_material_words(&dk.1[..self.output_len]),
+            key_material_words(&dk.2[..self.output_len]),
+        )
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let password_p = p.view.input.clone();
+        let password_p_next = p_next.view.input.clone();
+
+        let (k, k_next) = mpc_key_block_verify((&password_p, &password_p_next), p, p_next)?;
+        let pads = mpc_hmac_pads_verify((&k, &k_next), p, p_next)?;
+
+        let mut dk = (vec![], vec![]);
+        for i in 1..=self.num_of_blocks() {
+            let message = self.first_message(i);
+            let (mut u, mut u_next) =
+                mpc_hmac_sha256_verify(&pads, mpc_public_verify(message.as_slice()), p, p_next)?;
+            let mut t = (u.to_bytes().to_vec(), u_next.to_bytes().to_vec());
+
+            for _ in 1..self.iterations {
+                (u, u_next) =
+                    mpc_hmac_sha256_verify(&pads, (&u.to_bytes(), &u_next.to_bytes()), p, p_next)?;
+                xor_into(&mut t.0, &u);
+                xor_into(&mut t.1, &u_next);
+            }
+
+            dk.0.extend_from_slice(&t.0);
+            dk.1.extend_from_slice(&t.1);
+        }
+
+        Ok((
+            key_material_words(&dk.0[..self.output_len]),
+            key_material_words(&dk.1[..self.output_len]),
+        ))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"pbkdf2-hmac-sha256".as_slice(),
+            &(self.password_len as u64).to_le_bytes(),
+            &(self.salt.len() as u64).to_le_bytes(),
+            &self.salt,
+            &self.iterations.to_le_bytes(),
+            &(self.output_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.password_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        self.output_len.div_ceil(4)
+    }
+
+    /// Blocks to hash a long password and to compress its pads once, then for
+    /// every block of the derived key the HMAC of salt || i, and the HMACs of
+    /// the 32 byte U(j - 1) for the remaining iterations.
+    fn num_of_mul_gates(&self) -> usize {
+        let first = inner_blocks(self.salt.len() + 4) + 1;
+        let next = inner_blocks(HASH_LEN) + 1;
+        let per_block = first + (self.iterations as usize - 1) * next;
+
+        MUL_GATES_PER_BLOCK
+            * (key_hash_blocks(self.password_len) + 2 + self.num_of_blocks() * per_block)
+    }
+}
+
+#[cfg(test)]
+mod test_pbkdf2_circuit {
+    use super::Pbkdf2Sha256Circuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::{
+            sha256::MUL_GATES_PER_BLOCK,
+            test_utils::{check_full_run, check_zkbpp, verify},
+        },
+        error::Error,
+    };
+
+    /// Password, salt, iteration count and derived key
+    type TestVector = (&'static [u8], &'static [u8], u32, &'static [u8]);
+
+    /// From RFC 7914 for the first, and computed with a reference
+    /// implementation for the others
+    const TEST_VECTORS: [TestVector; 3] = [
+        (
+            b"passwd",
+            b"salt",
+            1,
+            &[
+                0x55, 0xac, 0x04, 0x6e, 0x56, 0xe3, 0x08, 0x9f, 0xec, 0x16, 0x91, 0xc2, 0x25, 0x44,
+                0xb6, 0x05, 0xf9, 0x41, 0x85, 0x21, 0x6d, 0xde, 0x04, 0x65, 0xe6, 0x8b, 0x9d, 0x57,
+                0xc2, 0x0d, 0xac, 0xbc, 0x49, 0xca, 0x9c, 0xcc, 0xf1, 0x79, 0xb6, 0x45, 0x99, 0x16,
+                0x64, 0xb3, 0x9d, 0x77, 0xef, 0x31, 0x7c, 0x71, 0xb8, 0x45, 0xb1, 0xe3, 0x0b, 0xd5,
+                0x09, 0x11, 0x20, 0x41, 0xd3, 0xa1, 0x97, 0x83,
+            ],
+        ),
+        (
+            b"password",
+            b"salt",
+            2,
+            &[
+                0xae, 0x4d, 0x0c, 0x95, 0xaf, 0x6b, 0x46, 0xd3, 0x2d, 0x0a, 0xdf, 0xf9, 0x28, 0xf0,
+                0x6d, 0xd0, 0x2a, 0x30, 0x3f, 0x8e, 0xf3, 0xc2, 0x51, 0xdf, 0xd6, 0xe2, 0xd8, 0x5a,
+                0x95, 0x47, 0x4c, 0x43,
+            ],
+        ),
+        (
+            b"passwordPASSWORDpassword",
+            b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+            3,
+            &[
+                0x32, 0x56, 0x51, 0xa5, 0xca, 0x81, 0x8d, 0x11, 0xf4, 0x33, 0x1c, 0xb0, 0xc3, 0x00,
+                0xd6, 0xf8, 0xb6, 0x87, 0x90, 0xc7, 0x5a, 0x09, 0xeb, 0xad, 0x49, 0x4e, 0x74, 0xb3,
+                0xf6, 0x49, 0x47, 0x58, 0x56, 0xc3, 0x92, 0xe0, 0x3e, 0x00, 0x70, 0x5f,
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_compute() {
+        for (password, salt, iterations, dk) in TEST_VECTORS {
+            let circuit = Pbkdf2Sha256Circuit::new(password.len(), salt, iterations, dk.len());
+            assert_eq!(circuit.compute(password), circuit.output_from_key(dk));
+        }
+
+        // 4096 iterations, from the same reference implementation
+        let circuit = Pbkdf2Sha256Circuit::new(8, b"salt", 4096, 32);
+        let dk = [
+            0xc5, 0xe4, 0x78, 0xd5, 0x92, 0x88, 0xc8, 0x41, 0xaa, 0x53, 0x0d, 0xb6, 0x84, 0x5c,
+            0x4c, 0x8d, 0x96, 0x28, 0x93, 0xa0, 0x01, 0xce, 0x4e, 0x11, 0xa4, 0x96, 0x38, 0x73,
+            0xaa, 0x98, 0x13, 0x4a,
+        ];
+        assert_eq!(circuit.compute(b"password"), circuit.output_from_key(&dk));
+    }
+
+    #[test]
+    fn test_num_of_mul_gates() {
+        // pads, then for each of the 2 blocks one HMAC of a 2 block inner
+        // hash and 9 HMACs of a 1 block inner hash
+        let circuit = Pbkdf2Sha256Circuit::new(8, &[0; 60], 10, 64);
+        assert_eq!(
+            circuit.num_of_mul_gates(),
+            MUL_GATES_PER_BLOCK * (2 + 2 * (3 + 9 * 2))
+        );
+    }
+
+    #[test]
+    fn test_full_run() {
+        for (password, salt, iterations, dk) in TEST_VECTORS {
+            let circuit = Pbkdf2Sha256Circuit::new(password.len(), salt, iterations, dk.len());
+            let output = circuit.output_from_key(dk);
+            let proof = check_full_run(&circuit, password, &output);
+
+            // the iteration count is part of the statement
+            assert!(matches!(
+                verify(
+                    &proof,
+                    &Pbkdf2Sha256Circuit::new(password.len(), salt, iterations + 1, dk.len()),
+                    &output
+                ),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let (password, salt, iterations, dk) = TEST_VECTORS[1];
+        let circuit = Pbkdf2Sha256Circuit::new(password.len(), salt, iterations, dk.len());
+        check_zkbpp(&circuit, password, &circuit.output_from_key(dk));
+    }
+}
@@ -0,0 +1,373 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    circuits::sha256::MUL_GATES_PER_BLOCK as SHA256_MUL_GATES_PER_BLOCK,
+    error::Error,
+    gadgets::{
+        mpc_public, mpc_public_verify,
+        ripemd160::{
+            init_iv, mpc_ripemd160_block, mpc_ripemd160_block_verify, padding, ripemd160_block,
+            State,
+        },
+        sha256,
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Mul gates of one block: one for each of the 128 steps that do not use f1,
+/// 4 additions for each of the 160 steps but the 32 whose constant is 0, and
+/// 10 to combine the two lines into the state.
+pub const MUL_GATES_PER_BLOCK: usize = 128 + 4 * 160 - 32 + 10;
+
+/// Proves knowledge of `x` such that RIPEMD-160(x) = y, where y is the public
+/// output read as little-endian words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ripemd160Circuit {
+    message_len: usize,
+}
+
+impl Ripemd160Circuit {
+    pub fn new(message_len: usize) -> Self {
+        Self { message_len }
+    }
+
+    pub fn message_len(&self) -> usize {
+        self.message_len
+    }
+
+    /// Number of blocks once padded, which appends at least 9 bytes.
+    pub fn num_of_blocks(&self) -> usize {
+        (self.message_len + 9).div_ceil(64)
+    }
+
+    /// Public output of the circuit for a `digest` y.
+    pub fn output_from_digest(digest: &[u8; 20]) -> Output<u32> {
+        State::from_bytes(digest).to_vec()
+    }
+}
+
+impl Circuit<u32> for Ripemd160Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.message_len);
+
+        let mut state = init_iv();
+        for block in padding(input).chunks(16) {
+            state = ripemd160_block(&block.try_into().unwrap(), &state);
+        }
+
+        state.to_vec()
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let p1_words = padding(&p1.view.input);
+        let p2_words = padding(&p2.view.input);
+        let p3_words = padding(&p3.view.input);
+
+        let mut state = mpc_public(init_iv());
+
+        let iter_chunks = p1_words
+            .chunks(16)
+            .zip(p2_words.chunks(16))
+            .zip(p3_words.chunks(16));
+
+        for ((p1_words, p2_words), p3_words) in iter_chunks {
+            state = mpc_ripemd160_block(
+                &p1_words.try_into().unwrap(),
+                &p2_words.try_into().unwrap(),
+                &p3_words.try_into().unwrap(),
+                &state,
+                p1,
+                p2,
+                p3,
+            );
+        }
+
+        (state.0.to_vec(), state.1.to_vec(), state.2.to_vec())
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let p_words = padding(&p.view.input);
+        let p_next_words = padding(&p_next.view.input);
+
+        let mut state = mpc_public_verify(init_iv());
+
+        for (p_words, p_next_words) in p_words.chunks(16).zip(p_next_words.chunks(16)) {
+            state = mpc_ripemd160_block_verify(
+                &p_words.try_into().unwrap(),
+                &p_next_words.try_into().unwrap(),
+                &state,
+                p,
+                p_next,
+            )?;
+        }
+
+        Ok((state.0.to_vec(), state.1.to_vec()))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"ripemd160".as_slice(),
+            &(self.message_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.message_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        5
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_BLOCK * self.num_of_blocks()
+    }
+}
+
+/// Proves knowledge of `x` such that RIPEMD-160(SHA-256(x)) = y, the HASH160
+/// of Bitcoin addresses, where y is the public output read as little-endian
+/// words.
+///
+/// The SHA-256 digest stays secret: its big-endian words are byte swapped in
+/// each share into the first little-endian words of the single RIPEMD-160
+/// block, whose padding is public.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hash160Circuit {
+    message_len: usize,
+}
+
+impl Hash160Circuit {
+    pub fn new(message_len: usize) -> Self {
+        Self { message_len }
+    }
+
+    pub fn message_len(&self) -> usize {
+        self.message_len
+    }
+
+    /// Number of SHA-256 blocks once padded, which appends at least 9 bytes.
+    pub fn num_of_sha256_blocks(&self) -> usize {
+        (self.message_len + 9).div_ceil(64)
+    }
+
+    /// Public output of the circuit for a `digest` y.
+    pub fn output_from_digest(digest: &[u8; 20]) -> Output<u32> {
+        Ripemd160Circuit::output_from_digest(digest)
+    }
+}
+
+/// The RIPEMD-160 block of a share of a SHA-256 digest, padded to 32 bytes.
+fn ripemd160_input(digest: &sha256::State) -> [GF2Word<u32>; 16] {
+    let mut block = [GF2Word::from(0u32); 16];
+    for (word, h) in block.iter_mut().zip(digest.to_vec()) {
+        *word = h.value.swap_bytes().into();
+    }
+    block[8] = 0x80.into();
+    block[14] = (8 * 32).into();
+    block
+}
+
+impl Circuit<u32> for Hash160Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.message_len);
+
+        let iv: sha256::State = sha256::init_iv().to_vec().into();
+        let digest = sha256::sha256_blocks(&sha256::padding(input), &iv);
+
+        ripemd160_block(&ripemd160_input(&digest), &init_iv()).to_vec()
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let p1_words = sha256::padding(&p1.view.input);
+        let p2_words = sha256::padding(&p2.view.input);
+        let p3_words = sha256::padding(&p3.view.input);
+
+        let iv: sha256::State = sha256::init_iv().to_vec().into();
+        let digest =
+            sha256::mpc_sha256_blocks(&p1_words, &p2_words, &p3_words, &mpc_public(iv), p1, p2, p3);
+
+        let state = mpc_ripemd160_block(
+            &ripemd160_input(&digest.0),
+            &ripemd160_input(&digest.1),
+            &ripemd160_input(&digest.2),
+            &mpc_public(init_iv()),
+            p1,
+            p2,
+            p3,
+        );
+
+        (state.0.to_vec(), state.1.to_vec(), state.2.to_vec())
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let p_words = sha256::padding(&p.view.input);
+        let p_next_words = sha256::padding(&p_next.view.input);
+
+        let iv: sha256::State = sha256::init_iv().to_vec().into();
+        let digest = sha256::mpc_sha256_blocks_verify(
+            &p_words,
+            &p_next_words,
+            &mpc_public_verify(iv),
+            p,
+            p_next,
+        )?;
+
+        let state = mpc_ripemd160_block_verify(
+            &ripemd160_input(&digest.0),
+            &ripemd160_input(&digest.1),
+            &mpc_public_verify(init_iv()),
+            p,
+            p_next,
+        )?;
+
+        Ok((state.0.to_vec(), state.1.to_vec()))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"hash160".as_slice(),
+            &(self.message_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.message_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        5
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        SHA256_MUL_GATES_PER_BLOCK * self.num_of_sha256_blocks() + MUL_GATES_PER_BLOCK
+    }
+}
+
+#[cfg(test)]
+mod test_ripemd160_circuit {
+    use super::{Hash160Circuit, Ripemd160Circuit};
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+    };
+
+    /// Examples of the RIPEMD-160 authors
+    const TEST_VECTORS: [(&[u8], [u8; 20]); 3] = [
+        (
+            b"",
+            [
+                0x9c, 0x11, 0x85, 0xa5, 0xc5, 0xe9, 0xfc, 0x54, 0x61, 0x28, 0x08, 0x97, 0x7e, 0xe8,
+                0xf5, 0x48, 0xb2, 0x25, 0x8d, 0x31,
+            ],
+        ),
+        (
+            b"abc",
+            [
+                0x8e, 0xb2, 0x08, 0xf7, 0xe0, 0x5d, 0x98, 0x7a, 0x9b, 0x04, 0x4a, 0x8e, 0x98, 0xc6,
+                0xb0, 0x87, 0xf1, 0x5a, 0x0b, 0xfc,
+            ],
+        ),
+        (
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            [
+                0x12, 0xa0, 0x53, 0x38, 0x4a, 0x9c, 0x0c, 0x88, 0xe4, 0x05, 0xa0, 0x6c, 0x27, 0xdc,
+                0xf4, 0x9a, 0xda, 0x62, 0xeb, 0x2b,
+            ],
+        ),
+    ];
+
+    /// A compressed public key and its HASH160, from the Bitcoin wiki
+    const PUBKEY: [u8; 33] = [
+        0x02, 0x50, 0x86, 0x3a, 0xd6, 0x4a, 0x87, 0xae, 0x8a, 0x2f, 0xe8, 0x3c, 0x1a, 0xf1, 0xa8,
+        0x40, 0x3c, 0xb5, 0x3f, 0x53, 0xe4, 0x86, 0xd8, 0x51, 0x1d, 0xad, 0x8a, 0x04, 0x88, 0x7e,
+        0x5b, 0x23, 0x52,
+    ];
+    const PUBKEY_HASH160: [u8; 20] = [
+        0xf5, 0x4a, 0x58, 0x51, 0xe9, 0x37, 0x2b, 0x87, 0x81, 0x0a, 0x8e, 0x60, 0xcd, 0xd2, 0xe7,
+        0xcf, 0xd8, 0x0b, 0x6e, 0x31,
+    ];
+
+    #[test]
+    fn test_compute() {
+        for (message, digest) in TEST_VECTORS {
+            let circuit = Ripemd160Circuit::new(message.len());
+            assert_eq!(
+                circuit.compute(message),
+                Ripemd160Circuit::output_from_digest(&digest)
+            );
+        }
+
+        let circuit = Hash160Circuit::new(PUBKEY.len());
+        assert_eq!(
+            circuit.compute(&PUBKEY),
+            Hash160Circuit::output_from_digest(&PUBKEY_HASH160)
+        );
+    }
+
+    #[test]
+    fn test_full_run() {
+        for (preimage, digest) in TEST_VECTORS {
+            let circuit = Ripemd160Circuit::new(preimage.len());
+            let output = Ripemd160Circuit::output_from_digest(&digest);
+            let proof = check_full_run(&circuit, preimage, &output);
+
+            // the message length is part of the statement
+            assert!(matches!(
+                verify(&proof, &Ripemd160Circuit::new(preimage.len() + 1), &output),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_hash160() {
+        let circuit = Hash160Circuit::new(PUBKEY.len());
+        let output = Hash160Circuit::output_from_digest(&PUBKEY_HASH160);
+        let proof = check_full_run(&circuit, &PUBKEY, &output);
+
+        // a proof of HASH160 is not one of RIPEMD-160 of the same message
+        assert!(matches!(
+            verify(&proof, &Ripemd160Circuit::new(PUBKEY.len()), &output),
+            Err(Error::CircuitMismatchError)
+        ));
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let circuit = Hash160Circuit::new(PUBKEY.len());
+        check_zkbpp(
+            &circuit,
+            &PUBKEY,
+            &Hash160Circuit::output_from_digest(&PUBKEY_HASH160),
+        );
+    }
+}
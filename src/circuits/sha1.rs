@@ -0,0 +1,200 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::{
+        mpc_public, mpc_public_verify,
+        sha1::{init_iv, mpc_sha1_block, mpc_sha1_block_verify, padding, sha1_block, State},
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Mul gates of one block: one for each of the 20 Ch and 20 Maj rounds, 4
+/// additions for each of the 80 rounds and 5 to update the state.
+pub const MUL_GATES_PER_BLOCK: usize = 2 * 20 + 4 * 80 + 5;
+
+/// Proves knowledge of `x` such that SHA-1(x) = y, where y is the public output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sha1Circuit {
+    message_len: usize,
+}
+
+impl Sha1Circuit {
+    pub fn new(message_len: usize) -> Self {
+        Self { message_len }
+    }
+
+    pub fn message_len(&self) -> usize {
+        self.message_len
+    }
+
+    /// Number of blocks once padded, which appends at least 9 bytes.
+    pub fn num_of_blocks(&self) -> usize {
+        (self.message_len + 9).div_ceil(64)
+    }
+
+    /// Public output of the circuit for a `digest` y.
+    pub fn output_from_digest(digest: &[u8; 20]) -> Output<u32> {
+        State::from_bytes(digest).to_vec()
+    }
+}
+
+impl Circuit<u32> for Sha1Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.message_len);
+
+        let mut state = init_iv();
+        for block in padding(input).chunks(16) {
+            state = sha1_block(&block.try_into().unwrap(), &state);
+        }
+
+        state.to_vec()
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let p1_words = padding(&p1.view.input);
+        let p2_words = padding(&p2.view.input);
+        let p3_words = padding(&p3.view.input);
+
+        let mut state = mpc_public(init_iv());
+
+        let iter_chunks = p1_words
+            .chunks(16)
+            .zip(p2_words.chunks(16))
+            .zip(p3_words.chunks(16));
+
+        for ((p1_words, p2_words), p3_words) in iter_chunks {
+            state = mpc_sha1_block(
+                &p1_words.try_into().unwrap(),
+                &p2_words.try_into().unwrap(),
+                &p3_words.try_into().unwrap(),
+                &state,
+                p1,
+                p2,
+                p3,
+            );
+        }
+
+        (state.0.to_vec(), state.1.to_vec(), state.2.to_vec())
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let p_words = padding(&p.view.input);
+        let p_next_words = padding(&p_next.view.input);
+
+        let mut state = mpc_public_verify(init_iv());
+
+        for (p_words, p_next_words) in p_words.chunks(16).zip(p_next_words.chunks(16)) {
+            state = mpc_sha1_block_verify(
+                &p_words.try_into().unwrap(),
+                &p_next_words.try_into().unwrap(),
+                &state,
+                p,
+                p_next,
+            )?;
+        }
+
+        Ok((state.0.to_vec(), state.1.to_vec()))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [b"sha1".as_slice(), &(self.message_len as u64).to_le_bytes()].concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.message_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        5
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_BLOCK * self.num_of_blocks()
+    }
+}
+
+#[cfg(test)]
+mod test_sha1_circuit {
+    use super::Sha1Circuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+    };
+
+    /// FIPS 180 examples, and the 112 byte message of the SHA-512 examples
+    const TEST_VECTORS: [(&[u8], [u8; 20]); 3] = [
+        (
+            b"abc",
+            [
+                0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e, 0x25, 0x71, 0x78, 0x50,
+                0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d,
+            ],
+        ),
+        (
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            [
+                0x84, 0x98, 0x3e, 0x44, 0x1c, 0x3b, 0xd2, 0x6e, 0xba, 0xae, 0x4a, 0xa1, 0xf9, 0x51,
+                0x29, 0xe5, 0xe5, 0x46, 0x70, 0xf1,
+            ],
+        ),
+        (
+            b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+            [
+                0xa4, 0x9b, 0x24, 0x46, 0xa0, 0x2c, 0x64, 0x5b, 0xf4, 0x19, 0xf9, 0x95, 0xb6, 0x70,
+                0x91, 0x25, 0x3a, 0x04, 0xa2, 0x59,
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_compute() {
+        for (message, digest) in TEST_VECTORS {
+            let circuit = Sha1Circuit::new(message.len());
+            assert_eq!(
+                circuit.compute(message),
+                Sha1Circuit::output_from_digest(&digest)
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_run() {
+        for (preimage, digest) in TEST_VECTORS {
+            let circuit = Sha1Circuit::new(preimage.len());
+            let output = Sha1Circuit::output_from_digest(&digest);
+            let proof = check_full_run(&circuit, preimage, &output);
+
+            // the message length is part of the statement
+            assert!(matches!(
+                verify(&proof, &Sha1Circuit::new(preimage.len() + 1), &output),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let (preimage, digest) = TEST_VECTORS[0];
+        let circuit = Sha1Circuit::new(preimage.len());
+        check_zkbpp(
+            &circuit,
+            preimage,
+            &Sha1Circuit::output_from_digest(&digest),
+        );
+    }
+}
@@ -0,0 +1,1052 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::{
+        mpc_public, mpc_public_verify,
+        sha256::{
+            init_iv, init_iv_224, mpc_secret_padding, mpc_secret_padding_verify, mpc_select_state,
+            mpc_select_state_verify, mpc_sha256_block, mpc_sha256_block_from_midstate,
+            mpc_sha256_block_from_midstate_verify, mpc_sha256_block_verify, mpc_sha256_blocks,
+            mpc_sha256_blocks_verify, padding, padding_after, sha256_block, sha256_blocks, State,
+        },
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Mul gates of one block: 3 additions for each of the 48 extended words of
+/// the message schedule, 9 per round of compression and 8 to update the state.
+pub const MUL_GATES_PER_BLOCK: usize = 3 * 48 + 9 * 64 + 8;
+
+/// A run of bytes of the hashed message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Segment {
+    /// Bytes known to the verifier, bound into the statement
+    Public(Vec<u8>),
+    /// The given number of bytes taken from the witness
+    Secret(usize),
+}
+
+/// Proves knowledge of `x` such that SHA-256(m) = y, where the message `m`
+/// interleaves public segments with the secret segments of the witness `x`,
+/// and `y` is the public output.
+///
+/// Public bytes are not part of the witness, so they do not grow the proof.
+/// Blocks before the first secret byte are compressed in the clear. Hashing
+/// may also resume from a public midstate, after a prefix that the verifier
+/// only knows the compression of. SHA-224 is the same circuit from another
+/// initial value, with y truncated to 7 words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sha256Circuit {
+    midstate: State,
+    /// Bytes compressed into `midstate`, a multiple of the block size
+    compressed_len: usize,
+    segments: Vec<Segment>,
+    /// Leading words of the final state in the output
+    output_words: usize,
+}
+
+impl Sha256Circuit {
+    /// A circuit for a fully secret message of `message_len` bytes.
+    pub fn new(message_len: usize) -> Self {
+        Self::from_segments(vec![Segment::Secret(message_len)])
+    }
+
+    /// A circuit for `prefix || x || suffix`, where `x` has `secret_len` bytes.
+    pub fn with_public_parts(prefix: &[u8], secret_len: usize, suffix: &[u8]) -> Self {
+        Self::from_segments(vec![
+            Segment::Public(prefix.to_vec()),
+            Segment::Secret(secret_len),
+            Segment::Public(suffix.to_vec()),
+        ])
+    }
+
+    pub fn from_segments(segments: Vec<Segment>) -> Self {
+        Self::from_midstate(init_iv().to_vec().into(), 0, segments)
+    }
+
+    /// A SHA-224 circuit for a fully secret message of `message_len` bytes.
+    pub fn sha224(message_len: usize) -> Self {
+        Self {
+            output_words: 7,
+            ..Self::from_midstate(
+                init_iv_224().to_vec().into(),
+                0,
+                vec![Segment::Secret(message_len)],
+            )
+        }
+    }
+
+    /// A circuit for a message whose first `compressed_len` bytes are already
+    /// compressed into `midstate`, followed by `segments`.
+    pub fn from_midstate(midstate: State, compressed_len: usize, segments: Vec<Segment>) -> Self {
+        assert!(
+            compressed_len.is_multiple_of(64),
+            "a midstate covers whole blocks"
+        );
+        Self {
+            midstate,
+            compressed_len,
+            segments,
+            output_words: 8,
+        }
+    }
+
+    pub fn midstate(&self) -> &State {
+        &self.midstate
+    }
+
+    pub fn compressed_len(&self) -> usize {
+        self.compressed_len
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    pub fn output_words(&self) -> usize {
+        self.output_words
+    }
+
+    /// Length of the segments, without the bytes compressed into the midstate.
+    pub fn message_len(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Public(bytes) => bytes.len(),
+                Segment::Secret(len) => *len,
+            })
+            .sum()
+    }
+
+    pub fn secret_len(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Public(_) => 0,
+                Segment::Secret(len) => *len,
+            })
+            .sum()
+    }
+
+    /// Number of blocks after the midstate once padded, which appends at least
+    /// 9 bytes.
+    pub fn num_of_blocks(&self) -> usize {
+        (self.message_len() + 9).div_ceil(64)
+    }
+
+    /// Number of leading blocks without secret bytes.
+    pub fn num_of_public_blocks(&self) -> usize {
+        let mut public_len = 0;
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Public(bytes) => public_len += bytes.len(),
+                Segment::Secret(0) => {}
+                Segment::Secret(_) => return public_len / 64,
+            }
+        }
+
+        // without secret bytes only the blocks holding the padding remain
+        public_len / 64
+    }
+
+    /// Public output of the circuit for a `digest` y.
+    pub fn output_from_digest(digest: &[u8; 32]) -> Output<u32> {
+        State::from_bytes(digest).to_vec()
+    }
+
+    /// Public output of a SHA-224 circuit for a `digest` y.
+    pub fn output_from_digest_224(digest: &[u8; 28]) -> Output<u32> {
+        digest
+            .chunks(4)
+            .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()).into())
+            .collect()
+    }
+
+    fn truncate(&self, state: State) -> Output<u32> {
+        let mut output = state.to_vec();
+        output.truncate(self.output_words);
+        output
+    }
+
+    /// Fill the secret segments with `secret`, which is either the witness or
+    /// a share of it, and pad the resulting message.
+    fn padded_message(&self, secret: &[u8]) -> Vec<GF2Word<u32>> {
+        let mut message = Vec::with_capacity(self.message_len());
+        let mut offset = 0;
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Public(bytes) => message.extend_from_slice(bytes),
+                Segment::Secret(len) => {
+                    message.extend_from_slice(&secret[offset..offset + len]);
+                    offset += len;
+                }
+            }
+        }
+
+        padding_after(&message, self.compressed_len)
+    }
+
+    /// Compress the public blocks of `secret`'s padded message in the clear,
+    /// returning the state and the words left to compress.
+    fn skip_public_blocks(&self, secret: &[u8]) -> (State, Vec<GF2Word<u32>>) {
+        let mut words = self.padded_message(secret);
+        let secret_words = words.split_off(16 * self.num_of_public_blocks());
+
+        (sha256_blocks(&words, &self.midstate), secret_words)
+    }
+}
+
+impl Circuit<u32> for Sha256Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), self.secret_len());
+
+        self.truncate(sha256_blocks(&self.padded_message(input), &self.midstate))
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let (p1_state, p1_words) = self.skip_public_blocks(&p1.view.input);
+        let (p2_state, p2_words) = self.skip_public_blocks(&p2.view.input);
+        let (p3_state, p3_words) = self.skip_public_blocks(&p3.view.input);
+
+        let (p1_state, p2_state, p3_state) = mpc_sha256_blocks(
+            &p1_words,
+            &p2_words,
+            &p3_words,
+            &(p1_state, p2_state, p3_state),
+            p1,
+            p2,
+            p3,
+        );
+
+        (
+            self.truncate(p1_state),
+            self.truncate(p2_state),
+            self.truncate(p3_state),
+        )
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let (p_state, p_words) = self.skip_public_blocks(&p.view.input);
+        let (p_next_state, p_next_words) = self.skip_public_blocks(&p_next.view.input);
+
+        let (p_state, p_next_state) =
+            mpc_sha256_blocks_verify(&p_words, &p_next_words, &(p_state, p_next_state), p, p_next)?;
+
+        Ok((self.truncate(p_state), self.truncate(p_next_state)))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        let segments = bincode::serialize(&self.segments).expect("segments are serializable");
+        [
+            b"sha256".as_slice(),
+            &self.midstate.to_bytes(),
+            &(self.compressed_len as u64).to_le_bytes(),
+            &(self.output_words as u64).to_le_bytes(),
+            &segments,
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.secret_len()
+    }
+
+    fn party_output_len(&self) -> usize {
+        self.output_words
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_BLOCK * (self.num_of_blocks() - self.num_of_public_blocks())
+    }
+}
+
+/// Proves knowledge of a block `x` such that compressing it into the public
+/// chaining value `h` gives y, the building block of Merkle-Damgård hashes.
+/// The witness is the 64 byte block, read as big-endian words, and y includes
+/// the final addition of `h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sha256CompressionCircuit {
+    chaining_value: State,
+}
+
+impl Sha256CompressionCircuit {
+    pub fn new(chaining_value: State) -> Self {
+        Self { chaining_value }
+    }
+
+    /// A circuit starting from the initial hash value of SHA-256.
+    pub fn with_iv() -> Self {
+        Self::new(init_iv().to_vec().into())
+    }
+
+    pub fn chaining_value(&self) -> &State {
+        &self.chaining_value
+    }
+
+    fn block_words(block: &[u8]) -> [GF2Word<u32>; 16] {
+        block
+            .chunks(4)
+            .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()).into())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+}
+
+impl Circuit<u32> for Sha256CompressionCircuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), 64);
+        sha256_block(&Self::block_words(input), &self.chaining_value).to_vec()
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let (p1_state, p2_state, p3_state) = mpc_sha256_block_from_midstate(
+            &Self::block_words(&p1.view.input),
+            &Self::block_words(&p2.view.input),
+            &Self::block_words(&p3.view.input),
+            &self.chaining_value,
+            p1,
+            p2,
+            p3,
+        );
+
+        (p1_state.to_vec(), p2_state.to_vec(), p3_state.to_vec())
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let (p_state, p_next_state) = mpc_sha256_block_from_midstate_verify(
+            &Self::block_words(&p.view.input),
+            &Self::block_words(&p_next.view.input),
+            &self.chaining_value,
+            p,
+            p_next,
+        )?;
+
+        Ok((p_state.to_vec(), p_next_state.to_vec()))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"sha256/compression".as_slice(),
+            &self.chaining_value.to_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        16
+    }
+
+    fn party_output_len(&self) -> usize {
+        8
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_BLOCK
+    }
+}
+
+/// Proves knowledge of `x` such that SHA-256(x) = y, where only an upper
+/// bound `max_len` on the length of `x` is public. The witness is the length
+/// of `x` as 2 big-endian bytes followed by `x` in a buffer of `max_len`
+/// bytes, see `witness`. The padding is computed in the MPC from the secret
+/// length and the digest is selected among the states after every block the
+/// buffer may need. Lengths above `max_len` give an all zero output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sha256HiddenLenCircuit {
+    max_len: usize,
+}
+
+impl Sha256HiddenLenCircuit {
+    pub fn new(max_len: usize) -> Self {
+        assert!(max_len <= u16::MAX as usize);
+        Self { max_len }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Encode `message` as the witness of this circuit.
+    pub fn witness(&self, message: &[u8]) -> Vec<u8> {
+        assert!(message.len() <= self.max_len);
+
+        let mut witness = Vec::with_capacity(2 + self.max_len);
+        witness.extend_from_slice(&(message.len() as u16).to_be_bytes());
+        witness.extend_from_slice(message);
+        witness.resize(2 + self.max_len, 0);
+        witness
+    }
+
+    /// Number of blocks of the longest message.
+    pub fn num_of_blocks(&self) -> usize {
+        (self.max_len + 9).div_ceil(64)
+    }
+
+    /// Split a share of the witness into shares of the length and the buffer.
+    fn split_share(share: &[u8]) -> (GF2Word<u32>, &[u8]) {
+        let len = u16::from_be_bytes([share[0], share[1]]) as u32;
+        (len.into(), &share[2..])
+    }
+}
+
+impl Circuit<u32> for Sha256HiddenLenCircuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+        assert_eq!(input.len(), 2 + self.max_len);
+
+        let (len, buffer) = Self::split_share(input);
+        let len = len.value as usize;
+        if len > self.max_len {
+            return vec![0.into(); 8];
+        }
+
+        let mut state: State = init_iv().to_vec().into();
+        for block in padding(&buffer[..len]).chunks(16) {
+            state = sha256_block(&block.try_into().unwrap(), &state);
+        }
+
+        state.to_vec()
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> TwoThreeDecOutput<u32> {
+        let input_p1 = p1.view.input.clone();
+        let input_p2 = p2.view.input.clone();
+        let input_p3 = p3.view.input.clone();
+        let (len_p1, buffer_p1) = Self::split_share(&input_p1);
+        let (len_p2, buffer_p2) = Self::split_share(&input_p2);
+        let (len_p3, buffer_p3) = Self::split_share(&input_p3);
+
+        let (padding_p1, padding_p2, padding_p3) = mpc_secret_padding(
+            (buffer_p1, buffer_p2, buffer_p3),
+            (len_p1, len_p2, len_p3),
+            self.num_of_blocks(),
+            p1,
+            p2,
+            p3,
+        );
+
+        let mut state: (State, State, State) = mpc_public(init_iv().to_vec().into());
+        let mut states = Vec::with_capacity(self.num_of_blocks());
+
+        let iter_chunks = padding_p1
+            .words
+            .chunks(16)
+            .zip(padding_p2.words.chunks(16))
+            .zip(padding_p3.words.chunks(16));
+
+        for ((p1_words, p2_words), p3_words) in iter_chunks {
+            state = mpc_sha256_block(
+                &p1_words.try_into().unwrap(),
+                &p2_words.try_into().unwrap(),
+                &p3_words.try_into().unwrap(),
+                &state,
+                p1,
+                p2,
+                p3,
+            );
+            states.push(state);
+        }
+
+        let (p1_state, p2_state, p3_state) =
+            mpc_select_state(&states, (&padding_p1, &padding_p2, &padding_p3), p1, p2, p3);
+
+        (p1_state.to_vec(), p2_state.to_vec(), p3_state.to_vec())
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Output<u32>, Output<u32>), Error> {
+        let input_p = p.view.input.clone();
+        let input_p_next = p_next.view.input.clone();
+        let (len_p, buffer_p) = Self::split_share(&input_p);
+        let (len_p_next, buffer_p_next) = Self::split_share(&input_p_next);
+
+        let (padding_p, padding_p_next) = mpc_secret_padding_verify(
+            (buffer_p, buffer_p_next),
+            (len_p, len_p_next),
+            self.num_of_blocks(),
+            p,
+            p_next,
+        )?;
+
+        let mut state: (State, State) = mpc_public_verify(init_iv().to_vec().into());
+        let mut states = Vec::with_capacity(self.num_of_blocks());
+
+        let iter_chunks = padding_p
+            .words
+            .chunks(16)
+            .zip(padding_p_next.words.chunks(16));
+
+        for (p_words, p_next_words) in iter_chunks {
+            state = mpc_sha256_block_verify(
+                &p_words.try_into().unwrap(),
+                &p_next_words.try_into().unwrap(),
+                &state,
+                p,
+                p_next,
+            )?;
+            states.push(state);
+        }
+
+        let (p_state, p_next_state) =
+            mpc_select_state_verify(&states, (&padding_p, &padding_p_next), p, p_next)?;
+
+        Ok((p_state.to_vec(), p_next_state.to_vec()))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"sha256/hidden-len".as_slice(),
+            &(self.max_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(4)
+    }
+
+    fn witness_len(&self) -> usize {
+        2 + self.max_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        8
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        let num_of_blocks = self.num_of_blocks();
+
+        // a comparison of the length with every position up to `max_len`,
+        // masking the buffer and placing the length, then selecting the state
+        let padding = (self.max_len + 1) + self.max_len.div_ceil(4) + num_of_blocks;
+        let selection = 8 * num_of_blocks + 8;
+
+        padding + MUL_GATES_PER_BLOCK * num_of_blocks + selection
+    }
+}
+
+#[cfg(test)]
+mod test_sha256_circuit {
+    use rand::{rngs::ThreadRng, thread_rng};
+    use rand_chacha::ChaCha20Rng;
+    use sha2::{Digest, Sha224, Sha256};
+    use sha3::Keccak256;
+
+    use super::{Segment, Sha256Circuit, Sha256CompressionCircuit, Sha256HiddenLenCircuit};
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::check_full_run,
+        data_structures::{Mode, Proof},
+        error::Error,
+        fs::DomainSeparator,
+        gadgets::sha256::{init_iv, padding, sha256_block, test_vectors, State},
+        prover::Prover,
+        security::SecurityParams,
+        verifier::Verifier,
+    };
+
+    #[test]
+    fn test_compute() {
+        // message lengths around the block boundaries
+        for message_len in [0, 3, 55, 56, 63, 64, 119, 120, 200] {
+            let message: Vec<u8> = (0..message_len).map(|i| i as u8).collect();
+            let circuit = Sha256Circuit::new(message_len);
+
+            let digest: [u8; 32] = Sha256::digest(&message).into();
+            assert_eq!(
+                circuit.compute(&message),
+                Sha256Circuit::output_from_digest(&digest)
+            );
+            assert_eq!(
+                circuit.num_of_blocks(),
+                (message_len + 9).div_ceil(64),
+                "{}",
+                message_len
+            );
+        }
+    }
+
+    #[test]
+    fn test_sha224() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+
+        for (preimage, expected_output) in [
+            (b"abc".as_slice(), test_vectors::short::DIGEST_OUTPUT_224),
+            (
+                b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                test_vectors::long::DIGEST_OUTPUT_224,
+            ),
+        ] {
+            let circuit = Sha256Circuit::sha224(preimage.len());
+            let output = circuit.compute(preimage);
+            assert_eq!(output.len(), 7);
+            for (&word, &expected_word) in output.iter().zip(expected_output.iter()) {
+                assert_eq!(word.value, expected_word);
+            }
+
+            let digest: [u8; 28] = Sha224::digest(preimage).into();
+            assert_eq!(output, Sha256Circuit::output_from_digest_224(&digest));
+
+            let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+                &mut rng, preimage, &circuit, &output, &params, &dst,
+            )
+            .unwrap();
+            Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                &proof, &circuit, &output, &params, &dst,
+            )
+            .unwrap();
+
+            // SHA-224 and SHA-256 of the same length are different statements
+            assert!(matches!(
+                Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                    &proof,
+                    &Sha256Circuit::new(preimage.len()),
+                    &output,
+                    &params,
+                    &dst
+                ),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_compute_segments() {
+        let circuit = Sha256Circuit::from_segments(vec![
+            Segment::Public(b"nonce".to_vec()),
+            Segment::Secret(3),
+            Segment::Public(vec![0xff; 100]),
+            Segment::Secret(61),
+        ]);
+        assert_eq!(circuit.message_len(), 169);
+        assert_eq!(circuit.secret_len(), 64);
+        assert_eq!(circuit.num_of_public_blocks(), 0);
+
+        let secret = [7u8; 64];
+        let message = [
+            b"nonce".as_slice(),
+            &secret[..3],
+            &[0xff; 100],
+            &secret[3..],
+        ]
+        .concat();
+        let digest: [u8; 32] = Sha256::digest(&message).into();
+        assert_eq!(
+            circuit.compute(&secret),
+            Sha256Circuit::output_from_digest(&digest)
+        );
+    }
+
+    #[test]
+    fn test_public_parts() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+
+        let secret = b"correct horse battery";
+        let suffix = b"zkboo/tests/context";
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+
+        let mut proof_lens = vec![];
+        for prefix in [[0x11u8; 64].as_slice(), &[0x22u8; 200]] {
+            let circuit = Sha256Circuit::with_public_parts(prefix, secret.len(), suffix);
+            assert_eq!(circuit.num_of_blocks() - circuit.num_of_public_blocks(), 1);
+
+            let message = [prefix, secret.as_slice(), suffix].concat();
+            let digest: [u8; 32] = Sha256::digest(&message).into();
+            let output = Sha256Circuit::output_from_digest(&digest);
+            assert_eq!(circuit.compute(secret), output);
+
+            let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+                &mut rng, secret, &circuit, &output, &params, &dst,
+            )
+            .unwrap();
+            Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                &proof, &circuit, &output, &params, &dst,
+            )
+            .unwrap();
+
+            // public bytes are part of the statement
+            let mut other_suffix = suffix.to_vec();
+            other_suffix[0] ^= 1;
+            let other_circuit =
+                Sha256Circuit::with_public_parts(prefix, secret.len(), &other_suffix);
+            assert!(matches!(
+                Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                    &proof,
+                    &other_circuit,
+                    &output,
+                    &params,
+                    &dst
+                ),
+                Err(Error::CircuitMismatchError)
+            ));
+
+            // only the secret is shared
+            assert!(proof
+                .party_inputs
+                .iter()
+                .all(|input| input.len() == secret.len()));
+            proof_lens.push(proof.to_bytes().unwrap().len());
+        }
+
+        // blocks made only of public bytes are not proven
+        assert_eq!(proof_lens[0], proof_lens[1]);
+    }
+
+    #[test]
+    fn test_midstate() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+
+        // the verifier only knows the compression of the first block
+        let first_block = [0x61u8; 64];
+        let midstate = sha256_block(
+            &padding(&first_block)[..16].try_into().unwrap(),
+            &init_iv().to_vec().into(),
+        );
+
+        let secret = b"midstate secret";
+        let segments = vec![
+            Segment::Secret(secret.len()),
+            Segment::Public(b"suffix".to_vec()),
+        ];
+        let circuit = Sha256Circuit::from_midstate(midstate, 64, segments.clone());
+        assert_eq!(circuit.num_of_blocks(), 1);
+
+        let message = [first_block.as_slice(), secret, b"suffix"].concat();
+        let digest: [u8; 32] = Sha256::digest(&message).into();
+        let output = Sha256Circuit::output_from_digest(&digest);
+        assert_eq!(circuit.compute(secret), output);
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, secret, &circuit, &output, &params, &dst,
+        )
+        .unwrap();
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
+
+        // the midstate and its length are part of the statement
+        for other_circuit in [
+            Sha256Circuit::from_midstate(init_iv().to_vec().into(), 64, segments.clone()),
+            Sha256Circuit::from_midstate(midstate, 128, segments),
+        ] {
+            assert!(matches!(
+                Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                    &proof,
+                    &other_circuit,
+                    &output,
+                    &params,
+                    &dst
+                ),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_compression() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+
+        // a single padded block compressed from the IV is the digest
+        let block: Vec<u8> = test_vectors::short::TEST_INPUT
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect();
+        let circuit = Sha256CompressionCircuit::with_iv();
+        let output = circuit.compute(&block);
+        for (&word, &expected_word) in output.iter().zip(test_vectors::short::DIGEST_OUTPUT.iter())
+        {
+            assert_eq!(word.value, expected_word);
+        }
+
+        // chaining the compression of two blocks, through the encoding of the
+        // chaining value
+        let first_block = [0x62u8; 64];
+        let chaining_value = State::from_bytes(&State::to_bytes(&sha256_block(
+            &padding(&first_block)[..16].try_into().unwrap(),
+            &init_iv().to_vec().into(),
+        )));
+        let mut second_block = b"the second block".to_vec();
+        second_block.push(0x80);
+        second_block.resize(56, 0);
+        second_block.extend_from_slice(&(8 * (64 + 16) as u64).to_be_bytes());
+
+        let circuit = Sha256CompressionCircuit::new(chaining_value);
+        let output = circuit.compute(&second_block);
+        let digest: [u8; 32] =
+            Sha256::digest([first_block.as_slice(), b"the second block"].concat()).into();
+        assert_eq!(output, Sha256Circuit::output_from_digest(&digest));
+
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng,
+            &second_block,
+            &circuit,
+            &output,
+            &params,
+            &dst,
+        )
+        .unwrap();
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
+
+        assert!(matches!(
+            Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                &proof,
+                &Sha256CompressionCircuit::with_iv(),
+                &output,
+                &params,
+                &dst
+            ),
+            Err(Error::CircuitMismatchError)
+        ));
+    }
+
+    #[test]
+    fn test_hidden_len_compute() {
+        let circuit = Sha256HiddenLenCircuit::new(130);
+        assert_eq!(circuit.num_of_blocks(), 3);
+
+        for len in [0, 1, 55, 56, 63, 64, 119, 120, 130] {
+            let message: Vec<u8> = (0..len).map(|i| (3 * i) as u8).collect();
+            let digest: [u8; 32] = Sha256::digest(&message).into();
+            assert_eq!(
+                circuit.compute(&circuit.witness(&message)),
+                Sha256Circuit::output_from_digest(&digest)
+            );
+        }
+
+        // lengths past the buffer do not hash anything
+        let mut witness = circuit.witness(b"");
+        witness[..2].copy_from_slice(&131u16.to_be_bytes());
+        assert!(circuit.compute(&witness).iter().all(|word| word.value == 0));
+    }
+
+    #[test]
+    fn test_hidden_len() {
+        let circuit = Sha256HiddenLenCircuit::new(64);
+        let mut proof_lens = vec![];
+        for password in [b"hunter2".as_slice(), &[0x41; 56], &[0x42; 64]] {
+            let digest: [u8; 32] = Sha256::digest(password).into();
+            let output = Sha256Circuit::output_from_digest(&digest);
+
+            let proof = check_full_run(&circuit, &circuit.witness(password), &output);
+            proof_lens.push(proof.to_bytes().unwrap().len());
+        }
+
+        // the proof does not depend on the length of the message
+        assert!(proof_lens.iter().all(|&len| len == proof_lens[0]));
+    }
+
+    #[test]
+    fn test_hidden_len_wrong_length() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+
+        // a witness claiming a shorter message does not prove the digest
+        let circuit = Sha256HiddenLenCircuit::new(16);
+        let digest: [u8; 32] = Sha256::digest(b"secret").into();
+        let output = Sha256Circuit::output_from_digest(&digest);
+
+        let mut witness = circuit.witness(b"secret");
+        witness[..2].copy_from_slice(&5u16.to_be_bytes());
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &witness, &circuit, &output, &params, &dst,
+        )
+        .unwrap();
+        assert!(Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+            &proof, &circuit, &output, &params, &dst
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_short_input() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_80;
+
+        let preimage = b"abc";
+        let circuit = Sha256Circuit::new(preimage.len());
+
+        let output = circuit.compute(preimage);
+        for (&word, &expected_word) in output.iter().zip(test_vectors::short::DIGEST_OUTPUT.iter())
+        {
+            assert_eq!(word.value, expected_word);
+        }
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, preimage, &circuit, &output, &params, &dst,
+        )
+        .unwrap();
+
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_long_input() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_80;
+
+        let preimage = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+        let circuit = Sha256Circuit::new(preimage.len());
+
+        let output = circuit.compute(preimage);
+        for (&word, &expected_word) in output.iter().zip(test_vectors::long::DIGEST_OUTPUT.iter()) {
+            assert_eq!(word.value, expected_word);
+        }
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, preimage, &circuit, &output, &params, &dst,
+        )
+        .unwrap();
+
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
+
+        // the message length is part of the statement
+        let other_circuit = Sha256Circuit::new(preimage.len() - 1);
+        assert!(Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+            &proof,
+            &other_circuit,
+            &output,
+            &params,
+            &dst
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_exact_gate_count() {
+        // 56 bytes no longer fit the length in the first block, and a public
+        // prefix of 70 bytes leaves one of its blocks to compress in the clear
+        let preimage = [0x5au8; 56];
+        let circuits = [
+            Sha256Circuit::new(preimage.len()),
+            Sha256Circuit::with_public_parts(&[0xa5; 70], preimage.len(), b"suffix"),
+        ];
+        assert_eq!(circuits[0].num_of_blocks(), 2);
+        assert_eq!(circuits[1].num_of_blocks(), 3);
+        assert_eq!(circuits[1].num_of_public_blocks(), 1);
+
+        for circuit in circuits.iter() {
+            check_full_run(circuit, &preimage, &circuit.compute(&preimage));
+        }
+    }
+
+    #[test]
+    fn test_proof_size() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+
+        let preimage = b"abc";
+        let circuit = Sha256Circuit::new(preimage.len());
+        let output = circuit.compute(preimage);
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, preimage, &circuit, &output, &params, &dst,
+        )
+        .unwrap();
+        let num_of_repetitions = proof.claimed_trits.len();
+
+        // a single block sends 128 AND outputs and 600 carries per opened view,
+        // which took 8736 bytes when every word carried its `usize` size
+        let view_len = proof.views[0].messages.len() * 4
+            + crate::view::packed_carries_len::<u32>(proof.views[0].carries.len());
+        assert_eq!(proof.views[0].messages.len(), 128);
+        assert_eq!(proof.views[0].carries.len(), 600);
+        assert_eq!(view_len, 2837);
+
+        let bytes = proof.to_bytes().unwrap();
+        assert!(bytes.len() <= num_of_repetitions * 3000);
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+
+        let preimage = b"abcdefghbcdefghicdefghijdefghijk";
+        let circuit = Sha256Circuit::new(preimage.len());
+        let output = circuit.compute(preimage);
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let prove = |rng: &mut ThreadRng, mode| {
+            Prover::<u32, ChaCha20Rng, Keccak256>::prove_with_mode(
+                rng, preimage, &circuit, &output, &params, &dst, mode,
+            )
+            .unwrap()
+        };
+
+        let zkboo_proof = prove(&mut rng, Mode::ZKBoo);
+        let zkbpp_proof = prove(&mut rng, Mode::ZKBPlusPlus);
+
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+            &zkbpp_proof,
+            &circuit,
+            &output,
+            &params,
+            &dst,
+        )
+        .unwrap();
+
+        // on average two thirds of the input shares are no longer sent
+        let num_of_repetitions = zkboo_proof.claimed_trits.len();
+        let sent_shares = |proof: &Proof<u32, Keccak256>| -> usize {
+            proof
+                .party_inputs
+                .iter()
+                .map(|input| input.len())
+                .sum::<usize>()
+                + proof
+                    .views
+                    .iter()
+                    .map(|view| view.input.len())
+                    .sum::<usize>()
+        };
+        assert_eq!(sent_shares(&zkboo_proof), 2 * num_of_repetitions * 32);
+        assert!(sent_shares(&zkbpp_proof) < sent_shares(&zkboo_proof));
+        assert!(zkbpp_proof.to_bytes().unwrap().len() < zkboo_proof.to_bytes().unwrap().len());
+    }
+}
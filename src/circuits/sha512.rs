@@ -0,0 +1,291 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::{
+        mpc_public, mpc_public_verify,
+        sha512::{
+            init_iv, init_iv_384, init_iv_512_256, mpc_sha512_block, mpc_sha512_block_verify,
+            padding, sha512_block, State,
+        },
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Mul gates of one block: 3 additions for each of the 64 extended words of
+/// the message schedule, 9 per round of compression and 8 to update the state.
+pub const MUL_GATES_PER_BLOCK: usize = 3 * 64 + 9 * 80 + 8;
+
+/// Proves knowledge of `x` such that H(x) = y for H one of SHA-512, SHA-384
+/// and SHA-512/256, which share the compression of SHA-512 and differ in the
+/// initial value and in the number of words of the final state kept in y.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sha512Circuit {
+    iv: State,
+    /// Leading words of the final state in the output
+    output_words: usize,
+    message_len: usize,
+}
+
+impl Sha512Circuit {
+    pub fn sha512(message_len: usize) -> Self {
+        Self {
+            iv: init_iv(),
+            output_words: 8,
+            message_len,
+        }
+    }
+
+    pub fn sha384(message_len: usize) -> Self {
+        Self {
+            iv: init_iv_384(),
+            output_words: 6,
+            message_len,
+        }
+    }
+
+    pub fn sha512_256(message_len: usize) -> Self {
+        Self {
+            iv: init_iv_512_256(),
+            output_words: 4,
+            message_len,
+        }
+    }
+
+    pub fn message_len(&self) -> usize {
+        self.message_len
+    }
+
+    pub fn output_words(&self) -> usize {
+        self.output_words
+    }
+
+    /// Number of blocks once padded, which appends at least 17 bytes.
+    pub fn num_of_blocks(&self) -> usize {
+        (self.message_len + 17).div_ceil(128)
+    }
+
+    /// Public output of the circuit for a `digest` y, of 8 bytes per output
+    /// word.
+    pub fn output_from_digest(&self, digest: &[u8]) -> Output<u64> {
+        assert_eq!(digest.len(), 8 * self.output_words);
+        digest
+            .chunks(8)
+            .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()).into())
+            .collect()
+    }
+
+    fn truncate(&self, state: State) -> Output<u64> {
+        let mut output = state.to_vec();
+        output.truncate(self.output_words);
+        output
+    }
+}
+
+impl Circuit<u64> for Sha512Circuit {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<u64>> {
+        assert_eq!(input.len(), self.message_len);
+
+        let mut state = self.iv;
+        for block in padding(input).chunks(16) {
+            state = sha512_block(&block.try_into().unwrap(), &state);
+        }
+
+        self.truncate(state)
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<u64>,
+        p2: &mut Party<u64>,
+        p3: &mut Party<u64>,
+    ) -> TwoThreeDecOutput<u64> {
+        let p1_words = padding(&p1.view.input);
+        let p2_words = padding(&p2.view.input);
+        let p3_words = padding(&p3.view.input);
+
+        let mut state = mpc_public(self.iv);
+
+        let iter_chunks = p1_words
+            .chunks(16)
+            .zip(p2_words.chunks(16))
+            .zip(p3_words.chunks(16));
+
+        for ((p1_words, p2_words), p3_words) in iter_chunks {
+            state = mpc_sha512_block(
+                &p1_words.try_into().unwrap(),
+                &p2_words.try_into().unwrap(),
+                &p3_words.try_into().unwrap(),
+                &state,
+                p1,
+                p2,
+                p3,
+            );
+        }
+
+        (
+            self.truncate(state.0),
+            self.truncate(state.1),
+            self.truncate(state.2),
+        )
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<u64>,
+        p_next: &mut Party<u64>,
+    ) -> Result<(Output<u64>, Output<u64>), Error> {
+        let p_words = padding(&p.view.input);
+        let p_next_words = padding(&p_next.view.input);
+
+        let mut state = mpc_public_verify(self.iv);
+
+        for (p_words, p_next_words) in p_words.chunks(16).zip(p_next_words.chunks(16)) {
+            state = mpc_sha512_block_verify(
+                &p_words.try_into().unwrap(),
+                &p_next_words.try_into().unwrap(),
+                &state,
+                p,
+                p_next,
+            )?;
+        }
+
+        Ok((self.truncate(state.0), self.truncate(state.1)))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"sha512".as_slice(),
+            &self.iv.to_bytes(),
+            &(self.output_words as u64).to_le_bytes(),
+            &(self.message_len as u64).to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    fn party_input_len(&self) -> usize {
+        self.witness_len().div_ceil(8)
+    }
+
+    fn witness_len(&self) -> usize {
+        self.message_len
+    }
+
+    fn party_output_len(&self) -> usize {
+        self.output_words
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        MUL_GATES_PER_BLOCK * self.num_of_blocks()
+    }
+}
+
+#[cfg(test)]
+mod test_sha512_circuit {
+    use sha2::{Digest, Sha384, Sha512, Sha512_256};
+
+    use super::Sha512Circuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+        gadgets::sha512::test_vectors,
+    };
+
+    #[test]
+    fn test_vectors() {
+        let cases: [(Sha512Circuit, &[u64], &[u8]); 6] = [
+            (
+                Sha512Circuit::sha512(3),
+                &test_vectors::SHORT_SHA512,
+                test_vectors::SHORT_INPUT,
+            ),
+            (
+                Sha512Circuit::sha384(3),
+                &test_vectors::SHORT_SHA384,
+                test_vectors::SHORT_INPUT,
+            ),
+            (
+                Sha512Circuit::sha512_256(3),
+                &test_vectors::SHORT_SHA512_256,
+                test_vectors::SHORT_INPUT,
+            ),
+            (
+                Sha512Circuit::sha512(112),
+                &test_vectors::LONG_SHA512,
+                test_vectors::LONG_INPUT,
+            ),
+            (
+                Sha512Circuit::sha384(112),
+                &test_vectors::LONG_SHA384,
+                test_vectors::LONG_INPUT,
+            ),
+            (
+                Sha512Circuit::sha512_256(112),
+                &test_vectors::LONG_SHA512_256,
+                test_vectors::LONG_INPUT,
+            ),
+        ];
+
+        for (circuit, digest, message) in cases {
+            let output = circuit.compute(message);
+            assert_eq!(output.len(), digest.len());
+            for (&word, &expected_word) in output.iter().zip(digest.iter()) {
+                assert_eq!(word.value, expected_word);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute() {
+        // message lengths around the block boundaries
+        for message_len in [0, 111, 112, 128, 239, 240, 300] {
+            let message: Vec<u8> = (0..message_len).map(|i| i as u8).collect();
+
+            let circuit = Sha512Circuit::sha512(message_len);
+            assert_eq!(
+                circuit.compute(&message),
+                circuit.output_from_digest(&Sha512::digest(&message))
+            );
+            let circuit = Sha512Circuit::sha384(message_len);
+            assert_eq!(
+                circuit.compute(&message),
+                circuit.output_from_digest(&Sha384::digest(&message))
+            );
+            let circuit = Sha512Circuit::sha512_256(message_len);
+            assert_eq!(
+                circuit.compute(&message),
+                circuit.output_from_digest(&Sha512_256::digest(&message))
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_run() {
+        let preimage = test_vectors::LONG_INPUT;
+        let circuits = [
+            Sha512Circuit::sha512(preimage.len()),
+            Sha512Circuit::sha384(preimage.len()),
+            Sha512Circuit::sha512_256(preimage.len()),
+        ];
+
+        for (i, circuit) in circuits.iter().enumerate() {
+            let output = circuit.compute(preimage);
+            let proof = check_full_run(circuit, preimage, &output);
+
+            // the variants share the compression function but not the statement
+            let other_circuit = &circuits[(i + 1) % circuits.len()];
+            assert!(matches!(
+                verify(&proof, other_circuit, &output),
+                Err(Error::CircuitMismatchError)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let preimage = test_vectors::SHORT_INPUT;
+        let circuit = Sha512Circuit::sha384(preimage.len());
+        check_zkbpp(&circuit, preimage, &circuit.compute(preimage));
+    }
+}
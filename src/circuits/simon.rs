@@ -0,0 +1,248 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    circuits::speck::{block_from_bytes, output},
+    error::Error,
+    gadgets::{
+        mpc_public, mpc_public_verify,
+        prepare::generic_parse,
+        simon::{encrypt, expand_key, mpc_encrypt, mpc_encrypt_verify, Block, Params},
+    },
+    gf2_word::{GF2Word, Value},
+    party::Party,
+};
+
+/// Proves knowledge of a key `k` such that Simon(k, m) = c, for a public
+/// plaintext `m` and a public ciphertext `c`. Keys and blocks are bytes as for
+/// `SpeckCircuit`: the key is the little-endian words `k_0, k_1, ..`, and a
+/// block is the little-endian words `y, x`, which are also the words of the
+/// public output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimonCircuit<T: Value> {
+    params: Params<T>,
+    plaintext: Block<T>,
+}
+
+impl<T: Value> SimonCircuit<T> {
+    pub fn new(params: Params<T>, plaintext: &[u8]) -> Self {
+        Self {
+            params,
+            plaintext: block_from_bytes(plaintext),
+        }
+    }
+
+    pub fn params(&self) -> &Params<T> {
+        &self.params
+    }
+
+    /// Public output of the circuit for a `ciphertext` c.
+    pub fn output_from_ciphertext(ciphertext: &[u8]) -> Output<T> {
+        output(block_from_bytes(ciphertext))
+    }
+}
+
+impl<T: Value> Circuit<T> for SimonCircuit<T> {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<T>> {
+        let key = generic_parse(input, self.party_input_len()).unwrap();
+
+        let round_keys = expand_key(&key, &self.params);
+        output(encrypt(&round_keys, self.plaintext))
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<T>,
+        p2: &mut Party<T>,
+        p3: &mut Party<T>,
+    ) -> TwoThreeDecOutput<T> {
+        let key_p1 = generic_parse(&p1.view.input, self.party_input_len()).unwrap();
+        let key_p2 = generic_parse(&p2.view.input, self.party_input_len()).unwrap();
+        let key_p3 = generic_parse(&p3.view.input, self.party_input_len()).unwrap();
+
+        // the key schedule is linear, and so is expanded on each share
+        let (c1, c2, c3) = mpc_encrypt(
+            (
+                &expand_key(&key_p1, &self.params),
+                &expand_key(&key_p2, &self.params),
+                &expand_key(&key_p3, &self.params),
+            ),
+            mpc_public(self.plaintext),
+            p1,
+            p2,
+            p3,
+        );
+
+        (output(c1), output(c2), output(c3))
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<T>,
+        p_next: &mut Party<T>,
+    ) -> Result<(Output<T>, Output<T>), Error> {
+        let key_p = generic_parse(&p.view.input, self.party_input_len())?;
+        let key_p_next = generic_parse(&p_next.view.input, self.party_input_len())?;
+
+        let (c, c_next) = mpc_encrypt_verify(
+            (
+                &expand_key(&key_p, &self.params),
+                &expand_key(&key_p_next, &self.params),
+            ),
+            mpc_public_verify(self.plaintext),
+            p,
+            p_next,
+        )?;
+
+        Ok((output(c), output(c_next)))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"simon".as_slice(),
+            &(T::bits_len() as u64).to_le_bytes(),
+            &(self.params.key_words as u64).to_le_bytes(),
+            &(self.params.rounds as u64).to_le_bytes(),
+            &(self.params.z as u64).to_le_bytes(),
+            &self.plaintext.1.value.to_bytes(),
+            &self.plaintext.0.value.to_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Words of the key.
+    fn party_input_len(&self) -> usize {
+        self.params.key_words
+    }
+
+    fn party_output_len(&self) -> usize {
+        2
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        self.params.num_of_mul_gates()
+    }
+}
+
+#[cfg(test)]
+mod test_simon_circuit {
+    use super::SimonCircuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+        gadgets::simon::Params,
+    };
+
+    /// Key, plaintext and ciphertext bytes of the Simon and Speck paper, in
+    /// the byte order of `SimonCircuit`.
+    type TestVector = (&'static [u8], &'static [u8], &'static [u8]);
+
+    const SIMON_64_96: TestVector = (
+        &[
+            0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13,
+        ],
+        &[0x63, 0x6c, 0x69, 0x6e, 0x67, 0x20, 0x72, 0x6f],
+        &[0xc8, 0x8f, 0x1a, 0x11, 0x7f, 0xe2, 0xa2, 0x5c],
+    );
+    const SIMON_64_128: TestVector = (
+        &[
+            0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13, 0x18, 0x19,
+            0x1a, 0x1b,
+        ],
+        &[0x75, 0x6e, 0x64, 0x20, 0x6c, 0x69, 0x6b, 0x65],
+        &[0x7a, 0xa0, 0xdf, 0xb9, 0x20, 0xfc, 0xc8, 0x44],
+    );
+    const SIMON_128_128: TestVector = (
+        &[
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ],
+        &[
+            0x20, 0x74, 0x72, 0x61, 0x76, 0x65, 0x6c, 0x6c, 0x65, 0x72, 0x73, 0x20, 0x64, 0x65,
+            0x73, 0x63,
+        ],
+        &[
+            0xbc, 0x0b, 0x4e, 0xf8, 0x2a, 0x83, 0xaa, 0x65, 0x3f, 0xfe, 0x54, 0x1e, 0x1e, 0x1b,
+            0x68, 0x49,
+        ],
+    );
+    const SIMON_128_256: TestVector = (
+        &[
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b,
+            0x1c, 0x1d, 0x1e, 0x1f,
+        ],
+        &[
+            0x69, 0x73, 0x20, 0x61, 0x20, 0x73, 0x69, 0x6d, 0x6f, 0x6f, 0x6d, 0x20, 0x69, 0x6e,
+            0x20, 0x74,
+        ],
+        &[
+            0x68, 0xb8, 0xe7, 0xef, 0x87, 0x2a, 0xf7, 0x3b, 0xa0, 0xa3, 0xc8, 0xaf, 0x79, 0x55,
+            0x2b, 0x8d,
+        ],
+    );
+
+    #[test]
+    fn test_compute() {
+        for (params, (key, plaintext, ciphertext)) in [
+            (Params::SIMON_64_96, SIMON_64_96),
+            (Params::SIMON_64_128, SIMON_64_128),
+        ] {
+            let circuit = SimonCircuit::new(params, plaintext);
+            assert_eq!(
+                circuit.compute(key),
+                SimonCircuit::output_from_ciphertext(ciphertext)
+            );
+        }
+
+        for (params, (key, plaintext, ciphertext)) in [
+            (Params::SIMON_128_128, SIMON_128_128),
+            (Params::SIMON_128_256, SIMON_128_256),
+        ] {
+            let circuit = SimonCircuit::new(params, plaintext);
+            assert_eq!(
+                circuit.compute(key),
+                SimonCircuit::output_from_ciphertext(ciphertext)
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_run() {
+        let (key, plaintext, ciphertext) = SIMON_64_96;
+        let circuit = SimonCircuit::new(Params::SIMON_64_96, plaintext);
+        let output = SimonCircuit::output_from_ciphertext(ciphertext);
+        let proof = check_full_run(&circuit, key, &output);
+
+        // the sequence of the key schedule is part of the statement
+        assert!(matches!(
+            verify(
+                &proof,
+                &SimonCircuit::new(Params::new(3, 42, 3), plaintext),
+                &output
+            ),
+            Err(Error::CircuitMismatchError)
+        ));
+    }
+
+    #[test]
+    fn test_full_run_u64() {
+        let (key, plaintext, ciphertext) = SIMON_128_128;
+        let circuit = SimonCircuit::new(Params::SIMON_128_128, plaintext);
+        check_full_run(
+            &circuit,
+            key,
+            &SimonCircuit::output_from_ciphertext(ciphertext),
+        );
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let (key, plaintext, ciphertext) = SIMON_128_256;
+        let circuit = SimonCircuit::new(Params::SIMON_128_256, plaintext);
+        check_zkbpp(
+            &circuit,
+            key,
+            &SimonCircuit::output_from_ciphertext(ciphertext),
+        );
+    }
+}
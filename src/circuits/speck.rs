@@ -0,0 +1,243 @@
+use crate::{
+    circuit::{Circuit, Output, TwoThreeDecOutput},
+    error::Error,
+    gadgets::{
+        mpc_public, mpc_public_verify,
+        prepare::generic_parse,
+        speck::{
+            encrypt, expand_key, mpc_encrypt, mpc_encrypt_verify, mpc_expand_key,
+            mpc_expand_key_verify, Block, Params,
+        },
+    },
+    gf2_word::{GF2Word, Value},
+    party::Party,
+};
+
+/// Proves knowledge of a key `k` such that Speck(k, m) = c, for a public
+/// plaintext `m` and a public ciphertext `c`. Keys and blocks are bytes in the
+/// order of the Speck implementation guide: the key is the little-endian words
+/// `k_0, l_0, l_1, ..`, and a block is the little-endian words `y, x`, which
+/// are also the words of the public output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeckCircuit<T: Value> {
+    params: Params<T>,
+    plaintext: Block<T>,
+}
+
+impl<T: Value> SpeckCircuit<T> {
+    pub fn new(params: Params<T>, plaintext: &[u8]) -> Self {
+        Self {
+            params,
+            plaintext: block_from_bytes(plaintext),
+        }
+    }
+
+    pub fn params(&self) -> &Params<T> {
+        &self.params
+    }
+
+    /// Public output of the circuit for a `ciphertext` c.
+    pub fn output_from_ciphertext(ciphertext: &[u8]) -> Output<T> {
+        output(block_from_bytes(ciphertext))
+    }
+}
+
+/// Read a block of bytes as the words `(x, y)`.
+pub(crate) fn block_from_bytes<T: Value>(bytes: &[u8]) -> (GF2Word<T>, GF2Word<T>) {
+    let words = generic_parse::<T>(bytes, 2).unwrap();
+    (words[1], words[0])
+}
+
+/// The words `y, x` of a block, in the order of its bytes.
+pub(crate) fn output<T: Value>((x, y): (GF2Word<T>, GF2Word<T>)) -> Output<T> {
+    vec![y, x]
+}
+
+impl<T: Value> Circuit<T> for SpeckCircuit<T> {
+    fn compute(&self, input: &[u8]) -> Vec<GF2Word<T>> {
+        let key = generic_parse(input, self.party_input_len()).unwrap();
+
+        let round_keys = expand_key(&key, &self.params);
+        output(encrypt(&round_keys, self.plaintext))
+    }
+
+    fn compute_23_decomposition(
+        &self,
+        p1: &mut Party<T>,
+        p2: &mut Party<T>,
+        p3: &mut Party<T>,
+    ) -> TwoThreeDecOutput<T> {
+        let key_p1 = generic_parse(&p1.view.input, self.party_input_len()).unwrap();
+        let key_p2 = generic_parse(&p2.view.input, self.party_input_len()).unwrap();
+        let key_p3 = generic_parse(&p3.view.input, self.party_input_len()).unwrap();
+
+        let (k1, k2, k3) = mpc_expand_key((&key_p1, &key_p2, &key_p3), &self.params, p1, p2, p3);
+        let (c1, c2, c3) = mpc_encrypt((&k1, &k2, &k3), mpc_public(self.plaintext), p1, p2, p3);
+
+        (output(c1), output(c2), output(c3))
+    }
+
+    fn simulate_two_parties(
+        &self,
+        p: &mut Party<T>,
+        p_next: &mut Party<T>,
+    ) -> Result<(Output<T>, Output<T>), Error> {
+        let key_p = generic_parse(&p.view.input, self.party_input_len())?;
+        let key_p_next = generic_parse(&p_next.view.input, self.party_input_len())?;
+
+        let (k, k_next) = mpc_expand_key_verify((&key_p, &key_p_next), &self.params, p, p_next)?;
+        let (c, c_next) =
+            mpc_encrypt_verify((&k, &k_next), mpc_public_verify(self.plaintext), p, p_next)?;
+
+        Ok((output(c), output(c_next)))
+    }
+
+    fn description(&self) -> Vec<u8> {
+        [
+            b"speck".as_slice(),
+            &(T::bits_len() as u64).to_le_bytes(),
+            &(self.params.key_words as u64).to_le_bytes(),
+            &(self.params.rounds as u64).to_le_bytes(),
+            &self.plaintext.1.value.to_bytes(),
+            &self.plaintext.0.value.to_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Words of the key.
+    fn party_input_len(&self) -> usize {
+        self.params.key_words
+    }
+
+    fn party_output_len(&self) -> usize {
+        2
+    }
+
+    fn num_of_mul_gates(&self) -> usize {
+        self.params.num_of_mul_gates()
+    }
+}
+
+#[cfg(test)]
+mod test_speck_circuit {
+    use super::SpeckCircuit;
+    use crate::{
+        circuit::Circuit,
+        circuits::test_utils::{check_full_run, check_zkbpp, verify},
+        error::Error,
+        gadgets::speck::Params,
+    };
+
+    /// Key, plaintext and ciphertext bytes of the Speck implementation guide.
+    type TestVector = (&'static [u8], &'static [u8], &'static [u8]);
+
+    const SPECK_64_96: TestVector = (
+        &[
+            0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13,
+        ],
+        &[0x65, 0x61, 0x6e, 0x73, 0x20, 0x46, 0x61, 0x74],
+        &[0x6c, 0x94, 0x75, 0x41, 0xec, 0x52, 0x79, 0x9f],
+    );
+    const SPECK_64_128: TestVector = (
+        &[
+            0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11, 0x12, 0x13, 0x18, 0x19,
+            0x1a, 0x1b,
+        ],
+        &[0x2d, 0x43, 0x75, 0x74, 0x74, 0x65, 0x72, 0x3b],
+        &[0x8b, 0x02, 0x4e, 0x45, 0x48, 0xa5, 0x6f, 0x8c],
+    );
+    const SPECK_128_128: TestVector = (
+        &[
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ],
+        &[
+            0x20, 0x6d, 0x61, 0x64, 0x65, 0x20, 0x69, 0x74, 0x20, 0x65, 0x71, 0x75, 0x69, 0x76,
+            0x61, 0x6c,
+        ],
+        &[
+            0x18, 0x0d, 0x57, 0x5c, 0xdf, 0xfe, 0x60, 0x78, 0x65, 0x32, 0x78, 0x79, 0x51, 0x98,
+            0x5d, 0xa6,
+        ],
+    );
+    const SPECK_128_256: TestVector = (
+        &[
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b,
+            0x1c, 0x1d, 0x1e, 0x1f,
+        ],
+        &[
+            0x70, 0x6f, 0x6f, 0x6e, 0x65, 0x72, 0x2e, 0x20, 0x49, 0x6e, 0x20, 0x74, 0x68, 0x6f,
+            0x73, 0x65,
+        ],
+        &[
+            0x43, 0x8f, 0x18, 0x9c, 0x8d, 0xb4, 0xee, 0x4e, 0x3e, 0xf5, 0xc0, 0x05, 0x04, 0x01,
+            0x09, 0x41,
+        ],
+    );
+
+    #[test]
+    fn test_compute() {
+        for (params, (key, plaintext, ciphertext)) in [
+            (Params::SPECK_64_96, SPECK_64_96),
+            (Params::SPECK_64_128, SPECK_64_128),
+        ] {
+            let circuit = SpeckCircuit::new(params, plaintext);
+            assert_eq!(
+                circuit.compute(key),
+                SpeckCircuit::output_from_ciphertext(ciphertext)
+            );
+        }
+
+        for (params, (key, plaintext, ciphertext)) in [
+            (Params::SPECK_128_128, SPECK_128_128),
+            (Params::SPECK_128_256, SPECK_128_256),
+        ] {
+            let circuit = SpeckCircuit::new(params, plaintext);
+            assert_eq!(
+                circuit.compute(key),
+                SpeckCircuit::output_from_ciphertext(ciphertext)
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_run() {
+        let (key, plaintext, ciphertext) = SPECK_64_128;
+        let circuit = SpeckCircuit::new(Params::SPECK_64_128, plaintext);
+        let output = SpeckCircuit::output_from_ciphertext(ciphertext);
+        let proof = check_full_run(&circuit, key, &output);
+
+        // the parameters are part of the statement
+        assert!(matches!(
+            verify(
+                &proof,
+                &SpeckCircuit::new(Params::SPECK_64_96, plaintext),
+                &output
+            ),
+            Err(Error::CircuitMismatchError)
+        ));
+    }
+
+    #[test]
+    fn test_full_run_u64() {
+        let (key, plaintext, ciphertext) = SPECK_128_256;
+        let circuit = SpeckCircuit::new(Params::SPECK_128_256, plaintext);
+        check_full_run(
+            &circuit,
+            key,
+            &SpeckCircuit::output_from_ciphertext(ciphertext),
+        );
+    }
+
+    #[test]
+    fn test_zkbpp() {
+        let (key, plaintext, ciphertext) = SPECK_128_128;
+        let circuit = SpeckCircuit::new(Params::SPECK_128_128, plaintext);
+        check_zkbpp(
+            &circuit,
+            key,
+            &SpeckCircuit::output_from_ciphertext(ciphertext),
+        );
+    }
+}
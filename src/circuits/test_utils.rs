@@ -0,0 +1,101 @@
+//! Proving runs shared by the circuit tests, all with the same security
+//! parameters, tape generator, hash and domain separation tag.
+
+use rand::thread_rng;
+use rand_chacha::ChaCha20Rng;
+use sha3::Keccak256;
+
+use crate::{
+    circuit::Circuit,
+    data_structures::{Mode, Proof},
+    diagnostics::Usage,
+    error::Error,
+    fs::DomainSeparator,
+    gf2_word::{GF2Word, Value},
+    prover::Prover,
+    security::SecurityParams,
+    verifier::Verifier,
+};
+
+fn params_and_dst() -> (SecurityParams, DomainSeparator) {
+    let params = SecurityParams::BITS_40;
+    let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+    (params, dst)
+}
+
+/// Prove with ZKBoo that `circuit` maps `witness` to `output`, and check that
+/// the proof verifies and that every repetition simulates the prover's
+/// transcript, reading both tapes to the end. The proof is returned for the
+/// mismatch cases of the caller.
+pub(crate) fn check_full_run<T: Value + PartialEq>(
+    circuit: &impl Circuit<T>,
+    witness: &[u8],
+    output: &[GF2Word<T>],
+) -> Proof<T, Keccak256> {
+    let (params, dst) = params_and_dst();
+    let output = output.to_vec();
+
+    let (proof, transcript) = Prover::<T, ChaCha20Rng, Keccak256>::prove_with_transcript(
+        &mut thread_rng(),
+        witness,
+        circuit,
+        &output,
+        &params,
+        &dst,
+        Mode::ZKBoo,
+    )
+    .unwrap();
+
+    Verifier::<T, ChaCha20Rng, Keccak256>::verify(&proof, circuit, &output, &params, &dst).unwrap();
+
+    let diagnostics = Verifier::<T, ChaCha20Rng, Keccak256>::diagnose(
+        &proof,
+        &transcript,
+        circuit,
+        &output,
+        &params,
+        &dst,
+    )
+    .unwrap();
+    assert!(diagnostics.verdict.is_ok());
+    for report in diagnostics.repetitions.iter() {
+        assert_eq!(report.tapes, (Usage::Exact, Usage::Exact));
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    proof
+}
+
+/// Prove with ZKB++ that `circuit` maps `witness` to `output`, and check that
+/// the proof verifies.
+pub(crate) fn check_zkbpp<T: Value + PartialEq>(
+    circuit: &impl Circuit<T>,
+    witness: &[u8],
+    output: &[GF2Word<T>],
+) {
+    let (params, dst) = params_and_dst();
+    let output = output.to_vec();
+
+    let proof = Prover::<T, ChaCha20Rng, Keccak256>::prove_with_mode(
+        &mut thread_rng(),
+        witness,
+        circuit,
+        &output,
+        &params,
+        &dst,
+        Mode::ZKBPlusPlus,
+    )
+    .unwrap();
+
+    Verifier::<T, ChaCha20Rng, Keccak256>::verify(&proof, circuit, &output, &params, &dst).unwrap();
+}
+
+/// Verify a proof of `check_full_run` against another `circuit` or `output`.
+pub(crate) fn verify<T: Value + PartialEq>(
+    proof: &Proof<T, Keccak256>,
+    circuit: &impl Circuit<T>,
+    output: &[GF2Word<T>],
+) -> Result<(), Error> {
+    let (params, dst) = params_and_dst();
+    Verifier::<T, ChaCha20Rng, Keccak256>::verify(proof, circuit, &output.to_vec(), &params, &dst)
+}
@@ -1,9 +1,13 @@
-use std::marker::PhantomData;
+use std::{fmt, marker::PhantomData};
 
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{Error as _, SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use sha3::Digest;
 
-use crate::{config::HASH_LEN, error::Error};
+use crate::error::Error;
 
 #[derive(Default, Serialize, Deserialize)]
 pub struct Blinding<T: Serialize>(pub T);
@@ -13,23 +17,61 @@ impl<T: Serialize> AsRef<T> for Blinding<T> {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// A digest of `D`, encoded without a length prefix since its length is that
+/// of `D`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct Commitment<D: Default + Digest> {
-    data: [u8; HASH_LEN],
+    #[serde(
+        serialize_with = "serialize_digest",
+        deserialize_with = "deserialize_digest::<D, _>"
+    )]
+    data: Vec<u8>,
     _digest: PhantomData<D>,
 }
 
+fn serialize_digest<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut tuple = serializer.serialize_tuple(data.len())?;
+    for byte in data {
+        tuple.serialize_element(byte)?;
+    }
+    tuple.end()
+}
+
+fn deserialize_digest<'de, D: Digest, De: Deserializer<'de>>(
+    deserializer: De,
+) -> Result<Vec<u8>, De::Error> {
+    struct DigestVisitor(usize);
+
+    impl<'de> Visitor<'de> for DigestVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a digest of {} bytes", self.0)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut data = Vec::with_capacity(self.0);
+            for i in 0..self.0 {
+                match seq.next_element()? {
+                    Some(byte) => data.push(byte),
+                    None => return Err(A::Error::invalid_length(i, &self)),
+                }
+            }
+            Ok(data)
+        }
+    }
+
+    let digest_len = <D as Digest>::output_size();
+    deserializer.deserialize_tuple(digest_len, DigestVisitor(digest_len))
+}
+
 impl<D: Default + Digest> Commitment<D> {
     /// Commit to a given `message` using by hashing it with some `blinding`.
     pub fn commit<U: Serialize, T: Serialize>(
         blinding: &Blinding<U>,
         message: &T,
     ) -> Result<Self, Error> {
-        let digest_len = <D as Digest>::output_size();
-        if HASH_LEN != digest_len {
-            return Err(Error::HashLenError(HASH_LEN, digest_len));
-        }
-
         let blinding =
             bincode::serialize(blinding.as_ref()).map_err(|_| Error::SerializationError)?;
         let message = bincode::serialize(message).map_err(|_| Error::SerializationError)?;
@@ -37,8 +79,7 @@ impl<D: Default + Digest> Commitment<D> {
         let mut hasher: D = Digest::new_with_prefix(blinding);
         hasher.update(&message);
 
-        // safe to unwrap since we check digest output is of right side
-        let data = hasher.finalize().to_vec().try_into().unwrap();
+        let data = hasher.finalize().to_vec();
         Ok(Self {
             data,
             _digest: PhantomData,
@@ -51,10 +92,6 @@ impl<D: Default + Digest> Commitment<D> {
         blinding: &Blinding<U>,
         message: &T,
     ) -> Result<bool, Error> {
-        let digest_len = <D as Digest>::output_size();
-        if HASH_LEN != digest_len {
-            return Err(Error::HashLenError(HASH_LEN, digest_len));
-        }
         let blinding =
             bincode::serialize(blinding.as_ref()).map_err(|_| Error::SerializationError)?;
         let message = bincode::serialize(message).map_err(|_| Error::SerializationError)?;
@@ -62,9 +99,7 @@ impl<D: Default + Digest> Commitment<D> {
         let mut hasher: D = Digest::new_with_prefix(blinding);
         hasher.update(&message);
 
-        // safe to unwrap since we check digest output is of right side
-        let claimed_data: [u8; HASH_LEN] = hasher.finalize().to_vec().try_into().unwrap();
-        Ok(claimed_data == self.data)
+        Ok(hasher.finalize().as_slice() == self.data.as_slice())
     }
 }
 
@@ -84,11 +119,26 @@ mod commitment_tests {
     }
 
     #[test]
-    #[should_panic]
-    fn test_wrong_hash_len() {
+    fn test_hash_len() {
         let blinding = Blinding(String::from("I'm blinder"));
         let message = 5u32;
 
-        let _ = Commitment::<Keccak224>::commit(&blinding, &message).unwrap();
+        let c = Commitment::<Keccak224>::commit(&blinding, &message).unwrap();
+        assert_eq!(c.data.len(), 28);
+        assert!(c.verify_opening(&blinding, &message).unwrap())
+    }
+
+    #[test]
+    fn test_encoding() {
+        let blinding = Blinding(String::from("I'm blinder"));
+        let message = 5u32;
+
+        // the length of a commitment is implied by the digest
+        let c = Commitment::<Keccak256>::commit(&blinding, &message).unwrap();
+        let bytes = bincode::serialize(&c).unwrap();
+        assert_eq!(bytes.len(), 32);
+        let decoded: Commitment<Keccak256> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.data, c.data);
+        assert!(bincode::deserialize::<Commitment<Keccak256>>(&bytes[..31]).is_err());
     }
 }
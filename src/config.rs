@@ -1,2 +1,7 @@
-pub const HASH_LEN: usize = 32;
+/// Length of the tape seeds. Keys shorter than this are padded with zeros.
 pub const KEY_LEN: usize = 32;
+/// Protocol name and version absorbed into every Fiat-Shamir domain separation tag.
+pub const PROTOCOL_NAME: &str = "zkboo";
+pub const PROTOCOL_VERSION: u16 = 1;
+/// Version of the binary proof encoding produced by `Proof::to_bytes`.
+pub const PROOF_FORMAT_VERSION: u16 = 2;
@@ -1,11 +1,15 @@
-use serde::Serialize;
+use bincode::Options;
+use serde::{Deserialize, Serialize};
 use sha3::Digest;
 
 use crate::{
+    circuit::CircuitId,
     commitment::{Blinding, Commitment},
+    config::PROOF_FORMAT_VERSION,
     error::Error,
     gf2_word::{GF2Word, Value},
     key::Key,
+    security::SecurityParams,
     view::View,
 };
 
@@ -21,39 +25,334 @@ pub struct PartyExecution<'a, T: Value> {
 impl<'a, T: Value> PartyExecution<'a, T> {
     pub fn commit<D: Default + Digest>(&self) -> Result<Commitment<D>, Error> {
         let blinding = Blinding(self.key);
-        let messages_bytes: Vec<u8> = self
-            .view
-            .messages
-            .iter()
-            .flat_map(|msg| msg.value.to_bytes())
-            .collect();
-
-        // TODO: consider more optimal way to prepare message for committing
-        // we omit commiting to full view to make sure that offset is not included which is just helper variable
-        let commitment = Commitment::<D>::commit(
-            &blinding,
-            &[self.view.input.clone(), messages_bytes].concat(),
-        )?;
+
+        // commit to the same compact encoding of the view that is sent in a proof
+        let commitment = Commitment::<D>::commit(&blinding, self.view)?;
         Ok(commitment)
     }
 }
 
+/// The variant of the protocol a proof is produced with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    /// ZKBoo as in (https://eprint.iacr.org/2016/163.pdf)
+    ZKBoo,
+    /// ZKB++ as in (https://eprint.iacr.org/2017/279.pdf): the input shares of
+    /// the first two parties are derived from their tape keys and are neither
+    /// committed to nor sent.
+    ZKBPlusPlus,
+}
+
+impl Mode {
+    /// Whether the input share of party `party_index` is derived from its key.
+    pub fn derives_share(&self, party_index: usize) -> bool {
+        *self == Mode::ZKBPlusPlus && party_index < 2
+    }
+}
+
 #[derive(Serialize)]
 pub struct PublicInput<'a, T: Value> {
-    pub hash_len: usize,
-    pub security_param: usize,
+    pub mode: Mode,
+    pub circuit_id: &'a CircuitId,
+    pub params: &'a SecurityParams,
     pub public_output: &'a Vec<GF2Word<T>>,
     pub outputs: &'a Vec<Vec<GF2Word<T>>>,
 }
 
-// TODO: add methods for computing proofs size, etc.
-pub struct Proof<T: Value, D, const SIGMA: usize>
+/// Label hashed with `D` to obtain the digest identifier stored in a proof header.
+const DIGEST_ID_LABEL: &[u8] = b"zkboo/digest-id";
+
+/// Identifies the digest `D` by its output on a fixed label, so that proofs
+/// committed with one hash function are not decoded for another.
+pub fn digest_id<D: Digest>() -> [u8; 8] {
+    let hash = D::digest(DIGEST_ID_LABEL);
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&hash[..8]);
+    id
+}
+
+/// The parameters a proof was produced with, written in front of the proof body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofHeader {
+    pub version: u16,
+    /// Number of bytes in a word of the circuit (`T::bytes_len()`)
+    pub word_width: u8,
+    pub digest_id: [u8; 8],
+    /// Also gives the length of the keys in the body
+    pub params: SecurityParams,
+}
+
+impl ProofHeader {
+    /// The header expected for a `Proof<T, D>` with `params` of the current
+    /// format version.
+    pub fn expected<T: Value, D: Digest>(params: &SecurityParams) -> Self {
+        Self {
+            version: PROOF_FORMAT_VERSION,
+            word_width: T::bytes_len() as u8,
+            digest_id: digest_id::<D>(),
+            params: *params,
+        }
+    }
+
+    /// Check that `self` matches the `expected` header.
+    pub fn check(&self, expected: &Self) -> Result<(), Error> {
+        if self.version != expected.version {
+            return Err(Error::ProofFormatVersionError(
+                expected.version,
+                self.version,
+            ));
+        }
+        if self.word_width != expected.word_width {
+            return Err(Error::WordWidthError(
+                expected.word_width as usize,
+                self.word_width as usize,
+            ));
+        }
+        if self.digest_id != expected.digest_id {
+            return Err(Error::DigestIdError);
+        }
+        if self.params != expected.params {
+            return Err(Error::SecurityParamsError(expected.params, self.params));
+        }
+        Ok(())
+    }
+}
+
+pub struct Proof<T: Value, D>
 where
     D: Default + Digest,
 {
+    /// Checked against the parameters expected by the verifier, and encoded
+    /// in the `ProofHeader`
+    pub params: SecurityParams,
+    pub mode: Mode,
+    pub circuit_id: CircuitId,
+    /// Input share of the first opened party per repetition. In ZKB++ it is only
+    /// sent for the third party and left empty otherwise.
     pub party_inputs: Vec<Vec<u8>>,
     pub commitments: Vec<Commitment<D>>,
     pub views: Vec<View<T>>,
-    pub keys: Vec<Key>,
+    /// Keys of the opened parties, `params.key_len` bytes each
+    pub keys: Vec<Vec<u8>>,
     pub claimed_trits: Vec<u8>,
 }
+
+/// The body of a proof as encoded after its header: every field but the
+/// parameters, with the keys concatenated since their length is in the header.
+type ProofBody<T, D> = (
+    Mode,
+    CircuitId,
+    Vec<Vec<u8>>,
+    Vec<Commitment<D>>,
+    Vec<View<T>>,
+    Vec<u8>,
+    Vec<u8>,
+);
+
+/// Fixed-width little-endian bincode, rejecting trailing bytes. The limit keeps
+/// length prefixes of malformed input from triggering huge allocations.
+fn encoding_options(limit: u64) -> impl Options + Copy {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .reject_trailing_bytes()
+        .with_limit(limit)
+}
+
+impl<T: Value, D: Default + Digest> Proof<T, D> {
+    /// Canonical binary encoding: a `ProofHeader` followed by the proof body.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let key_len = self.params.key_len as usize;
+        if let Some(key) = self.keys.iter().find(|key| key.len() != key_len) {
+            return Err(Error::KeyLenError(key_len, key.len()));
+        }
+
+        let header = ProofHeader::expected::<T, D>(&self.params);
+        let options = encoding_options(u64::MAX);
+
+        let mut bytes = options
+            .serialize(&header)
+            .map_err(|_| Error::SerializationError)?;
+        let body = options
+            .serialize(&(
+                &self.mode,
+                &self.circuit_id,
+                &self.party_inputs,
+                &self.commitments,
+                &self.views,
+                &self.keys.concat(),
+                &self.claimed_trits,
+            ))
+            .map_err(|_| Error::SerializationError)?;
+        bytes.extend_from_slice(&body);
+
+        Ok(bytes)
+    }
+
+    /// Decode a proof produced by `to_bytes`, checking that its header matches
+    /// the word width, digest and security parameters expected by the caller.
+    pub fn from_bytes(bytes: &[u8], params: &SecurityParams) -> Result<Self, Error> {
+        params.check::<D>()?;
+        let expected = ProofHeader::expected::<T, D>(params);
+        let options = encoding_options(bytes.len() as u64);
+
+        let header_len = options
+            .serialized_size(&expected)
+            .map_err(|_| Error::SerializationError)? as usize;
+        if bytes.len() < header_len {
+            return Err(Error::DeserializationError);
+        }
+        let (header_bytes, body_bytes) = bytes.split_at(header_len);
+
+        let header: ProofHeader = options
+            .deserialize(header_bytes)
+            .map_err(|_| Error::DeserializationError)?;
+        header.check(&expected)?;
+
+        let body: ProofBody<T, D> = options
+            .deserialize(body_bytes)
+            .map_err(|_| Error::DeserializationError)?;
+        let (mode, circuit_id, party_inputs, commitments, views, keys, claimed_trits) = body;
+
+        let key_len = params.key_len as usize;
+        if keys.len() % key_len != 0 {
+            return Err(Error::DeserializationError);
+        }
+
+        Ok(Self {
+            params: *params,
+            mode,
+            circuit_id,
+            party_inputs,
+            commitments,
+            views,
+            keys: keys.chunks(key_len).map(<[u8]>::to_vec).collect(),
+            claimed_trits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_proof_format {
+    use sha3::{Keccak256, Sha3_256};
+
+    use super::{Mode, Proof, ProofHeader};
+    use crate::{
+        circuit::CircuitId,
+        commitment::{Blinding, Commitment},
+        config::PROOF_FORMAT_VERSION,
+        error::Error,
+        security::SecurityParams,
+        view::View,
+    };
+
+    fn dummy_proof() -> Proof<u32, Keccak256> {
+        let mut view = View::new(vec![1, 2, 3, 4]);
+        view.send_msg(7u32.into());
+        view.send_msg(13u32.into());
+
+        Proof {
+            params: SecurityParams::BITS_40,
+            mode: Mode::ZKBoo,
+            circuit_id: CircuitId {
+                description_hash: vec![0u8; 32],
+                num_of_mul_gates: 2,
+                witness_len: 4,
+                party_output_len: 1,
+            },
+            party_inputs: vec![vec![5, 6, 7, 8]],
+            commitments: vec![Commitment::commit(&Blinding([9u8; 32]), &10u32).unwrap()],
+            views: vec![view],
+            keys: vec![vec![11u8; 16], vec![12u8; 16]],
+            claimed_trits: vec![2],
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let bytes = dummy_proof().to_bytes().unwrap();
+        let decoded =
+            Proof::<u32, Keccak256>::from_bytes(&bytes, &SecurityParams::BITS_40).unwrap();
+        assert_eq!(decoded.keys, dummy_proof().keys);
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_header_mismatch() {
+        let bytes = dummy_proof().to_bytes().unwrap();
+
+        assert!(matches!(
+            Proof::<u64, Keccak256>::from_bytes(&bytes, &SecurityParams::BITS_40),
+            Err(Error::WordWidthError(8, 4))
+        ));
+        assert!(matches!(
+            Proof::<u32, Sha3_256>::from_bytes(&bytes, &SecurityParams::BITS_40),
+            Err(Error::DigestIdError)
+        ));
+        assert!(matches!(
+            Proof::<u32, Keccak256>::from_bytes(&bytes, &SecurityParams::BITS_80),
+            Err(Error::SecurityParamsError(
+                SecurityParams::BITS_80,
+                SecurityParams::BITS_40
+            ))
+        ));
+
+        let mut bytes = bytes;
+        bytes[..2].copy_from_slice(&(PROOF_FORMAT_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            Proof::<u32, Keccak256>::from_bytes(&bytes, &SecurityParams::BITS_40),
+            Err(Error::ProofFormatVersionError(PROOF_FORMAT_VERSION, _))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_body() {
+        let bytes = dummy_proof().to_bytes().unwrap();
+
+        for len in 0..bytes.len() {
+            assert!(
+                Proof::<u32, Keccak256>::from_bytes(&bytes[..len], &SecurityParams::BITS_40)
+                    .is_err()
+            );
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            Proof::<u32, Keccak256>::from_bytes(&trailing, &SecurityParams::BITS_40),
+            Err(Error::DeserializationError)
+        ));
+
+        // a huge length prefix for `party_inputs` must not be allocated
+        let mut huge = bytes;
+        let header_len = bincode::serialized_size(&ProofHeader::expected::<u32, Keccak256>(
+            &SecurityParams::BITS_40,
+        ))
+        .unwrap() as usize;
+        // skip the mode tag and the circuit id
+        let offset = header_len + 4 + (8 + 32 + 3 * 8);
+        huge[offset..offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            Proof::<u32, Keccak256>::from_bytes(&huge, &SecurityParams::BITS_40),
+            Err(Error::DeserializationError)
+        ));
+    }
+
+    #[test]
+    fn test_key_len() {
+        // keys of another length than in the parameters cannot be encoded
+        let mut proof = dummy_proof();
+        proof.keys[1].pop();
+        assert!(matches!(proof.to_bytes(), Err(Error::KeyLenError(16, 15))));
+
+        // nor decoded, as their length is implied by the header
+        let mut bytes = dummy_proof().to_bytes().unwrap();
+        let keys_offset = bytes.len() - (8 + 1) - (8 + 2 * 16);
+        bytes[keys_offset..keys_offset + 8].copy_from_slice(&31u64.to_le_bytes());
+        bytes.remove(keys_offset + 8);
+        assert!(matches!(
+            Proof::<u32, Keccak256>::from_bytes(&bytes, &SecurityParams::BITS_40),
+            Err(Error::DeserializationError)
+        ));
+    }
+}
@@ -0,0 +1,519 @@
+use std::fmt::{self, Display};
+
+use rand::{CryptoRng, RngCore, SeedableRng};
+use sha3::{digest::FixedOutputReset, Digest};
+
+use crate::{
+    circuit::{Circuit, CircuitId, Output},
+    data_structures::{Mode, Proof},
+    error::Error,
+    fs::DomainSeparator,
+    gf2_word::{GF2Word, Value},
+    key::Key,
+    prover::Prover,
+    security::SecurityParams,
+    verifier::Verifier,
+    view::View,
+};
+
+/// Views and outputs of all three parties in every repetition, in the order
+/// they are committed to. It reveals the witness, so it is only meant for
+/// debugging circuits and must never be sent along with a proof.
+pub struct Transcript<T: Value> {
+    pub views: Vec<View<T>>,
+    pub outputs: Vec<Output<T>>,
+}
+
+/// How much of a tape or of a view sent in a proof a simulated party read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Usage {
+    Exact,
+    Under {
+        read: usize,
+        available: usize,
+    },
+    /// The simulation asked for more than the `available` values.
+    Over {
+        available: usize,
+    },
+}
+
+impl Usage {
+    /// Usage of a source of `available` values, of which `read` were asked
+    /// for, including a failed read past the end.
+    fn new(read: usize, available: usize) -> Self {
+        if read > available {
+            Usage::Over { available }
+        } else if read < available {
+            Usage::Under { read, available }
+        } else {
+            Usage::Exact
+        }
+    }
+}
+
+impl Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Usage::Exact => write!(f, "fully read"),
+            Usage::Under { read, available } => {
+                write!(f, "under-consumed ({} of {} read)", read, available)
+            }
+            Usage::Over { available } => {
+                write!(
+                    f,
+                    "over-consumed (all {} read and more requested)",
+                    available
+                )
+            }
+        }
+    }
+}
+
+/// Agreement between values recomputed by the verifier and the prover's ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Prefix {
+    /// Number of leading values that agree
+    pub matched: usize,
+    pub recomputed: usize,
+    pub expected: usize,
+}
+
+impl Prefix {
+    fn new<T: Value>(recomputed: &[GF2Word<T>], expected: &[GF2Word<T>]) -> Self {
+        let matched = recomputed
+            .iter()
+            .zip(expected.iter())
+            .take_while(|(r, e)| r == e)
+            .count();
+
+        Self {
+            matched,
+            recomputed: recomputed.len(),
+            expected: expected.len(),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.matched == self.recomputed && self.matched == self.expected
+    }
+}
+
+/// Outcome of simulating a single repetition of a proof.
+#[derive(Debug)]
+pub struct RepetitionReport {
+    pub repetition: usize,
+    /// The opened parties: the view of the first is recomputed by the
+    /// verifier while the view of the second is read from the proof.
+    pub opened: (usize, usize),
+    /// Error returned by the simulation or when deriving the third output
+    pub error: Option<Error>,
+    pub tapes: (Usage, Usage),
+    /// Messages and carries of the view sent for the second party
+    pub messages: Usage,
+    pub carries: Usage,
+    /// Messages and carries recomputed for the first party against the prover's
+    pub recomputed_messages: Prefix,
+    pub recomputed_carries: Prefix,
+    /// Whether the output derived for the unopened party matches the prover's,
+    /// unknown when the simulation failed
+    pub third_output_consistent: Option<bool>,
+}
+
+impl RepetitionReport {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+            && self.tapes == (Usage::Exact, Usage::Exact)
+            && self.messages == Usage::Exact
+            && self.carries == Usage::Exact
+            && self.recomputed_messages.is_full()
+            && self.recomputed_carries.is_full()
+            && self.third_output_consistent == Some(true)
+    }
+}
+
+impl Display for RepetitionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (i0, i1) = self.opened;
+        let mut issues = Vec::new();
+
+        if let Some(error) = &self.error {
+            issues.push(format!("simulation failed: {}", error));
+        }
+        for (party, usage) in [(i0, self.tapes.0), (i1, self.tapes.1)] {
+            if usage != Usage::Exact {
+                issues.push(format!("tape of party {} {}", party, usage));
+            }
+        }
+        for (name, usage) in [("messages", self.messages), ("carries", self.carries)] {
+            if usage != Usage::Exact {
+                issues.push(format!("{} of party {} {}", name, i1, usage));
+            }
+        }
+        for (name, prefix) in [
+            ("messages", self.recomputed_messages),
+            ("carries", self.recomputed_carries),
+        ] {
+            if !prefix.is_full() {
+                issues.push(format!(
+                    "recomputed {} of party {} diverge after {} ({} recomputed, {} expected)",
+                    name, i0, prefix.matched, prefix.recomputed, prefix.expected
+                ));
+            }
+        }
+        if self.third_output_consistent == Some(false) {
+            issues.push(format!("output of party {} is inconsistent", (i0 + 2) % 3));
+        }
+
+        write!(
+            f,
+            "repetition {} (parties {} and {}): ",
+            self.repetition, i0, i1
+        )?;
+        if issues.is_empty() {
+            write!(f, "ok")
+        } else {
+            write!(f, "{}", issues.join("; "))
+        }
+    }
+}
+
+/// Per-repetition reports for a proof next to the result of verifying it.
+#[derive(Debug)]
+pub struct Diagnostics {
+    pub repetitions: Vec<RepetitionReport>,
+    /// Result of `Verifier::verify` on the same proof
+    pub verdict: Result<(), Error>,
+}
+
+impl Diagnostics {
+    pub fn failures(&self) -> impl Iterator<Item = &RepetitionReport> {
+        self.repetitions.iter().filter(|report| !report.is_ok())
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for report in self.failures() {
+            writeln!(f, "{}", report)?;
+        }
+        match &self.verdict {
+            Ok(()) => write!(f, "verification succeeded"),
+            Err(error) => write!(f, "verification failed: {}", error),
+        }
+    }
+}
+
+impl<T: Value, TapeR, D> Prover<T, TapeR, D>
+where
+    TapeR: SeedableRng<Seed = Key> + RngCore + CryptoRng,
+    D: fmt::Debug + Default + Digest + FixedOutputReset + Send + Sync,
+{
+    /// Prove as in `prove_with_mode` and also return the full transcript,
+    /// to be checked with `Verifier::diagnose`.
+    pub fn prove_with_transcript<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        witness: &[u8],
+        circuit: &impl Circuit<T>,
+        public_output: &Vec<GF2Word<T>>,
+        params: &SecurityParams,
+        dst: &DomainSeparator,
+        mode: Mode,
+    ) -> Result<(Proof<T, D>, Transcript<T>), Error> {
+        let execution = Self::execute(rng, witness, circuit, params, dst, mode)?;
+        let transcript = Transcript {
+            views: execution.views.clone(),
+            outputs: execution.outputs.clone(),
+        };
+        let proof = Self::open(execution, public_output, params, dst, mode)?;
+
+        Ok((proof, transcript))
+    }
+}
+
+impl<T, TapeR, D> Verifier<T, TapeR, D>
+where
+    T: Value + PartialEq,
+    TapeR: SeedableRng<Seed = Key> + RngCore + CryptoRng,
+    D: Clone + Default + Digest + FixedOutputReset + Send + Sync,
+{
+    /// Simulate every repetition of `proof` and compare it against the
+    /// prover's `transcript`, to find where `simulate_two_parties` of a
+    /// circuit disagrees with its `compute_23_decomposition`. Errors are only
+    /// returned when the proof or transcript is malformed.
+    pub fn diagnose(
+        proof: &Proof<T, D>,
+        transcript: &Transcript<T>,
+        circuit: &impl Circuit<T>,
+        public_output: &Vec<GF2Word<T>>,
+        params: &SecurityParams,
+        dst: &DomainSeparator,
+    ) -> Result<Diagnostics, Error> {
+        if proof.params != *params {
+            return Err(Error::SecurityParamsError(*params, proof.params));
+        }
+        if proof.circuit_id != CircuitId::new::<T, D>(circuit) {
+            return Err(Error::CircuitMismatchError);
+        }
+
+        let num_of_repetitions = params.num_of_repetitions();
+        Self::check_len("party_inputs", num_of_repetitions, proof.party_inputs.len())?;
+        Self::check_len("views", num_of_repetitions, proof.views.len())?;
+        Self::check_len(
+            "claimed_trits",
+            num_of_repetitions,
+            proof.claimed_trits.len(),
+        )?;
+        Self::check_len("keys", 2 * num_of_repetitions, proof.keys.len())?;
+        Self::check_key_lens(proof)?;
+        Self::check_len(
+            "transcript views",
+            3 * num_of_repetitions,
+            transcript.views.len(),
+        )?;
+        Self::check_len(
+            "transcript outputs",
+            3 * num_of_repetitions,
+            transcript.outputs.len(),
+        )?;
+
+        let mut repetitions = Vec::with_capacity(num_of_repetitions);
+
+        for (repetition, &party_index) in proof.claimed_trits.iter().enumerate() {
+            if party_index > 2 {
+                return Err(Error::TritError(party_index));
+            }
+            let i0 = party_index as usize;
+            let i1 = (i0 + 1) % 3;
+            let i2 = (i0 + 2) % 3;
+
+            let (mut p, mut p_next) = Self::open_parties(proof, repetition, circuit)?;
+            let simulation = circuit.simulate_two_parties(&mut p, &mut p_next);
+
+            let tapes = (
+                Usage::new(p.tape.values_read(), p.tape.capacity()),
+                Usage::new(p_next.tape.values_read(), p_next.tape.capacity()),
+            );
+            let messages = Usage::new(p_next.view.messages_read(), p_next.view.messages.len());
+            let carries = Usage::new(p_next.view.carries_read(), p_next.view.carries.len());
+
+            let expected_view = &transcript.views[3 * repetition + i0];
+            let recomputed_messages = Prefix::new(&p.view.messages, &expected_view.messages);
+            let recomputed_carries = Prefix::new(&p.view.carries, &expected_view.carries);
+
+            let (error, third_output_consistent) = match simulation
+                .and_then(|(o0, o1)| Self::derive_third_output(public_output, circuit, (&o0, &o1)))
+            {
+                Ok(o2) => (None, Some(o2 == transcript.outputs[3 * repetition + i2])),
+                Err(error) => (Some(error), None),
+            };
+
+            repetitions.push(RepetitionReport {
+                repetition,
+                opened: (i0, i1),
+                error,
+                tapes,
+                messages,
+                carries,
+                recomputed_messages,
+                recomputed_carries,
+                third_output_consistent,
+            });
+        }
+
+        Ok(Diagnostics {
+            repetitions,
+            verdict: Self::verify(proof, circuit, public_output, params, dst),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_diagnostics {
+    use rand::thread_rng;
+    use rand_chacha::ChaCha20Rng;
+    use sha3::Keccak256;
+
+    use super::Usage;
+    use crate::{
+        circuit::{Circuit, Output, TwoThreeDecOutput},
+        data_structures::Mode,
+        error::Error,
+        fs::DomainSeparator,
+        gadgets::{mpc_and, mpc_and_verify, prepare::generic_parse},
+        gf2_word::GF2Word,
+        party::Party,
+        prover::Prover,
+        security::SecurityParams,
+        verifier::Verifier,
+    };
+
+    // computes: x1 & x2, with faults that can be injected in the simulation
+    struct AndCircuit {
+        simulate_x1_and_x1: bool,
+        simulate_extra_and: bool,
+        declared_mul_gates: usize,
+    }
+
+    impl Circuit<u32> for AndCircuit {
+        fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
+            let x = generic_parse(input, self.party_input_len()).unwrap();
+            vec![x[0] & x[1]]
+        }
+
+        fn compute_23_decomposition(
+            &self,
+            p1: &mut Party<u32>,
+            p2: &mut Party<u32>,
+            p3: &mut Party<u32>,
+        ) -> TwoThreeDecOutput<u32> {
+            let x = generic_parse(&p1.view.input, self.party_input_len()).unwrap();
+            let y = generic_parse(&p2.view.input, self.party_input_len()).unwrap();
+            let z = generic_parse(&p3.view.input, self.party_input_len()).unwrap();
+
+            let (o1, o2, o3) = mpc_and((x[0], x[1]), (y[0], y[1]), (z[0], z[1]), p1, p2, p3);
+            (vec![o1], vec![o2], vec![o3])
+        }
+
+        fn simulate_two_parties(
+            &self,
+            p: &mut Party<u32>,
+            p_next: &mut Party<u32>,
+        ) -> Result<(Output<u32>, Output<u32>), Error> {
+            let x = generic_parse(&p.view.input, self.party_input_len())?;
+            let y = generic_parse(&p_next.view.input, self.party_input_len())?;
+
+            let (o1, o2) = if self.simulate_x1_and_x1 {
+                mpc_and_verify((x[0], x[0]), (y[0], y[0]), p, p_next)?
+            } else {
+                mpc_and_verify((x[0], x[1]), (y[0], y[1]), p, p_next)?
+            };
+            if self.simulate_extra_and {
+                mpc_and_verify((o1, o1), (o2, o2), p, p_next)?;
+            }
+            Ok((vec![o1], vec![o2]))
+        }
+
+        fn description(&self) -> Vec<u8> {
+            b"x1 & x2".to_vec()
+        }
+
+        fn party_input_len(&self) -> usize {
+            2
+        }
+
+        fn party_output_len(&self) -> usize {
+            1
+        }
+
+        fn num_of_mul_gates(&self) -> usize {
+            self.declared_mul_gates
+        }
+    }
+
+    fn diagnose(circuit: &AndCircuit) -> super::Diagnostics {
+        let mut rng = thread_rng();
+        let params = SecurityParams::BITS_40;
+        let input: Vec<u8> = [0xf0f0f0f0u32, 0x12345678]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let output = circuit.compute(&input);
+
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let (proof, transcript) = Prover::<u32, ChaCha20Rng, Keccak256>::prove_with_transcript(
+            &mut rng,
+            &input,
+            circuit,
+            &output,
+            &params,
+            &dst,
+            Mode::ZKBoo,
+        )
+        .unwrap();
+
+        Verifier::<u32, ChaCha20Rng, Keccak256>::diagnose(
+            &proof,
+            &transcript,
+            circuit,
+            &output,
+            &params,
+            &dst,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_consistent_circuit() {
+        let diagnostics = diagnose(&AndCircuit {
+            simulate_x1_and_x1: false,
+            simulate_extra_and: false,
+            declared_mul_gates: 1,
+        });
+
+        assert!(diagnostics.verdict.is_ok());
+        assert_eq!(diagnostics.failures().count(), 0);
+    }
+
+    #[test]
+    fn test_diverging_simulation() {
+        let diagnostics = diagnose(&AndCircuit {
+            simulate_x1_and_x1: true,
+            simulate_extra_and: false,
+            declared_mul_gates: 1,
+        });
+
+        assert!(matches!(
+            diagnostics.verdict,
+            Err(Error::FiatShamirOutputsMatchingError)
+        ));
+        assert!(diagnostics.failures().count() > 0);
+        for report in diagnostics.failures() {
+            assert!(report.error.is_none());
+            assert_eq!(report.recomputed_messages.matched, 0);
+            assert!(report.to_string().contains("diverge after 0"));
+        }
+    }
+
+    #[test]
+    fn test_under_consumed_tapes() {
+        let diagnostics = diagnose(&AndCircuit {
+            simulate_x1_and_x1: false,
+            simulate_extra_and: false,
+            declared_mul_gates: 2,
+        });
+
+        // the proof verifies, but the declared number of gates is wrong
+        assert!(diagnostics.verdict.is_ok());
+        assert_eq!(
+            diagnostics.failures().count(),
+            diagnostics.repetitions.len()
+        );
+        for report in diagnostics.failures() {
+            let under = Usage::Under {
+                read: 1,
+                available: 2,
+            };
+            assert_eq!(report.tapes, (under, under));
+            assert_eq!(report.third_output_consistent, Some(true));
+        }
+    }
+
+    #[test]
+    fn test_over_consumed_tape() {
+        let diagnostics = diagnose(&AndCircuit {
+            simulate_x1_and_x1: false,
+            simulate_extra_and: true,
+            declared_mul_gates: 1,
+        });
+
+        // only the tape of the first opened party is read past its end
+        assert!(diagnostics.verdict.is_err());
+        for report in diagnostics.repetitions.iter() {
+            assert!(matches!(report.error, Some(Error::TapeExhaustedError)));
+            assert_eq!(report.tapes, (Usage::Over { available: 1 }, Usage::Exact));
+            assert_eq!(report.messages, Usage::Exact);
+            assert_eq!(report.carries, Usage::Exact);
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use std::fmt::{self, Display};
+
+use crate::security::SecurityParams;
+
 #[derive(Debug)]
 pub enum Error {
     SerializationError,
@@ -6,4 +10,83 @@ pub enum Error {
     OutputReconstructionError,
     FiatShamirOutputsMatchingError,
     BitError,
+    DeserializationError,
+    ProofFormatVersionError(u16, u16),
+    WordWidthError(usize, usize),
+    DigestIdError,
+    SecurityParamsError(SecurityParams, SecurityParams),
+    KeyLenError(usize, usize),
+    InsecureParamsError,
+    DomainSeparationError,
+    CircuitMismatchError,
+    ProofLengthError(&'static str, usize, usize),
+    TritError(u8),
+    InputLengthError(usize, usize),
+    OutputLengthError(usize, usize),
+    TapeExhaustedError,
+    ViewExhaustedError,
+    KeysExhaustedError,
 }
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::SerializationError => write!(f, "serialization failed"),
+            Error::HashLenError(expected, got) => {
+                write!(f, "expected digest of {} bytes, got {}", expected, got)
+            }
+            Error::VerificationError => write!(f, "verification failed"),
+            Error::OutputReconstructionError => write!(f, "failed to reconstruct output"),
+            Error::FiatShamirOutputsMatchingError => {
+                write!(
+                    f,
+                    "recomputed Fiat-Shamir challenge does not match the proof"
+                )
+            }
+            Error::BitError => write!(f, "bit position out of range"),
+            Error::DeserializationError => write!(f, "malformed proof encoding"),
+            Error::ProofFormatVersionError(expected, got) => {
+                write!(f, "expected proof format version {}, got {}", expected, got)
+            }
+            Error::WordWidthError(expected, got) => {
+                write!(f, "expected words of {} bytes, got {}", expected, got)
+            }
+            Error::DigestIdError => write!(f, "proof was produced with a different digest"),
+            Error::SecurityParamsError(expected, got) => {
+                write!(f, "expected {}, got {}", expected, got)
+            }
+            Error::KeyLenError(expected, got) => {
+                write!(f, "expected key of {} bytes, got {}", expected, got)
+            }
+            Error::InsecureParamsError => {
+                write!(f, "hash or key length is too short for the security level")
+            }
+            Error::DomainSeparationError => {
+                write!(
+                    f,
+                    "domain separation tag does not match the parameters in use"
+                )
+            }
+            Error::CircuitMismatchError => write!(f, "proof was produced for a different circuit"),
+            Error::ProofLengthError(field, expected, got) => {
+                write!(
+                    f,
+                    "expected {} entries in `{}`, got {}",
+                    expected, field, got
+                )
+            }
+            Error::TritError(trit) => write!(f, "{} is not a trit", trit),
+            Error::InputLengthError(expected, got) => {
+                write!(f, "expected input of {} bytes, got {}", expected, got)
+            }
+            Error::OutputLengthError(expected, got) => {
+                write!(f, "expected output of {} words, got {}", expected, got)
+            }
+            Error::TapeExhaustedError => write!(f, "random tape has no values left"),
+            Error::ViewExhaustedError => write!(f, "view has no messages left"),
+            Error::KeysExhaustedError => write!(f, "key manager has no keys left"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -3,13 +3,70 @@
 //! This is used to derive verifier opening queries non-interactively, after the
 //! prover commits to all its views.
 
+use rand::{RngCore, SeedableRng};
+use serde::Serialize;
 use sha3::{
     digest::{FixedOutputReset, OutputSizeUser},
     Digest,
 };
 use std::marker::PhantomData;
 
-use crate::{commitment::Commitment, data_structures::PublicInput, error::Error, gf2_word::Value};
+use crate::{
+    commitment::Commitment,
+    config::{PROTOCOL_NAME, PROTOCOL_VERSION},
+    data_structures::{digest_id, PublicInput},
+    error::Error,
+    gf2_word::Value,
+    key::Key,
+    security::SecurityParams,
+    tape::tape_id,
+};
+
+/// Domain separation tag the Fiat-Shamir oracle is initialized with, so that
+/// proofs for different applications, parameters or primitives never share a
+/// challenge space.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DomainSeparator {
+    pub protocol_name: String,
+    pub protocol_version: u16,
+    /// Label chosen by the application, e.g. `b"my-service/login/v2"`
+    pub application_label: Vec<u8>,
+    pub params: SecurityParams,
+    pub tape_id: [u8; 8],
+    pub commitment_id: [u8; 8],
+}
+
+impl DomainSeparator {
+    pub fn new<TapeR, D>(application_label: &[u8], params: &SecurityParams) -> Self
+    where
+        TapeR: SeedableRng<Seed = Key> + RngCore,
+        D: Digest,
+    {
+        Self {
+            protocol_name: PROTOCOL_NAME.to_string(),
+            protocol_version: PROTOCOL_VERSION,
+            application_label: application_label.to_vec(),
+            params: *params,
+            tape_id: tape_id::<TapeR>(),
+            commitment_id: digest_id::<D>(),
+        }
+    }
+
+    /// Check that the tag was built for this protocol version and for the
+    /// tape, commitment scheme and security parameters in use, and that those
+    /// parameters can be used with `D`.
+    pub fn check<TapeR, D>(&self, params: &SecurityParams) -> Result<(), Error>
+    where
+        TapeR: SeedableRng<Seed = Key> + RngCore,
+        D: Digest,
+    {
+        params.check::<D>()?;
+        if *self != Self::new::<TapeR, D>(&self.application_label, params) {
+            return Err(Error::DomainSeparationError);
+        }
+        Ok(())
+    }
+}
 
 pub struct SigmaProtocolStatelessFiatShamir<D: Clone + Digest>(PhantomData<D>);
 
@@ -42,8 +99,8 @@ impl<D: Clone + Digest> SigmaProtocolStatelessFiatShamir<D> {
                 pos = 0;
             }
 
-            let b1 = get_bit(hash[(pos / 8)], pos % 8);
-            let b2 = get_bit(hash[((pos + 1) / 8)], (pos + 1) % 8);
+            let b1 = get_bit(hash[pos / 8], pos % 8);
+            let b2 = get_bit(hash[(pos + 1) / 8], (pos + 1) % 8);
 
             let trit = (b1 << 1) | b2;
             if trit < 3 {
@@ -63,9 +120,10 @@ pub struct SigmaFS<D: Digest + FixedOutputReset> {
 }
 
 impl<D: Default + Digest + FixedOutputReset> SigmaFS<D> {
-    pub fn initialize(seed: &[u8]) -> Self {
+    pub fn initialize(dst: &DomainSeparator) -> Result<Self, Error> {
+        let seed = bincode::serialize(dst).map_err(|_| Error::SerializationError)?;
         let hasher = Digest::new_with_prefix(seed);
-        Self { hasher }
+        Ok(Self { hasher })
     }
 
     pub fn digest_public_data<T: Value>(&mut self, pi: &PublicInput<T>) -> Result<(), Error> {
@@ -102,8 +160,8 @@ impl<D: Default + Digest + FixedOutputReset> SigmaFS<D> {
                 pos = 0;
             }
 
-            let b1 = get_bit(hash[(pos / 8)], pos % 8);
-            let b2 = get_bit(hash[((pos + 1) / 8)], (pos + 1) % 8);
+            let b1 = get_bit(hash[pos / 8], pos % 8);
+            let b2 = get_bit(hash[(pos + 1) / 8], (pos + 1) % 8);
 
             let trit = (b1 << 1) | b2;
             if trit < 3 {
@@ -120,8 +178,41 @@ impl<D: Default + Digest + FixedOutputReset> SigmaFS<D> {
 
 #[cfg(test)]
 mod test_fs {
-    use super::SigmaProtocolStatelessFiatShamir;
-    use sha3::Keccak256;
+    use super::{DomainSeparator, SigmaFS, SigmaProtocolStatelessFiatShamir};
+    use crate::{error::Error, security::SecurityParams};
+    use rand_chacha::{ChaCha12Rng, ChaCha20Rng};
+    use sha3::{Keccak256, Sha3_256};
+
+    #[test]
+    fn test_domain_separation() {
+        let params = SecurityParams::BITS_40;
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"app", &params);
+        assert!(dst.check::<ChaCha20Rng, Keccak256>(&params).is_ok());
+
+        for wrong in [
+            dst.check::<ChaCha12Rng, Keccak256>(&params),
+            dst.check::<ChaCha20Rng, Sha3_256>(&params),
+            dst.check::<ChaCha20Rng, Keccak256>(&SecurityParams::BITS_80),
+        ] {
+            assert!(matches!(wrong, Err(Error::DomainSeparationError)));
+        }
+
+        let mut old_version = dst.clone();
+        old_version.protocol_version -= 1;
+        assert!(old_version
+            .check::<ChaCha20Rng, Keccak256>(&params)
+            .is_err());
+
+        // different labels give different challenges
+        let other = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"other app", &params);
+        let trits = SigmaFS::<Keccak256>::initialize(&dst)
+            .unwrap()
+            .sample_trits(64);
+        let other_trits = SigmaFS::<Keccak256>::initialize(&other)
+            .unwrap()
+            .sample_trits(64);
+        assert_ne!(trits, other_trits);
+    }
 
     #[test]
     fn test_stateless() {
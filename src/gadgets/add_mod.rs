@@ -1,4 +1,6 @@
 use crate::{
+    error::Error,
+    gadgets::TAPE_LEN_MSG,
     gf2_word::{Bit, GF2Word, Value},
     party::Party,
 };
@@ -48,9 +50,9 @@ pub fn mpc_add_mod_k<T: Value>(
     p2: &mut Party<T>,
     p3: &mut Party<T>,
 ) -> (GF2Word<T>, GF2Word<T>, GF2Word<T>) {
-    let rand_p1 = p1.read_tape();
-    let rand_p2 = p2.read_tape();
-    let rand_p3 = p3.read_tape();
+    let rand_p1 = p1.read_tape().expect(TAPE_LEN_MSG);
+    let rand_p2 = p2.read_tape().expect(TAPE_LEN_MSG);
+    let rand_p3 = p3.read_tape().expect(TAPE_LEN_MSG);
 
     let mut carry_p1: GF2Word<T> = T::zero().into();
     let mut carry_p2: GF2Word<T> = T::zero().into();
@@ -79,9 +81,9 @@ pub fn mpc_add_mod_k<T: Value>(
         carry_p3 = carry_p3.value.set_bit(i + 1, ci_p3.inner()).into();
     }
 
-    p1.view.send_msg(carry_p1);
-    p2.view.send_msg(carry_p2);
-    p3.view.send_msg(carry_p3);
+    p1.view.send_carry(carry_p1);
+    p2.view.send_carry(carry_p2);
+    p3.view.send_carry(carry_p3);
 
     let o1 = input_p1 ^ k ^ carry_p1;
     let o2 = input_p2 ^ k ^ carry_p2;
@@ -100,9 +102,9 @@ pub fn mpc_add_mod<T: Value>(
     p2: &mut Party<T>,
     p3: &mut Party<T>,
 ) -> (GF2Word<T>, GF2Word<T>, GF2Word<T>) {
-    let rand_p1 = p1.read_tape();
-    let rand_p2 = p2.read_tape();
-    let rand_p3 = p3.read_tape();
+    let rand_p1 = p1.read_tape().expect(TAPE_LEN_MSG);
+    let rand_p2 = p2.read_tape().expect(TAPE_LEN_MSG);
+    let rand_p3 = p3.read_tape().expect(TAPE_LEN_MSG);
 
     let mut carry_p1: GF2Word<T> = T::zero().into();
     let mut carry_p2: GF2Word<T> = T::zero().into();
@@ -131,9 +133,9 @@ pub fn mpc_add_mod<T: Value>(
         carry_p3 = carry_p3.value.set_bit(i + 1, ci_p3.inner()).into();
     }
 
-    p1.view.send_msg(carry_p1);
-    p2.view.send_msg(carry_p2);
-    p3.view.send_msg(carry_p3);
+    p1.view.send_carry(carry_p1);
+    p2.view.send_carry(carry_p2);
+    p3.view.send_carry(carry_p3);
 
     let o1 = input_p1.0 ^ input_p1.1 ^ carry_p1;
     let o2 = input_p2.0 ^ input_p2.1 ^ carry_p2;
@@ -147,12 +149,12 @@ pub fn add_mod_verify<T: Value>(
     input_p_next: (GF2Word<T>, GF2Word<T>),
     p: &mut Party<T>,
     p_next: &mut Party<T>,
-) -> (GF2Word<T>, GF2Word<T>) {
-    let ri = p.read_tape();
-    let ri_next = p_next.read_tape();
+) -> Result<(GF2Word<T>, GF2Word<T>), Error> {
+    let ri = p.read_tape()?;
+    let ri_next = p_next.read_tape()?;
 
     let mut carry_p = T::zero().into();
-    let carry_p_next = p_next.view.read_next();
+    let carry_p_next = p_next.view.read_next_carry()?;
 
     for i in 0..T::bytes_len() * 8 - 1 {
         let ri_p = ri.value.get_bit(i);
@@ -170,12 +172,12 @@ pub fn add_mod_verify<T: Value>(
         carry_p = carry_p.value.set_bit(i + 1, ci_p.inner()).into();
     }
 
-    p.view.send_msg(carry_p);
+    p.view.send_carry(carry_p);
 
     let o1 = input_p.0 ^ input_p.1 ^ carry_p;
     let o2 = input_p_next.0 ^ input_p_next.1 ^ carry_p_next;
 
-    (o1, o2)
+    Ok((o1, o2))
 }
 
 pub fn add_mod_verify_k<T: Value>(
@@ -184,12 +186,12 @@ pub fn add_mod_verify_k<T: Value>(
     k: GF2Word<T>,
     p: &mut Party<T>,
     p_next: &mut Party<T>,
-) -> (GF2Word<T>, GF2Word<T>) {
-    let ri = p.read_tape();
-    let ri_next = p_next.read_tape();
+) -> Result<(GF2Word<T>, GF2Word<T>), Error> {
+    let ri = p.read_tape()?;
+    let ri_next = p_next.read_tape()?;
 
     let mut carry_p = T::zero().into();
-    let carry_p_next = p_next.view.read_next();
+    let carry_p_next = p_next.view.read_next_carry()?;
 
     for i in 0..T::bytes_len() * 8 - 1 {
         let ri_p = ri.value.get_bit(i);
@@ -207,12 +209,12 @@ pub fn add_mod_verify_k<T: Value>(
         carry_p = carry_p.value.set_bit(i + 1, ci_p.inner()).into();
     }
 
-    p.view.send_msg(carry_p);
+    p.view.send_carry(carry_p);
 
     let o1 = input_p ^ k ^ carry_p;
     let o2 = input_p_next ^ k ^ carry_p_next;
 
-    (o1, o2)
+    Ok((o1, o2))
 }
 
 #[cfg(test)]
@@ -235,7 +237,7 @@ mod adder_tests {
 
     impl<T: Value> Circuit<T> for AddModKCircuit<T> {
         fn compute(&self, input: &[u8]) -> Vec<GF2Word<T>> {
-            let input = generic_parse(input, self.party_input_len())[0];
+            let input = generic_parse(input, self.party_input_len()).unwrap()[0];
             let res = adder(input.value, self.k.value);
             vec![res.into()]
         }
@@ -246,9 +248,9 @@ mod adder_tests {
             p2: &mut Party<T>,
             p3: &mut Party<T>,
         ) -> (Vec<GF2Word<T>>, Vec<GF2Word<T>>, Vec<GF2Word<T>>) {
-            let input_p1 = generic_parse(&p1.view.input, self.party_input_len())[0];
-            let input_p2 = generic_parse(&p2.view.input, self.party_input_len())[0];
-            let input_p3 = generic_parse(&p3.view.input, self.party_input_len())[0];
+            let input_p1 = generic_parse(&p1.view.input, self.party_input_len()).unwrap()[0];
+            let input_p2 = generic_parse(&p2.view.input, self.party_input_len()).unwrap()[0];
+            let input_p3 = generic_parse(&p3.view.input, self.party_input_len()).unwrap()[0];
 
             let (o1, o2, o3) = mpc_add_mod_k(input_p1, input_p2, input_p3, self.k, p1, p2, p3);
             (vec![o1], vec![o2], vec![o3])
@@ -259,13 +261,17 @@ mod adder_tests {
             p: &mut Party<T>,
             p_next: &mut Party<T>,
         ) -> Result<(Output<T>, Output<T>), Error> {
-            let input_p = generic_parse(&p.view.input, self.party_input_len())[0];
-            let input_p_next = generic_parse(&p_next.view.input, self.party_input_len())[0];
+            let input_p = generic_parse(&p.view.input, self.party_input_len())?[0];
+            let input_p_next = generic_parse(&p_next.view.input, self.party_input_len())?[0];
 
-            let (o1, o2) = add_mod_verify_k(input_p, input_p_next, self.k, p, p_next);
+            let (o1, o2) = add_mod_verify_k(input_p, input_p_next, self.k, p, p_next)?;
             Ok((vec![o1], vec![o2]))
         }
 
+        fn description(&self) -> Vec<u8> {
+            [b"add_mod_k".as_slice(), &self.k.value.to_bytes()].concat()
+        }
+
         fn party_output_len(&self) -> usize {
             1
         }
@@ -281,18 +287,25 @@ mod adder_tests {
 
     #[cfg(test)]
     mod test_adder {
-        use rand::{rngs::ThreadRng, thread_rng};
+        use rand::thread_rng;
         use rand_chacha::ChaCha20Rng;
         use sha3::Keccak256;
 
-        use crate::{circuit::Circuit, prover::Prover, verifier::Verifier};
+        use crate::{
+            circuit::{Circuit, CircuitId},
+            error::Error,
+            fs::DomainSeparator,
+            prover::Prover,
+            security::SecurityParams,
+            verifier::Verifier,
+        };
 
         use super::AddModKCircuit;
 
         #[test]
         fn test_circuit() {
             let mut rng = thread_rng();
-            const SIGMA: usize = 80;
+            let params = SecurityParams::BITS_80;
             let input = 4294u32.to_le_bytes().to_vec();
 
             let circuit = AddModKCircuit::<u32> {
@@ -301,12 +314,59 @@ mod adder_tests {
 
             let output = circuit.compute(&input);
 
-            let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove::<ThreadRng, SIGMA>(
-                &mut rng, &input, &circuit, &output,
+            let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+            let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+                &mut rng, &input, &circuit, &output, &params, &dst,
             )
             .unwrap();
 
-            Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output).unwrap();
+            Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                &proof, &circuit, &output, &params, &dst,
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn test_circuit_mismatch() {
+            let mut rng = thread_rng();
+            let params = SecurityParams::BITS_40;
+            let input = 4294u32.to_le_bytes().to_vec();
+
+            // same shape and gate count, different constant
+            let circuit = AddModKCircuit::<u32> { k: 0u32.into() };
+            let other_circuit = AddModKCircuit::<u32> { k: 4294u32.into() };
+
+            // x + 4294 for x = 0 has the same output as x + 0 for x = 4294
+            let output = circuit.compute(&input);
+            assert_eq!(output, other_circuit.compute(&0u32.to_le_bytes()));
+
+            let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+            let mut proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+                &mut rng, &input, &circuit, &output, &params, &dst,
+            )
+            .unwrap();
+
+            assert!(matches!(
+                Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                    &proof,
+                    &other_circuit,
+                    &output,
+                    &params,
+                    &dst
+                ),
+                Err(Error::CircuitMismatchError)
+            ));
+
+            // the id is bound by Fiat-Shamir, so replacing it does not help
+            proof.circuit_id = CircuitId::new::<u32, Keccak256>(&other_circuit);
+            assert!(Verifier::<u32, ChaCha20Rng, Keccak256>::verify(
+                &proof,
+                &other_circuit,
+                &output,
+                &params,
+                &dst
+            )
+            .is_err());
         }
     }
 }
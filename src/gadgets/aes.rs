@@ -0,0 +1,537 @@
+use std::convert::Infallible;
+
+use crate::{
+    error::Error,
+    gadgets::{mpc_and, mpc_and_verify},
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Bytes of a block.
+pub const BLOCK_LEN: usize = 16;
+
+/// Mul gates of one layer of S-boxes: the 32 ANDs of the Boyar–Peralta
+/// circuit, on bitsliced words that hold up to 32 bytes.
+pub const MUL_GATES_PER_SBOX_LAYER: usize = 32;
+
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// A block, or the state, in the byte order of FIPS 197: column by column.
+pub type Block = [u8; BLOCK_LEN];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySize {
+    Aes128,
+    Aes256,
+}
+
+impl KeySize {
+    pub fn key_len(&self) -> usize {
+        match self {
+            KeySize::Aes128 => 16,
+            KeySize::Aes256 => 32,
+        }
+    }
+
+    pub fn rounds(&self) -> usize {
+        match self {
+            KeySize::Aes128 => 10,
+            KeySize::Aes256 => 14,
+        }
+    }
+
+    /// Words of the key, Nk.
+    fn key_words(&self) -> usize {
+        self.key_len() / 4
+    }
+
+    /// Whether the `i`-th word of the key expansion goes through SubWord.
+    fn sub_word_at(&self, i: usize) -> bool {
+        let nk = self.key_words();
+        i.is_multiple_of(nk) || (nk > 6 && i % nk == 4)
+    }
+
+    /// Layers of S-boxes: SubBytes in every round and SubWord in the key
+    /// expansion.
+    pub fn num_of_sbox_layers(&self) -> usize {
+        let sub_words = (self.key_words()..4 * (self.rounds() + 1))
+            .filter(|&i| self.sub_word_at(i))
+            .count();
+        self.rounds() + sub_words
+    }
+}
+
+/// One bitsliced word in each of `N` shares: the plain value for N = 1, the
+/// three parties of the prover, or the two parties opened to the verifier.
+type Shares<const N: usize> = [GF2Word<u32>; N];
+
+fn xor<const N: usize>(a: Shares<N>, b: Shares<N>) -> Shares<N> {
+    std::array::from_fn(|p| a[p] ^ b[p])
+}
+
+/// Complement every share, with a XOR by all ones.
+fn not<const N: usize>(a: Shares<N>) -> Shares<N> {
+    a.map(|a| a ^ u32::MAX.into())
+}
+
+/// The bits of up to 32 bytes: the `i`-th word holds bit `7 - i` of the byte
+/// in each lane, so that word 0 is the most significant bit.
+fn bitslice(bytes: &[u8]) -> [GF2Word<u32>; 8] {
+    assert!(bytes.len() <= 32);
+
+    let mut bits = [0u32; 8];
+    for (lane, byte) in bytes.iter().enumerate() {
+        for (i, bit) in bits.iter_mut().enumerate() {
+            *bit |= (((byte >> (7 - i)) & 1) as u32) << lane;
+        }
+    }
+    bits.map(GF2Word::from)
+}
+
+fn unbitslice(bits: &[GF2Word<u32>; 8], bytes: &mut [u8]) {
+    for (lane, byte) in bytes.iter_mut().enumerate() {
+        *byte = 0;
+        for (i, bit) in bits.iter().enumerate() {
+            *byte |= (((bit.value >> lane) & 1) as u8) << (7 - i);
+        }
+    }
+}
+
+/// The AES S-box as the circuit of Boyar and Peralta, with 32 ANDs and 83
+/// XORs or XNORs, on bitsliced bits `x[0]` (most significant) to `x[7]`.
+/// Only `and` differs between the native and the MPC evaluations.
+fn sbox<const N: usize, E>(
+    x: [Shares<N>; 8],
+    and: &mut impl FnMut(Shares<N>, Shares<N>) -> Result<Shares<N>, E>,
+) -> Result<[Shares<N>; 8], E> {
+    let [x0, x1, x2, x3, x4, x5, x6, x7] = x;
+
+    // top linear transformation
+    let y14 = xor(x3, x5);
+    let y13 = xor(x0, x6);
+    let y9 = xor(x0, x3);
+    let y8 = xor(x0, x5);
+    let t0 = xor(x1, x2);
+    let y1 = xor(t0, x7);
+    let y4 = xor(y1, x3);
+    let y12 = xor(y13, y14);
+    let y2 = xor(y1, x0);
+    let y5 = xor(y1, x6);
+    let y3 = xor(y5, y8);
+    let t1 = xor(x4, y12);
+    let y15 = xor(t1, x5);
+    let y20 = xor(t1, x1);
+    let y6 = xor(y15, x7);
+    let y10 = xor(y15, t0);
+    let y11 = xor(y20, y9);
+    let y7 = xor(x7, y11);
+    let y17 = xor(y10, y11);
+    let y19 = xor(y10, y8);
+    let y16 = xor(t0, y11);
+    let y21 = xor(y13, y16);
+    let y18 = xor(x0, y16);
+
+    // inversion in GF(2^4)^2
+    let t2 = and(y12, y15)?;
+    let t3 = and(y3, y6)?;
+    let t4 = xor(t3, t2);
+    let t5 = and(y4, x7)?;
+    let t6 = xor(t5, t2);
+    let t7 = and(y13, y16)?;
+    let t8 = and(y5, y1)?;
+    let t9 = xor(t8, t7);
+    let t10 = and(y2, y7)?;
+    let t11 = xor(t10, t7);
+    let t12 = and(y9, y11)?;
+    let t13 = and(y14, y17)?;
+    let t14 = xor(t13, t12);
+    let t15 = and(y8, y10)?;
+    let t16 = xor(t15, t12);
+    let t17 = xor(t4, t14);
+    let t18 = xor(t6, t16);
+    let t19 = xor(t9, t14);
+    let t20 = xor(t11, t16);
+    let t21 = xor(t17, y20);
+    let t22 = xor(t18, y19);
+    let t23 = xor(t19, y21);
+    let t24 = xor(t20, y18);
+    let t25 = xor(t21, t22);
+    let t26 = and(t21, t23)?;
+    let t27 = xor(t24, t26);
+    let t28 = and(t25, t27)?;
+    let t29 = xor(t28, t22);
+    let t30 = xor(t23, t24);
+    let t31 = xor(t22, t26);
+    let t32 = and(t31, t30)?;
+    let t33 = xor(t32, t24);
+    let t34 = xor(t23, t33);
+    let t35 = xor(t27, t33);
+    let t36 = and(t24, t35)?;
+    let t37 = xor(t36, t34);
+    let t38 = xor(t27, t36);
+    let t39 = and(t29, t38)?;
+    let t40 = xor(t25, t39);
+    let t41 = xor(t40, t37);
+    let t42 = xor(t29, t33);
+    let t43 = xor(t29, t40);
+    let t44 = xor(t33, t37);
+    let t45 = xor(t42, t41);
+    let z0 = and(t44, y15)?;
+    let z1 = and(t37, y6)?;
+    let z2 = and(t33, x7)?;
+    let z3 = and(t43, y16)?;
+    let z4 = and(t40, y1)?;
+    let z5 = and(t29, y7)?;
+    let z6 = and(t42, y11)?;
+    let z7 = and(t45, y17)?;
+    let z8 = and(t41, y10)?;
+    let z9 = and(t44, y12)?;
+    let z10 = and(t37, y3)?;
+    let z11 = and(t33, y4)?;
+    let z12 = and(t43, y13)?;
+    let z13 = and(t40, y5)?;
+    let z14 = and(t29, y2)?;
+    let z15 = and(t42, y9)?;
+    let z16 = and(t45, y14)?;
+    let z17 = and(t41, y8)?;
+
+    // bottom linear transformation
+    let t46 = xor(z15, z16);
+    let t47 = xor(z10, z11);
+    let t48 = xor(z5, z13);
+    let t49 = xor(z9, z10);
+    let t50 = xor(z2, z12);
+    let t51 = xor(z2, z5);
+    let t52 = xor(z7, z8);
+    let t53 = xor(z0, z3);
+    let t54 = xor(z6, z7);
+    let t55 = xor(z16, z17);
+    let t56 = xor(z12, t48);
+    let t57 = xor(t50, t53);
+    let t58 = xor(z4, t46);
+    let t59 = xor(z3, t54);
+    let t60 = xor(t46, t57);
+    let t61 = xor(z14, t57);
+    let t62 = xor(t52, t58);
+    let t63 = xor(t49, t58);
+    let t64 = xor(z4, t59);
+    let t65 = xor(t61, t62);
+    let t66 = xor(z1, t63);
+    let s0 = xor(t59, t63);
+    let s6 = not(xor(t56, t62));
+    let s7 = not(xor(t48, t60));
+    let t67 = xor(t64, t65);
+    let s3 = xor(t53, t66);
+    let s4 = xor(t51, t66);
+    let s5 = xor(t47, t65);
+    let s1 = not(xor(t64, s3));
+    let s2 = not(xor(t55, t67));
+
+    Ok([s0, s1, s2, s3, s4, s5, s6, s7])
+}
+
+/// Substitute every byte of the `N` shares, up to 32 bytes each, with one
+/// layer of S-boxes. Bitslicing is a permutation of bits, and so applies to
+/// each share on its own.
+fn sub_bytes_with<const N: usize, E>(
+    shares: [&mut [u8]; N],
+    and: &mut impl FnMut(Shares<N>, Shares<N>) -> Result<Shares<N>, E>,
+) -> Result<(), E> {
+    let sliced = shares.each_ref().map(|share| bitslice(share));
+    let x = std::array::from_fn(|i| std::array::from_fn(|p| sliced[p][i]));
+
+    let s = sbox(x, and)?;
+    for (p, share) in shares.into_iter().enumerate() {
+        unbitslice(&s.map(|word| word[p]), share);
+    }
+    Ok(())
+}
+
+/// Cyclically shift row `r` of the state left by `r` bytes.
+pub fn shift_rows(state: &mut Block) {
+    let old = *state;
+    for c in 0..4 {
+        for r in 0..4 {
+            state[r + 4 * c] = old[r + 4 * ((c + r) % 4)];
+        }
+    }
+}
+
+/// Multiplication by x in GF(2^8), which is linear.
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ if b & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// Multiply every column of the state by the MDS matrix, which is linear.
+pub fn mix_columns(state: &mut Block) {
+    for column in state.chunks_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        let all = a0 ^ a1 ^ a2 ^ a3;
+        column[0] ^= all ^ xtime(a0 ^ a1);
+        column[1] ^= all ^ xtime(a1 ^ a2);
+        column[2] ^= all ^ xtime(a2 ^ a3);
+        column[3] ^= all ^ xtime(a3 ^ a0);
+    }
+}
+
+pub fn add_round_key(state: &mut Block, round_key: &Block) {
+    for (s, k) in state.iter_mut().zip(round_key) {
+        *s ^= k;
+    }
+}
+
+fn expand_key_with<const N: usize, E>(
+    key: [&[u8]; N],
+    key_size: KeySize,
+    and: &mut impl FnMut(Shares<N>, Shares<N>) -> Result<Shares<N>, E>,
+) -> Result<Vec<[Block; N]>, E> {
+    for key in key {
+        assert_eq!(key.len(), key_size.key_len());
+    }
+
+    let nk = key_size.key_words();
+    let mut w: Vec<[[u8; 4]; N]> = (0..nk)
+        .map(|i| key.map(|key| key[4 * i..4 * (i + 1)].try_into().unwrap()))
+        .collect();
+
+    for i in nk..4 * (key_size.rounds() + 1) {
+        let mut temp = w[i - 1];
+        if key_size.sub_word_at(i) {
+            if i.is_multiple_of(nk) {
+                temp.iter_mut().for_each(|word| word.rotate_left(1));
+            }
+            sub_bytes_with(temp.each_mut().map(|word| word.as_mut_slice()), and)?;
+            if i.is_multiple_of(nk) {
+                temp.iter_mut().for_each(|word| word[0] ^= RCON[i / nk - 1]);
+            }
+        }
+
+        let previous = w[i - nk];
+        w.push(std::array::from_fn(|p| {
+            std::array::from_fn(|j| previous[p][j] ^ temp[p][j])
+        }));
+    }
+
+    Ok(w.chunks(4)
+        .map(|words| {
+            std::array::from_fn(|p| {
+                let mut round_key = [0u8; BLOCK_LEN];
+                for (chunk, word) in round_key.chunks_mut(4).zip(words) {
+                    chunk.copy_from_slice(&word[p]);
+                }
+                round_key
+            })
+        })
+        .collect())
+}
+
+fn encrypt_with<const N: usize, E>(
+    round_keys: &[[Block; N]],
+    plaintext: &Block,
+    and: &mut impl FnMut(Shares<N>, Shares<N>) -> Result<Shares<N>, E>,
+) -> Result<[Block; N], E> {
+    let mut state = [*plaintext; N];
+    for (state, round_key) in state.iter_mut().zip(&round_keys[0]) {
+        add_round_key(state, round_key);
+    }
+
+    let rounds = round_keys.len() - 1;
+    for (r, round_keys) in round_keys.iter().enumerate().skip(1) {
+        sub_bytes_with(state.each_mut().map(|state| state.as_mut_slice()), and)?;
+        for (state, round_key) in state.iter_mut().zip(round_keys) {
+            shift_rows(state);
+            if r < rounds {
+                mix_columns(state);
+            }
+            add_round_key(state, round_key);
+        }
+    }
+
+    Ok(state)
+}
+
+fn native_and(a: Shares<1>, b: Shares<1>) -> Result<Shares<1>, Infallible> {
+    Ok([a[0] & b[0]])
+}
+
+/// Natively substitute every byte of up to 32 bytes.
+pub fn sub_bytes(bytes: &mut [u8]) {
+    let Ok(()) = sub_bytes_with([bytes], &mut native_and);
+}
+
+/// Natively expand `key` into the round keys.
+pub fn expand_key(key: &[u8], key_size: KeySize) -> Vec<Block> {
+    let Ok(round_keys) = expand_key_with([key], key_size, &mut native_and);
+    round_keys
+        .into_iter()
+        .map(|[round_key]| round_key)
+        .collect()
+}
+
+/// Expand shares of the key into shares of the round keys, with one layer of
+/// S-boxes for every SubWord.
+pub fn mpc_expand_key(
+    key: (&[u8], &[u8], &[u8]),
+    key_size: KeySize,
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (Vec<Block>, Vec<Block>, Vec<Block>) {
+    let mut and = |a: Shares<3>, b: Shares<3>| -> Result<_, Infallible> {
+        let (o1, o2, o3) = mpc_and((a[0], b[0]), (a[1], b[1]), (a[2], b[2]), p1, p2, p3);
+        Ok([o1, o2, o3])
+    };
+    let Ok(round_keys) = expand_key_with([key.0, key.1, key.2], key_size, &mut and);
+
+    (
+        round_keys.iter().map(|round_key| round_key[0]).collect(),
+        round_keys.iter().map(|round_key| round_key[1]).collect(),
+        round_keys.iter().map(|round_key| round_key[2]).collect(),
+    )
+}
+
+pub fn mpc_expand_key_verify(
+    key: (&[u8], &[u8]),
+    key_size: KeySize,
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(Vec<Block>, Vec<Block>), Error> {
+    let round_keys = expand_key_with([key.0, key.1], key_size, &mut |a, b| {
+        let (o, o_next) = mpc_and_verify((a[0], b[0]), (a[1], b[1]), p, p_next)?;
+        Ok([o, o_next])
+    })?;
+
+    Ok((
+        round_keys.iter().map(|round_key| round_key[0]).collect(),
+        round_keys.iter().map(|round_key| round_key[1]).collect(),
+    ))
+}
+
+/// Natively encrypt a block with expanded round keys.
+pub fn encrypt(round_keys: &[Block], plaintext: &Block) -> Block {
+    let round_keys: Vec<[Block; 1]> = round_keys.iter().map(|round_key| [*round_key]).collect();
+    let Ok([ciphertext]) = encrypt_with(&round_keys, plaintext, &mut native_and);
+    ciphertext
+}
+
+/// Encrypt a public block with shares of the round keys, with one layer of
+/// S-boxes for every round.
+pub fn mpc_encrypt(
+    round_keys: (&[Block], &[Block], &[Block]),
+    plaintext: &Block,
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (Block, Block, Block) {
+    let round_keys: Vec<[Block; 3]> = (0..round_keys.0.len())
+        .map(|r| [round_keys.0[r], round_keys.1[r], round_keys.2[r]])
+        .collect();
+
+    let mut and = |a: Shares<3>, b: Shares<3>| -> Result<_, Infallible> {
+        let (o1, o2, o3) = mpc_and((a[0], b[0]), (a[1], b[1]), (a[2], b[2]), p1, p2, p3);
+        Ok([o1, o2, o3])
+    };
+    let Ok([c1, c2, c3]) = encrypt_with(&round_keys, plaintext, &mut and);
+    (c1, c2, c3)
+}
+
+pub fn mpc_encrypt_verify(
+    round_keys: (&[Block], &[Block]),
+    plaintext: &Block,
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(Block, Block), Error> {
+    let round_keys: Vec<[Block; 2]> = (0..round_keys.0.len())
+        .map(|r| [round_keys.0[r], round_keys.1[r]])
+        .collect();
+
+    let [c, c_next] = encrypt_with(&round_keys, plaintext, &mut |a, b| {
+        let (o, o_next) = mpc_and_verify((a[0], b[0]), (a[1], b[1]), p, p_next)?;
+        Ok([o, o_next])
+    })?;
+    Ok((c, c_next))
+}
+
+#[cfg(test)]
+mod test_aes {
+    use super::{
+        encrypt, expand_key, mpc_encrypt, mpc_encrypt_verify, mpc_expand_key,
+        mpc_expand_key_verify, sub_bytes, Block, KeySize, MUL_GATES_PER_SBOX_LAYER,
+    };
+    use crate::{
+        circuit::Output,
+        gadgets::{prepare::generic_parse, test_utils::check_shares},
+    };
+
+    fn words(block: &Block) -> Output<u32> {
+        generic_parse(block, 4).unwrap()
+    }
+
+    #[test]
+    fn test_sub_bytes() {
+        // a few entries of the S-box table of FIPS 197
+        let mut bytes = [0x00, 0x01, 0x53, 0x7c, 0xff];
+        sub_bytes(&mut bytes);
+        assert_eq!(bytes, [0x63, 0x7c, 0xed, 0x10, 0x16]);
+    }
+
+    #[test]
+    fn test_encrypt() {
+        // FIPS 197, appendix B
+        let key = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+            0x4f, 0x3c,
+        ];
+        let plaintext = [
+            0x32, 0x43, 0xf6, 0xa8, 0x88, 0x5a, 0x30, 0x8d, 0x31, 0x31, 0x98, 0xa2, 0xe0, 0x37,
+            0x07, 0x34,
+        ];
+
+        let round_keys = expand_key(&key, KeySize::Aes128);
+        assert_eq!(
+            round_keys[10],
+            [
+                0xd0, 0x14, 0xf9, 0xa8, 0xc9, 0xee, 0x25, 0x89, 0xe1, 0x3f, 0x0c, 0xc8, 0xb6, 0x63,
+                0x0c, 0xa6
+            ]
+        );
+        assert_eq!(
+            encrypt(&round_keys, &plaintext),
+            [
+                0x39, 0x25, 0x84, 0x1d, 0x02, 0xdc, 0x09, 0xfb, 0xdc, 0x11, 0x85, 0x97, 0x19, 0x6a,
+                0x0b, 0x32
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mpc_encrypt() {
+        let plaintext: Block = std::array::from_fn(|i| 0x11 * i as u8);
+
+        for key_size in [KeySize::Aes128, KeySize::Aes256] {
+            let key: Vec<u8> = (0..key_size.key_len() as u8).collect();
+
+            check_shares(
+                &key,
+                MUL_GATES_PER_SBOX_LAYER * key_size.num_of_sbox_layers(),
+                |key| words(&encrypt(&expand_key(key, key_size), &plaintext)),
+                |p1, p2, p3| {
+                    let key = (
+                        p1.view.input.clone(),
+                        p2.view.input.clone(),
+                        p3.view.input.clone(),
+                    );
+                    let k = mpc_expand_key((&key.0, &key.1, &key.2), key_size, p1, p2, p3);
+                    let (c1, c2, c3) = mpc_encrypt((&k.0, &k.1, &k.2), &plaintext, p1, p2, p3);
+                    (words(&c1), words(&c2), words(&c3))
+                },
+                |p, p_next| {
+                    let key = (p.view.input.clone(), p_next.view.input.clone());
+                    let k = mpc_expand_key_verify((&key.0, &key.1), key_size, p, p_next)?;
+                    let (c, c_next) = mpc_encrypt_verify((&k.0, &k.1), &plaintext, p, p_next)?;
+                    Ok((words(&c), words(&c_next)))
+                },
+            );
+        }
+    }
+}
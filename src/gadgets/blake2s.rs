@@ -0,0 +1,407 @@
+use crate::{
+    error::Error,
+    gadgets::add_mod::{add_mod_verify, mpc_add_mod},
+    gf2_word::{BitUtils, GF2Word},
+    party::Party,
+};
+
+/// Initial value of BLAKE2s, and of BLAKE3, which is the one of SHA-256.
+pub(crate) const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Message schedule of each of the 10 rounds.
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Positions `(a, b, c, d)` of the G functions of a round, on the columns and
+/// then on the diagonals of the working vector seen as a 4x4 matrix.
+const G_POSITIONS: [(usize, usize, usize, usize); 8] = [
+    (0, 4, 8, 12),
+    (1, 5, 9, 13),
+    (2, 6, 10, 14),
+    (3, 7, 11, 15),
+    (0, 5, 10, 15),
+    (1, 6, 11, 12),
+    (2, 7, 8, 13),
+    (3, 4, 9, 14),
+];
+
+/// Mul gates of one G function, which adds 6 words.
+pub const MUL_GATES_PER_G: usize = 6;
+
+/// Mul gates of one round, made of 8 G functions.
+pub const MUL_GATES_PER_ROUND: usize = 8 * MUL_GATES_PER_G;
+
+/// Number of rounds of the BLAKE2s compression function.
+pub const ROUNDS: usize = 10;
+
+/// The 16 words of the working vector of a compression.
+pub type Vector = [GF2Word<u32>; 16];
+
+/// The 16 message words of a block.
+pub type Block = [GF2Word<u32>; 16];
+
+/// Read a block of up to 64 bytes as 16 little-endian words, padded with 0.
+pub fn block_words(block: &[u8]) -> Block {
+    let mut bytes = [0u8; 64];
+    bytes[..block.len()].copy_from_slice(block);
+
+    let mut words = [GF2Word::from(0u32); 16];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap()).into();
+    }
+    words
+}
+
+/// `x rightrotate n` of `a xor b`.
+fn xor_rotr(a: GF2Word<u32>, b: GF2Word<u32>, n: usize) -> GF2Word<u32> {
+    (a ^ b).value.right_rotate(n).into()
+}
+
+/// Natively apply one round to `v`, where the G function at position `i`
+/// mixes the message words `m[2i]` and `m[2i + 1]`.
+pub fn round(v: &mut Vector, m: &Block) {
+    for (i, &(a, b, c, d)) in G_POSITIONS.iter().enumerate() {
+        let (x, y) = (m[2 * i], m[2 * i + 1]);
+
+        v[a] = v[a]
+            .value
+            .wrapping_add(v[b].value)
+            .wrapping_add(x.value)
+            .into();
+        v[d] = xor_rotr(v[d], v[a], 16);
+        v[c] = v[c].value.wrapping_add(v[d].value).into();
+        v[b] = xor_rotr(v[b], v[c], 12);
+        v[a] = v[a]
+            .value
+            .wrapping_add(v[b].value)
+            .wrapping_add(y.value)
+            .into();
+        v[d] = xor_rotr(v[d], v[a], 8);
+        v[c] = v[c].value.wrapping_add(v[d].value).into();
+        v[b] = xor_rotr(v[b], v[c], 7);
+    }
+}
+
+/// One round on shares of `v`, with `MUL_GATES_PER_ROUND` mul gates.
+pub fn mpc_round(
+    v: (&mut Vector, &mut Vector, &mut Vector),
+    m: (&Block, &Block, &Block),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) {
+    let (v1, v2, v3) = v;
+    let (m1, m2, m3) = m;
+
+    for (i, &(a, b, c, d)) in G_POSITIONS.iter().enumerate() {
+        // a := a + b + x
+        (v1[a], v2[a], v3[a]) =
+            mpc_add_mod((v1[a], v1[b]), (v2[a], v2[b]), (v3[a], v3[b]), p1, p2, p3);
+        (v1[a], v2[a], v3[a]) = mpc_add_mod(
+            (v1[a], m1[2 * i]),
+            (v2[a], m2[2 * i]),
+            (v3[a], m3[2 * i]),
+            p1,
+            p2,
+            p3,
+        );
+
+        // d := (d xor a) rightrotate 16, c := c + d, b := (b xor c) rightrotate 12
+        (v1[d], v2[d], v3[d]) = (
+            xor_rotr(v1[d], v1[a], 16),
+            xor_rotr(v2[d], v2[a], 16),
+            xor_rotr(v3[d], v3[a], 16),
+        );
+        (v1[c], v2[c], v3[c]) =
+            mpc_add_mod((v1[c], v1[d]), (v2[c], v2[d]), (v3[c], v3[d]), p1, p2, p3);
+        (v1[b], v2[b], v3[b]) = (
+            xor_rotr(v1[b], v1[c], 12),
+            xor_rotr(v2[b], v2[c], 12),
+            xor_rotr(v3[b], v3[c], 12),
+        );
+
+        // a := a + b + y
+        (v1[a], v2[a], v3[a]) =
+            mpc_add_mod((v1[a], v1[b]), (v2[a], v2[b]), (v3[a], v3[b]), p1, p2, p3);
+        (v1[a], v2[a], v3[a]) = mpc_add_mod(
+            (v1[a], m1[2 * i + 1]),
+            (v2[a], m2[2 * i + 1]),
+            (v3[a], m3[2 * i + 1]),
+            p1,
+            p2,
+            p3,
+        );
+
+        // d := (d xor a) rightrotate 8, c := c + d, b := (b xor c) rightrotate 7
+        (v1[d], v2[d], v3[d]) = (
+            xor_rotr(v1[d], v1[a], 8),
+            xor_rotr(v2[d], v2[a], 8),
+            xor_rotr(v3[d], v3[a], 8),
+        );
+        (v1[c], v2[c], v3[c]) =
+            mpc_add_mod((v1[c], v1[d]), (v2[c], v2[d]), (v3[c], v3[d]), p1, p2, p3);
+        (v1[b], v2[b], v3[b]) = (
+            xor_rotr(v1[b], v1[c], 7),
+            xor_rotr(v2[b], v2[c], 7),
+            xor_rotr(v3[b], v3[c], 7),
+        );
+    }
+}
+
+pub fn mpc_round_verify(
+    v: (&mut Vector, &mut Vector),
+    m: (&Block, &Block),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(), Error> {
+    let (v, v_next) = v;
+    let (m, m_next) = m;
+
+    for (i, &(a, b, c, d)) in G_POSITIONS.iter().enumerate() {
+        (v[a], v_next[a]) = add_mod_verify((v[a], v[b]), (v_next[a], v_next[b]), p, p_next)?;
+        (v[a], v_next[a]) =
+            add_mod_verify((v[a], m[2 * i]), (v_next[a], m_next[2 * i]), p, p_next)?;
+
+        (v[d], v_next[d]) = (xor_rotr(v[d], v[a], 16), xor_rotr(v_next[d], v_next[a], 16));
+        (v[c], v_next[c]) = add_mod_verify((v[c], v[d]), (v_next[c], v_next[d]), p, p_next)?;
+        (v[b], v_next[b]) = (xor_rotr(v[b], v[c], 12), xor_rotr(v_next[b], v_next[c], 12));
+
+        (v[a], v_next[a]) = add_mod_verify((v[a], v[b]), (v_next[a], v_next[b]), p, p_next)?;
+        (v[a], v_next[a]) = add_mod_verify(
+            (v[a], m[2 * i + 1]),
+            (v_next[a], m_next[2 * i + 1]),
+            p,
+            p_next,
+        )?;
+
+        (v[d], v_next[d]) = (xor_rotr(v[d], v[a], 8), xor_rotr(v_next[d], v_next[a], 8));
+        (v[c], v_next[c]) = add_mod_verify((v[c], v[d]), (v_next[c], v_next[d]), p, p_next)?;
+        (v[b], v_next[b]) = (xor_rotr(v[b], v[c], 7), xor_rotr(v_next[b], v_next[c], 7));
+    }
+
+    Ok(())
+}
+
+/// Chaining value of BLAKE2s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    h: [GF2Word<u32>; 8],
+}
+
+impl State {
+    pub fn to_vec(&self) -> Vec<GF2Word<u32>> {
+        self.h.to_vec()
+    }
+}
+
+/// Initial state of unkeyed BLAKE2s with a digest of `output_len` bytes,
+/// which mixes the parameter block into the IV.
+pub fn init_iv(output_len: usize) -> State {
+    assert!((1..=32).contains(&output_len));
+
+    let mut h = IV;
+    h[0] ^= 0x01010000 ^ output_len as u32;
+    State {
+        h: h.map(GF2Word::from),
+    }
+}
+
+/// Parameters of the compression of one block, all public.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockParams {
+    /// Bytes of the message compressed so far, this block included
+    pub counter: u64,
+    /// Whether this is the last block of the message
+    pub last: bool,
+}
+
+/// The working vector: the state, then the IV with the counter and the
+/// finalization flag.
+fn init_vector(state: &State, params: &BlockParams) -> Vector {
+    let mut tail = IV;
+    tail[4] ^= params.counter as u32;
+    tail[5] ^= (params.counter >> 32) as u32;
+    if params.last {
+        tail[6] ^= u32::MAX;
+    }
+
+    let mut v = [GF2Word::from(0u32); 16];
+    v[..8].copy_from_slice(&state.h);
+    for (v, word) in v[8..].iter_mut().zip(tail) {
+        *v = word.into();
+    }
+    v
+}
+
+/// h[i] := h[i] xor v[i] xor v[i + 8], linear and so applied to each share.
+fn finalize(state: &State, v: &Vector) -> State {
+    let mut h = state.h;
+    for (i, h) in h.iter_mut().enumerate() {
+        *h = *h ^ v[i] ^ v[i + 8];
+    }
+    State { h }
+}
+
+fn scheduled(m: &Block, r: usize) -> Block {
+    SIGMA[r].map(|i| m[i])
+}
+
+/// Natively compress the block `m` into `state`.
+pub fn compress(state: &State, m: &Block, params: &BlockParams) -> State {
+    let mut v = init_vector(state, params);
+    for r in 0..ROUNDS {
+        round(&mut v, &scheduled(m, r));
+    }
+
+    finalize(state, &v)
+}
+
+/// Compress shares of the block `m` into shares of `state`, with
+/// `ROUNDS * MUL_GATES_PER_ROUND` mul gates.
+pub fn mpc_compress(
+    state: &(State, State, State),
+    m: (&Block, &Block, &Block),
+    params: &BlockParams,
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let mut v1 = init_vector(&state.0, params);
+    let mut v2 = init_vector(&state.1, params);
+    let mut v3 = init_vector(&state.2, params);
+
+    for r in 0..ROUNDS {
+        mpc_round(
+            (&mut v1, &mut v2, &mut v3),
+            (&scheduled(m.0, r), &scheduled(m.1, r), &scheduled(m.2, r)),
+            p1,
+            p2,
+            p3,
+        );
+    }
+
+    (
+        finalize(&state.0, &v1),
+        finalize(&state.1, &v2),
+        finalize(&state.2, &v3),
+    )
+}
+
+pub fn mpc_compress_verify(
+    state: &(State, State),
+    m: (&Block, &Block),
+    params: &BlockParams,
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let mut v = init_vector(&state.0, params);
+    let mut v_next = init_vector(&state.1, params);
+
+    for r in 0..ROUNDS {
+        mpc_round_verify(
+            (&mut v, &mut v_next),
+            (&scheduled(m.0, r), &scheduled(m.1, r)),
+            p,
+            p_next,
+        )?;
+    }
+
+    Ok((finalize(&state.0, &v), finalize(&state.1, &v_next)))
+}
+
+/// Blocks of a message of `message_len` bytes, with parameters. The empty
+/// message is one block of zeros.
+pub fn blocks(message_len: usize) -> Vec<(std::ops::Range<usize>, BlockParams)> {
+    let num_of_blocks = message_len.div_ceil(64).max(1);
+    (0..num_of_blocks)
+        .map(|i| {
+            let end = (64 * (i + 1)).min(message_len);
+            let params = BlockParams {
+                counter: end as u64,
+                last: i + 1 == num_of_blocks,
+            };
+            (64 * i..end, params)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_blake2s {
+    use super::{
+        block_words, blocks, compress, init_iv, mpc_compress, mpc_compress_verify, BlockParams,
+        MUL_GATES_PER_ROUND, ROUNDS,
+    };
+    use crate::gadgets::{mpc_public, mpc_public_verify, test_utils::check_shares};
+
+    #[test]
+    fn test_compress() {
+        // RFC 7693 example for "abc"
+        let mut state = init_iv(32);
+        for (range, params) in blocks(3) {
+            state = compress(&state, &block_words(&b"abc"[range]), &params);
+        }
+
+        let digest: Vec<u8> = state
+            .to_vec()
+            .iter()
+            .flat_map(|word| word.value.to_le_bytes())
+            .collect();
+        assert_eq!(
+            digest,
+            [
+                0x50, 0x8c, 0x5e, 0x8c, 0x32, 0x7c, 0x14, 0xe2, 0xe1, 0xa7, 0x2b, 0xa3, 0x4e, 0xeb,
+                0x45, 0x2f, 0x37, 0x45, 0x8b, 0x20, 0x9e, 0xd6, 0x3a, 0x29, 0x4d, 0x99, 0x9b, 0x4c,
+                0x86, 0x67, 0x59, 0x82
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mpc_compress() {
+        let input: Vec<u8> = (0..64).collect();
+        let state = init_iv(32);
+        let params = BlockParams {
+            counter: 64,
+            last: true,
+        };
+
+        check_shares(
+            &input,
+            ROUNDS * MUL_GATES_PER_ROUND,
+            |input| compress(&state, &block_words(input), &params).to_vec(),
+            |p1, p2, p3| {
+                let m = (
+                    block_words(&p1.view.input),
+                    block_words(&p2.view.input),
+                    block_words(&p3.view.input),
+                );
+                let (s1, s2, s3) =
+                    mpc_compress(&mpc_public(state), (&m.0, &m.1, &m.2), &params, p1, p2, p3);
+                (s1.to_vec(), s2.to_vec(), s3.to_vec())
+            },
+            |p, p_next| {
+                let m = (block_words(&p.view.input), block_words(&p_next.view.input));
+                let (s, s_next) = mpc_compress_verify(
+                    &mpc_public_verify(state),
+                    (&m.0, &m.1),
+                    &params,
+                    p,
+                    p_next,
+                )?;
+                Ok((s.to_vec(), s_next.to_vec()))
+            },
+        );
+    }
+}
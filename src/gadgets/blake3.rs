@@ -0,0 +1,231 @@
+use std::ops::Range;
+
+use crate::{
+    error::Error,
+    gadgets::blake2s::{mpc_round, mpc_round_verify, round, Block, Vector, IV},
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+pub use crate::gadgets::blake2s::block_words;
+
+/// Number of rounds of the BLAKE3 compression function.
+pub const ROUNDS: usize = 7;
+
+/// Bytes of a block, and of a chunk, the leaves of the BLAKE3 tree.
+pub const BLOCK_LEN: usize = 64;
+pub const CHUNK_LEN: usize = 1024;
+
+/// Domain separation flags.
+pub const CHUNK_START: u32 = 1 << 0;
+pub const CHUNK_END: u32 = 1 << 1;
+pub const ROOT: u32 = 1 << 3;
+
+/// Permutation of the message words between two rounds.
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+/// Chaining value between the compressions of the blocks of a chunk.
+pub type ChainingValue = [GF2Word<u32>; 8];
+
+pub fn init_cv() -> ChainingValue {
+    IV.map(GF2Word::from)
+}
+
+/// Parameters of the compression of one block, all public.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockParams {
+    /// Index of the chunk in the tree
+    pub counter: u64,
+    /// Bytes of the message in this block
+    pub block_len: u32,
+    pub flags: u32,
+}
+
+/// The working vector: the chaining value, then the first words of the IV
+/// and the parameters.
+fn init_vector(cv: &ChainingValue, params: &BlockParams) -> Vector {
+    let tail = [
+        IV[0],
+        IV[1],
+        IV[2],
+        IV[3],
+        params.counter as u32,
+        (params.counter >> 32) as u32,
+        params.block_len,
+        params.flags,
+    ];
+
+    let mut v = [GF2Word::from(0u32); 16];
+    v[..8].copy_from_slice(cv);
+    for (v, word) in v[8..].iter_mut().zip(tail) {
+        *v = word.into();
+    }
+    v
+}
+
+/// The next chaining value, v[i] xor v[i + 8], which is linear.
+fn finalize(v: &Vector) -> ChainingValue {
+    let mut cv = [GF2Word::from(0u32); 8];
+    for (i, cv) in cv.iter_mut().enumerate() {
+        *cv = v[i] ^ v[i + 8];
+    }
+    cv
+}
+
+fn permute(m: &Block) -> Block {
+    MSG_PERMUTATION.map(|i| m[i])
+}
+
+/// Natively compress the block `m` into `cv`.
+pub fn compress(cv: &ChainingValue, m: &Block, params: &BlockParams) -> ChainingValue {
+    let mut v = init_vector(cv, params);
+    let mut m = *m;
+    for r in 0..ROUNDS {
+        if r > 0 {
+            m = permute(&m);
+        }
+        round(&mut v, &m);
+    }
+
+    finalize(&v)
+}
+
+/// Compress shares of the block `m` into shares of `cv`, with
+/// `ROUNDS * MUL_GATES_PER_ROUND` mul gates.
+pub fn mpc_compress(
+    cv: &(ChainingValue, ChainingValue, ChainingValue),
+    m: (&Block, &Block, &Block),
+    params: &BlockParams,
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (ChainingValue, ChainingValue, ChainingValue) {
+    let mut v1 = init_vector(&cv.0, params);
+    let mut v2 = init_vector(&cv.1, params);
+    let mut v3 = init_vector(&cv.2, params);
+    let (mut m1, mut m2, mut m3) = (*m.0, *m.1, *m.2);
+
+    for r in 0..ROUNDS {
+        if r > 0 {
+            (m1, m2, m3) = (permute(&m1), permute(&m2), permute(&m3));
+        }
+        mpc_round((&mut v1, &mut v2, &mut v3), (&m1, &m2, &m3), p1, p2, p3);
+    }
+
+    (finalize(&v1), finalize(&v2), finalize(&v3))
+}
+
+pub fn mpc_compress_verify(
+    cv: &(ChainingValue, ChainingValue),
+    m: (&Block, &Block),
+    params: &BlockParams,
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(ChainingValue, ChainingValue), Error> {
+    let mut v = init_vector(&cv.0, params);
+    let mut v_next = init_vector(&cv.1, params);
+    let (mut m, mut m_next) = (*m.0, *m.1);
+
+    for r in 0..ROUNDS {
+        if r > 0 {
+            (m, m_next) = (permute(&m), permute(&m_next));
+        }
+        mpc_round_verify((&mut v, &mut v_next), (&m, &m_next), p, p_next)?;
+    }
+
+    Ok((finalize(&v), finalize(&v_next)))
+}
+
+/// Blocks of a message that fits in a single chunk, which is then the root
+/// of the tree, with their parameters. The empty message is one empty block.
+pub fn single_chunk_blocks(message_len: usize) -> Vec<(Range<usize>, BlockParams)> {
+    assert!(message_len <= CHUNK_LEN, "the message spans several chunks");
+
+    let num_of_blocks = message_len.div_ceil(BLOCK_LEN).max(1);
+    (0..num_of_blocks)
+        .map(|i| {
+            let range = BLOCK_LEN * i..(BLOCK_LEN * (i + 1)).min(message_len);
+
+            let mut flags = 0;
+            if i == 0 {
+                flags |= CHUNK_START;
+            }
+            if i + 1 == num_of_blocks {
+                flags |= CHUNK_END | ROOT;
+            }
+
+            let params = BlockParams {
+                counter: 0,
+                block_len: range.len() as u32,
+                flags,
+            };
+            (range, params)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_blake3 {
+    use super::{
+        block_words, compress, init_cv, mpc_compress, mpc_compress_verify, single_chunk_blocks,
+        BlockParams, CHUNK_END, CHUNK_START, ROOT, ROUNDS,
+    };
+    use crate::gadgets::{
+        blake2s::MUL_GATES_PER_ROUND, mpc_public, mpc_public_verify, test_utils::check_shares,
+    };
+
+    #[test]
+    fn test_compress() {
+        // hash of the empty message, from the reference test vectors
+        let mut cv = init_cv();
+        for (range, params) in single_chunk_blocks(0) {
+            cv = compress(&cv, &block_words(&[][range]), &params);
+        }
+
+        let digest: Vec<u8> = cv
+            .iter()
+            .flat_map(|word| word.value.to_le_bytes())
+            .collect();
+        assert_eq!(
+            digest,
+            [
+                0xaf, 0x13, 0x49, 0xb9, 0xf5, 0xf9, 0xa1, 0xa6, 0xa0, 0x40, 0x4d, 0xea, 0x36, 0xdc,
+                0xc9, 0x49, 0x9b, 0xcb, 0x25, 0xc9, 0xad, 0xc1, 0x12, 0xb7, 0xcc, 0x9a, 0x93, 0xca,
+                0xe4, 0x1f, 0x32, 0x62
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mpc_compress() {
+        let input: Vec<u8> = (0..64).collect();
+        let cv = init_cv();
+        let params = BlockParams {
+            counter: 0,
+            block_len: 64,
+            flags: CHUNK_START | CHUNK_END | ROOT,
+        };
+
+        check_shares(
+            &input,
+            ROUNDS * MUL_GATES_PER_ROUND,
+            |input| compress(&cv, &block_words(input), &params).to_vec(),
+            |p1, p2, p3| {
+                let m = (
+                    block_words(&p1.view.input),
+                    block_words(&p2.view.input),
+                    block_words(&p3.view.input),
+                );
+                let (cv1, cv2, cv3) =
+                    mpc_compress(&mpc_public(cv), (&m.0, &m.1, &m.2), &params, p1, p2, p3);
+                (cv1.to_vec(), cv2.to_vec(), cv3.to_vec())
+            },
+            |p, p_next| {
+                let m = (block_words(&p.view.input), block_words(&p_next.view.input));
+                let (cv, cv_next) =
+                    mpc_compress_verify(&mpc_public_verify(cv), (&m.0, &m.1), &params, p, p_next)?;
+                Ok((cv.to_vec(), cv_next.to_vec()))
+            },
+        );
+    }
+}
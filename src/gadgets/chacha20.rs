@@ -0,0 +1,387 @@
+use crate::{
+    error::Error,
+    gadgets::add_mod::{add_mod_verify, mpc_add_mod},
+    gf2_word::{BitUtils, GF2Word},
+    party::Party,
+};
+
+/// "expand 32-byte k"
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+/// Positions `(a, b, c, d)` of the quarter rounds of a double round, on the
+/// columns and then on the diagonals of the state seen as a 4x4 matrix.
+const QUARTER_ROUNDS: [(usize, usize, usize, usize); 8] = [
+    (0, 4, 8, 12),
+    (1, 5, 9, 13),
+    (2, 6, 10, 14),
+    (3, 7, 11, 15),
+    (0, 5, 10, 15),
+    (1, 6, 11, 12),
+    (2, 7, 8, 13),
+    (3, 4, 9, 14),
+];
+
+/// Number of double rounds of ChaCha20.
+pub const DOUBLE_ROUNDS: usize = 10;
+
+/// Bytes of a keystream block.
+pub const BLOCK_LEN: usize = 64;
+
+/// Bytes of the key.
+pub const KEY_LEN: usize = 32;
+
+/// Mul gates of one quarter round, which adds 4 words.
+pub const MUL_GATES_PER_QUARTER_ROUND: usize = 4;
+
+/// Mul gates of one block: 8 quarter rounds per double round, and the 16
+/// additions of the input state to the output.
+pub const MUL_GATES_PER_BLOCK: usize = DOUBLE_ROUNDS * 8 * MUL_GATES_PER_QUARTER_ROUND + 16;
+
+/// The 8 little-endian words of a key.
+pub type Key = [GF2Word<u32>; 8];
+
+/// The 16 words of the state of a block.
+pub type State = [GF2Word<u32>; 16];
+
+/// Public parameters of the keystream: the nonce and the counter of the first
+/// block, as in RFC 8439.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nonce {
+    pub nonce: [u8; 12],
+    pub counter: u32,
+}
+
+/// Read a key of `KEY_LEN` bytes as little-endian words.
+pub fn key_words(key: &[u8]) -> Key {
+    assert_eq!(key.len(), KEY_LEN);
+
+    let mut words = [GF2Word::from(0u32); 8];
+    for (word, chunk) in words.iter_mut().zip(key.chunks(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap()).into();
+    }
+    words
+}
+
+/// Number of keystream blocks to encrypt `message_len` bytes.
+pub fn num_of_blocks(message_len: usize) -> usize {
+    message_len.div_ceil(BLOCK_LEN)
+}
+
+/// Whether the keystream for `message_len` bytes fits in the block counters
+/// left after `nonce.counter`. RFC 8439 forbids wrapping the counter, which
+/// would repeat the keystream.
+pub fn counter_fits(nonce: &Nonce, message_len: usize) -> bool {
+    nonce.counter as u64 + num_of_blocks(message_len) as u64 <= 1 << 32
+}
+
+/// The input state of the `i`-th block, from a share of the key and the public
+/// constants, counter and nonce.
+fn init_state(key: &Key, nonce: &Nonce, i: usize) -> State {
+    let mut state = [GF2Word::from(0u32); 16];
+    for (word, constant) in state.iter_mut().zip(CONSTANTS) {
+        *word = constant.into();
+    }
+    state[4..12].copy_from_slice(key);
+    state[12] = (nonce.counter + i as u32).into();
+    for (word, chunk) in state[13..].iter_mut().zip(nonce.nonce.chunks(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap()).into();
+    }
+    state
+}
+
+/// `(a xor b) leftrotate n`.
+fn xor_rotl(a: GF2Word<u32>, b: GF2Word<u32>, n: usize) -> GF2Word<u32> {
+    (a ^ b).value.left_rotate(n).into()
+}
+
+/// Little-endian encoding of the output state, which is the keystream block.
+fn serialize(state: &State) -> [u8; BLOCK_LEN] {
+    let mut bytes = [0u8; BLOCK_LEN];
+    for (chunk, word) in bytes.chunks_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Natively apply the quarter round at positions `(a, b, c, d)`.
+pub fn quarter_round(s: &mut State, (a, b, c, d): (usize, usize, usize, usize)) {
+    s[a] = s[a].value.wrapping_add(s[b].value).into();
+    s[d] = xor_rotl(s[d], s[a], 16);
+    s[c] = s[c].value.wrapping_add(s[d].value).into();
+    s[b] = xor_rotl(s[b], s[c], 12);
+    s[a] = s[a].value.wrapping_add(s[b].value).into();
+    s[d] = xor_rotl(s[d], s[a], 8);
+    s[c] = s[c].value.wrapping_add(s[d].value).into();
+    s[b] = xor_rotl(s[b], s[c], 7);
+}
+
+/// The quarter round on shares of the state, with
+/// `MUL_GATES_PER_QUARTER_ROUND` mul gates.
+pub fn mpc_quarter_round(
+    s: (&mut State, &mut State, &mut State),
+    (a, b, c, d): (usize, usize, usize, usize),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) {
+    let (s1, s2, s3) = s;
+
+    for (r1, r2) in [(16, 12), (8, 7)] {
+        // a += b; d ^= a; d <<<= r1
+        (s1[a], s2[a], s3[a]) =
+            mpc_add_mod((s1[a], s1[b]), (s2[a], s2[b]), (s3[a], s3[b]), p1, p2, p3);
+        (s1[d], s2[d], s3[d]) = (
+            xor_rotl(s1[d], s1[a], r1),
+            xor_rotl(s2[d], s2[a], r1),
+            xor_rotl(s3[d], s3[a], r1),
+        );
+
+        // c += d; b ^= c; b <<<= r2
+        (s1[c], s2[c], s3[c]) =
+            mpc_add_mod((s1[c], s1[d]), (s2[c], s2[d]), (s3[c], s3[d]), p1, p2, p3);
+        (s1[b], s2[b], s3[b]) = (
+            xor_rotl(s1[b], s1[c], r2),
+            xor_rotl(s2[b], s2[c], r2),
+            xor_rotl(s3[b], s3[c], r2),
+        );
+    }
+}
+
+pub fn mpc_quarter_round_verify(
+    s: (&mut State, &mut State),
+    (a, b, c, d): (usize, usize, usize, usize),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(), Error> {
+    let (s, s_next) = s;
+
+    for (r1, r2) in [(16, 12), (8, 7)] {
+        (s[a], s_next[a]) = add_mod_verify((s[a], s[b]), (s_next[a], s_next[b]), p, p_next)?;
+        (s[d], s_next[d]) = (xor_rotl(s[d], s[a], r1), xor_rotl(s_next[d], s_next[a], r1));
+
+        (s[c], s_next[c]) = add_mod_verify((s[c], s[d]), (s_next[c], s_next[d]), p, p_next)?;
+        (s[b], s_next[b]) = (xor_rotl(s[b], s[c], r2), xor_rotl(s_next[b], s_next[c], r2));
+    }
+
+    Ok(())
+}
+
+/// Natively compute the `i`-th keystream block.
+pub fn block(key: &Key, nonce: &Nonce, i: usize) -> [u8; BLOCK_LEN] {
+    let input = init_state(key, nonce, i);
+    let mut s = input;
+    for _ in 0..DOUBLE_ROUNDS {
+        for position in QUARTER_ROUNDS {
+            quarter_round(&mut s, position);
+        }
+    }
+
+    for (s, input) in s.iter_mut().zip(input) {
+        *s = s.value.wrapping_add(input.value).into();
+    }
+    serialize(&s)
+}
+
+/// Shares of the `i`-th keystream block from shares of the key, with
+/// `MUL_GATES_PER_BLOCK` mul gates.
+pub fn mpc_block(
+    key: (&Key, &Key, &Key),
+    nonce: &Nonce,
+    i: usize,
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> ([u8; BLOCK_LEN], [u8; BLOCK_LEN], [u8; BLOCK_LEN]) {
+    let input = (
+        init_state(key.0, nonce, i),
+        init_state(key.1, nonce, i),
+        init_state(key.2, nonce, i),
+    );
+    let (mut s1, mut s2, mut s3) = input;
+
+    for _ in 0..DOUBLE_ROUNDS {
+        for position in QUARTER_ROUNDS {
+            mpc_quarter_round((&mut s1, &mut s2, &mut s3), position, p1, p2, p3);
+        }
+    }
+
+    for w in 0..16 {
+        (s1[w], s2[w], s3[w]) = mpc_add_mod(
+            (s1[w], input.0[w]),
+            (s2[w], input.1[w]),
+            (s3[w], input.2[w]),
+            p1,
+            p2,
+            p3,
+        );
+    }
+    (serialize(&s1), serialize(&s2), serialize(&s3))
+}
+
+pub fn mpc_block_verify(
+    key: (&Key, &Key),
+    nonce: &Nonce,
+    i: usize,
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<([u8; BLOCK_LEN], [u8; BLOCK_LEN]), Error> {
+    let input = (init_state(key.0, nonce, i), init_state(key.1, nonce, i));
+    let (mut s, mut s_next) = input;
+
+    for _ in 0..DOUBLE_ROUNDS {
+        for position in QUARTER_ROUNDS {
+            mpc_quarter_round_verify((&mut s, &mut s_next), position, p, p_next)?;
+        }
+    }
+
+    for w in 0..16 {
+        (s[w], s_next[w]) = add_mod_verify((s[w], input.0[w]), (s_next[w], input.1[w]), p, p_next)?;
+    }
+    Ok((serialize(&s), serialize(&s_next)))
+}
+
+/// XOR `message` with the keystream, which is linear and so applied to each
+/// share.
+fn xor_keystream(message: &[u8], keystream: &[u8]) -> Vec<u8> {
+    message.iter().zip(keystream).map(|(m, k)| m ^ k).collect()
+}
+
+/// Natively encrypt, or decrypt, `message` under `key`.
+pub fn encrypt(key: &Key, nonce: &Nonce, message: &[u8]) -> Vec<u8> {
+    let keystream: Vec<u8> = (0..num_of_blocks(message.len()))
+        .flat_map(|i| block(key, nonce, i))
+        .collect();
+    xor_keystream(message, &keystream)
+}
+
+/// Encrypt shares of `message` under shares of the key, with
+/// `MUL_GATES_PER_BLOCK` mul gates per block of the message.
+pub fn mpc_encrypt(
+    key: (&Key, &Key, &Key),
+    nonce: &Nonce,
+    message: (&[u8], &[u8], &[u8]),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let (mut keystream_1, mut keystream_2, mut keystream_3) = (vec![], vec![], vec![]);
+    for i in 0..num_of_blocks(message.0.len()) {
+        let (k1, k2, k3) = mpc_block(key, nonce, i, p1, p2, p3);
+        keystream_1.extend(k1);
+        keystream_2.extend(k2);
+        keystream_3.extend(k3);
+    }
+
+    (
+        xor_keystream(message.0, &keystream_1),
+        xor_keystream(message.1, &keystream_2),
+        xor_keystream(message.2, &keystream_3),
+    )
+}
+
+pub fn mpc_encrypt_verify(
+    key: (&Key, &Key),
+    nonce: &Nonce,
+    message: (&[u8], &[u8]),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let (mut keystream, mut keystream_next) = (vec![], vec![]);
+    for i in 0..num_of_blocks(message.0.len()) {
+        let (k, k_next) = mpc_block_verify(key, nonce, i, p, p_next)?;
+        keystream.extend(k);
+        keystream_next.extend(k_next);
+    }
+
+    Ok((
+        xor_keystream(message.0, &keystream),
+        xor_keystream(message.1, &keystream_next),
+    ))
+}
+
+#[cfg(test)]
+mod test_chacha20 {
+    use super::{
+        block, encrypt, key_words, mpc_encrypt, mpc_encrypt_verify, num_of_blocks, Nonce, KEY_LEN,
+        MUL_GATES_PER_BLOCK,
+    };
+    use crate::{
+        circuit::Output,
+        gadgets::{prepare::generic_parse, test_utils::check_shares},
+    };
+
+    fn words(bytes: &[u8]) -> Output<u32> {
+        generic_parse(bytes, bytes.len().div_ceil(4)).unwrap()
+    }
+
+    #[test]
+    fn test_block() {
+        // RFC 8439, section 2.3.2
+        let key: Vec<u8> = (0..32).collect();
+        let nonce = Nonce {
+            nonce: [0, 0, 0, 0x09, 0, 0, 0, 0x4a, 0, 0, 0, 0],
+            counter: 1,
+        };
+        assert_eq!(
+            block(&key_words(&key), &nonce, 0),
+            [
+                0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15, 0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20,
+                0x71, 0xc4, 0xc7, 0xd1, 0xf4, 0xc7, 0x33, 0xc0, 0x68, 0x03, 0x04, 0x22, 0xaa, 0x9a,
+                0xc3, 0xd4, 0x6c, 0x4e, 0xd2, 0x82, 0x64, 0x46, 0x07, 0x9f, 0xaa, 0x09, 0x14, 0xc2,
+                0xd7, 0x05, 0xd9, 0x8b, 0x02, 0xa2, 0xb5, 0x12, 0x9c, 0xd1, 0xde, 0x16, 0x4e, 0xb9,
+                0xcb, 0xd0, 0x83, 0xe8, 0xa2, 0x50, 0x3c, 0x4e
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mpc_encrypt() {
+        // a secret key followed by a secret message of a block and a half
+        let input: Vec<u8> = (0..KEY_LEN + 96).map(|i| i as u8).collect();
+        let nonce = Nonce {
+            nonce: [0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0],
+            counter: 1,
+        };
+
+        check_shares(
+            &input,
+            num_of_blocks(96) * MUL_GATES_PER_BLOCK,
+            |input| {
+                let (key, message) = input.split_at(KEY_LEN);
+                words(&encrypt(&key_words(key), &nonce, message))
+            },
+            |p1, p2, p3| {
+                let (key_1, message_1) = p1.view.input.split_at(KEY_LEN);
+                let (key_2, message_2) = p2.view.input.split_at(KEY_LEN);
+                let (key_3, message_3) = p3.view.input.split_at(KEY_LEN);
+                let key = (key_words(key_1), key_words(key_2), key_words(key_3));
+                let message = (message_1.to_vec(), message_2.to_vec(), message_3.to_vec());
+
+                let (c1, c2, c3) = mpc_encrypt(
+                    (&key.0, &key.1, &key.2),
+                    &nonce,
+                    (&message.0, &message.1, &message.2),
+                    p1,
+                    p2,
+                    p3,
+                );
+                (words(&c1), words(&c2), words(&c3))
+            },
+            |p, p_next| {
+                let (key_p, message_p) = p.view.input.split_at(KEY_LEN);
+                let (key_p_next, message_p_next) = p_next.view.input.split_at(KEY_LEN);
+                let key = (key_words(key_p), key_words(key_p_next));
+                let message = (message_p.to_vec(), message_p_next.to_vec());
+
+                let (c, c_next) = mpc_encrypt_verify(
+                    (&key.0, &key.1),
+                    &nonce,
+                    (&message.0, &message.1),
+                    p,
+                    p_next,
+                )?;
+                Ok((words(&c), words(&c_next)))
+            },
+        );
+    }
+}
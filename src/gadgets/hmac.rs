@@ -0,0 +1,369 @@
+use crate::{
+    error::Error,
+    gadgets::{
+        mpc_public, mpc_public_verify,
+        sha256::{
+            init_iv, mpc_sha256_block, mpc_sha256_block_verify, mpc_sha256_blocks,
+            mpc_sha256_blocks_verify, padding, padding_after, sha256_block, sha256_blocks, State,
+        },
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Bytes of a SHA-256 block, the size of the HMAC key block.
+pub const BLOCK_LEN: usize = 64;
+
+const IPAD: u32 = 0x36363636;
+const OPAD: u32 = 0x5c5c5c5c;
+
+/// One party's key block: the key, or its hash if longer than a block, padded
+/// with zeros and read as big-endian words.
+pub type KeyBlock = [GF2Word<u32>; 16];
+
+/// Midstates after compressing the key block XOR ipad and XOR opad, which
+/// only depend on the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pads {
+    pub inner: State,
+    pub outer: State,
+}
+
+/// Blocks compressed to hash a key longer than a block, 0 otherwise.
+pub fn key_hash_blocks(key_len: usize) -> usize {
+    if key_len > BLOCK_LEN {
+        (key_len + 9).div_ceil(BLOCK_LEN)
+    } else {
+        0
+    }
+}
+
+/// Blocks of the inner hash after the key block, for a message of
+/// `message_len` bytes.
+pub fn inner_blocks(message_len: usize) -> usize {
+    (message_len + 9).div_ceil(BLOCK_LEN)
+}
+
+/// Zero pad a key of at most a block.
+fn pad_key(key: &[u8]) -> KeyBlock {
+    assert!(key.len() <= BLOCK_LEN);
+
+    let mut bytes = [0u8; BLOCK_LEN];
+    bytes[..key.len()].copy_from_slice(key);
+    bytes
+        .chunks(4)
+        .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()).into())
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+/// Natively derive the key block of `key`.
+pub fn key_block(key: &[u8]) -> KeyBlock {
+    if key.len() > BLOCK_LEN {
+        let digest = sha256_blocks(&padding(key), &init_iv().to_vec().into());
+        pad_key(&digest.to_bytes())
+    } else {
+        pad_key(key)
+    }
+}
+
+/// Derive the key block from shares of the key. Zero padding is linear, while
+/// hashing a long key takes `key_hash_blocks` blocks of mul gates.
+pub fn mpc_key_block(
+    key: (&[u8], &[u8], &[u8]),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (KeyBlock, KeyBlock, KeyBlock) {
+    if key.0.len() <= BLOCK_LEN {
+        return (pad_key(key.0), pad_key(key.1), pad_key(key.2));
+    }
+
+    let iv: State = init_iv().to_vec().into();
+    let (digest_1, digest_2, digest_3) = mpc_sha256_blocks(
+        &padding(key.0),
+        &padding(key.1),
+        &padding(key.2),
+        &mpc_public(iv),
+        p1,
+        p2,
+        p3,
+    );
+
+    (
+        pad_key(&digest_1.to_bytes()),
+        pad_key(&digest_2.to_bytes()),
+        pad_key(&digest_3.to_bytes()),
+    )
+}
+
+pub fn mpc_key_block_verify(
+    key: (&[u8], &[u8]),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(KeyBlock, KeyBlock), Error> {
+    if key.0.len() <= BLOCK_LEN {
+        return Ok((pad_key(key.0), pad_key(key.1)));
+    }
+
+    let iv: State = init_iv().to_vec().into();
+    let (digest, digest_next) = mpc_sha256_blocks_verify(
+        &padding(key.0),
+        &padding(key.1),
+        &mpc_public_verify(iv),
+        p,
+        p_next,
+    )?;
+
+    Ok((
+        pad_key(&digest.to_bytes()),
+        pad_key(&digest_next.to_bytes()),
+    ))
+}
+
+/// XOR every word of a share of the key block with a public pad.
+fn xor_pad(key: &KeyBlock, pad: u32) -> KeyBlock {
+    key.map(|word| word ^ pad.into())
+}
+
+/// Natively compress the padded key blocks.
+pub fn hmac_pads(key: &KeyBlock) -> Pads {
+    let iv: State = init_iv().to_vec().into();
+    Pads {
+        inner: sha256_block(&xor_pad(key, IPAD), &iv),
+        outer: sha256_block(&xor_pad(key, OPAD), &iv),
+    }
+}
+
+/// Compress shares of the padded key blocks, with 2 blocks of mul gates.
+pub fn mpc_hmac_pads(
+    key: (&KeyBlock, &KeyBlock, &KeyBlock),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (Pads, Pads, Pads) {
+    let iv: State = init_iv().to_vec().into();
+    let (inner_1, inner_2, inner_3) = mpc_sha256_block(
+        &xor_pad(key.0, IPAD),
+        &xor_pad(key.1, IPAD),
+        &xor_pad(key.2, IPAD),
+        &mpc_public(iv),
+        p1,
+        p2,
+        p3,
+    );
+    let (outer_1, outer_2, outer_3) = mpc_sha256_block(
+        &xor_pad(key.0, OPAD),
+        &xor_pad(key.1, OPAD),
+        &xor_pad(key.2, OPAD),
+        &mpc_public(iv),
+        p1,
+        p2,
+        p3,
+    );
+
+    (
+        Pads {
+            inner: inner_1,
+            outer: outer_1,
+        },
+        Pads {
+            inner: inner_2,
+            outer: outer_2,
+        },
+        Pads {
+            inner: inner_3,
+            outer: outer_3,
+        },
+    )
+}
+
+pub fn mpc_hmac_pads_verify(
+    key: (&KeyBlock, &KeyBlock),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(Pads, Pads), Error> {
+    let iv: State = init_iv().to_vec().into();
+    let (inner, inner_next) = mpc_sha256_block_verify(
+        &xor_pad(key.0, IPAD),
+        &xor_pad(key.1, IPAD),
+        &mpc_public_verify(iv),
+        p,
+        p_next,
+    )?;
+    let (outer, outer_next) = mpc_sha256_block_verify(
+        &xor_pad(key.0, OPAD),
+        &xor_pad(key.1, OPAD),
+        &mpc_public_verify(iv),
+        p,
+        p_next,
+    )?;
+
+    Ok((
+        Pads { inner, outer },
+        Pads {
+            inner: inner_next,
+            outer: outer_next,
+        },
+    ))
+}
+
+/// Natively compute HMAC(key, message) from the pads of the key.
+pub fn hmac_sha256(pads: &Pads, message: &[u8]) -> State {
+    let inner = sha256_blocks(&padding_after(message, BLOCK_LEN), &pads.inner);
+    sha256_blocks(&padding_after(&inner.to_bytes(), BLOCK_LEN), &pads.outer)
+}
+
+/// Compute shares of HMAC(key, message) from shares of the pads of the key,
+/// with `inner_blocks(message.len()) + 1` blocks of mul gates.
+pub fn mpc_hmac_sha256(
+    pads: &(Pads, Pads, Pads),
+    message: (&[u8], &[u8], &[u8]),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let (inner_1, inner_2, inner_3) = mpc_sha256_blocks(
+        &padding_after(message.0, BLOCK_LEN),
+        &padding_after(message.1, BLOCK_LEN),
+        &padding_after(message.2, BLOCK_LEN),
+        &(pads.0.inner, pads.1.inner, pads.2.inner),
+        p1,
+        p2,
+        p3,
+    );
+
+    mpc_sha256_blocks(
+        &padding_after(&inner_1.to_bytes(), BLOCK_LEN),
+        &padding_after(&inner_2.to_bytes(), BLOCK_LEN),
+        &padding_after(&inner_3.to_bytes(), BLOCK_LEN),
+        &(pads.0.outer, pads.1.outer, pads.2.outer),
+        p1,
+        p2,
+        p3,
+    )
+}
+
+pub fn mpc_hmac_sha256_verify(
+    pads: &(Pads, Pads),
+    message: (&[u8], &[u8]),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let (inner, inner_next) = mpc_sha256_blocks_verify(
+        &padding_after(message.0, BLOCK_LEN),
+        &padding_after(message.1, BLOCK_LEN),
+        &(pads.0.inner, pads.1.inner),
+        p,
+        p_next,
+    )?;
+
+    mpc_sha256_blocks_verify(
+        &padding_after(&inner.to_bytes(), BLOCK_LEN),
+        &padding_after(&inner_next.to_bytes(), BLOCK_LEN),
+        &(pads.0.outer, pads.1.outer),
+        p,
+        p_next,
+    )
+}
+
+#[cfg(test)]
+mod test_hmac {
+    use super::{
+        hmac_pads, hmac_sha256, inner_blocks, key_block, key_hash_blocks, mpc_hmac_pads,
+        mpc_hmac_pads_verify, mpc_hmac_sha256, mpc_hmac_sha256_verify, mpc_key_block,
+        mpc_key_block_verify,
+    };
+    use crate::{
+        circuits::sha256::MUL_GATES_PER_BLOCK,
+        gadgets::{mpc_public, mpc_public_verify, test_utils::check_shares},
+    };
+
+    const MESSAGE: &[u8] = b"what do ya want for nothing?";
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231 test case 2
+        let tag = hmac_sha256(&hmac_pads(&key_block(b"Jefe")), MESSAGE);
+        assert_eq!(
+            tag.to_bytes(),
+            [
+                0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+                0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+                0x64, 0xec, 0x38, 0x43
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mpc_hmac_sha256() {
+        // keys of less and of more than a block, the latter being hashed
+        for key_len in [20, 100] {
+            let key: Vec<u8> = (0..key_len).map(|i| i as u8).collect();
+            let blocks = key_hash_blocks(key_len) + 2 + inner_blocks(MESSAGE.len()) + 1;
+
+            check_shares(
+                &key,
+                blocks * MUL_GATES_PER_BLOCK,
+                |key| hmac_sha256(&hmac_pads(&key_block(key)), MESSAGE).to_vec(),
+                |p1, p2, p3| {
+                    let key = (
+                        p1.view.input.clone(),
+                        p2.view.input.clone(),
+                        p3.view.input.clone(),
+                    );
+                    let k = mpc_key_block((&key.0, &key.1, &key.2), p1, p2, p3);
+                    let pads = mpc_hmac_pads((&k.0, &k.1, &k.2), p1, p2, p3);
+                    let (t1, t2, t3) = mpc_hmac_sha256(&pads, mpc_public(MESSAGE), p1, p2, p3);
+                    (t1.to_vec(), t2.to_vec(), t3.to_vec())
+                },
+                |p, p_next| {
+                    let key = (p.view.input.clone(), p_next.view.input.clone());
+                    let k = mpc_key_block_verify((&key.0, &key.1), p, p_next)?;
+                    let pads = mpc_hmac_pads_verify((&k.0, &k.1), p, p_next)?;
+                    let (t, t_next) =
+                        mpc_hmac_sha256_verify(&pads, mpc_public_verify(MESSAGE), p, p_next)?;
+                    Ok((t.to_vec(), t_next.to_vec()))
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_mpc_hmac_sha256_secret_message() {
+        // a public key and a secret message, as in HKDF-Extract
+        let pads = hmac_pads(&key_block(b"Jefe"));
+
+        check_shares(
+            MESSAGE,
+            (inner_blocks(MESSAGE.len()) + 1) * MUL_GATES_PER_BLOCK,
+            |message| hmac_sha256(&pads, message).to_vec(),
+            |p1, p2, p3| {
+                let message = (
+                    p1.view.input.clone(),
+                    p2.view.input.clone(),
+                    p3.view.input.clone(),
+                );
+                let (t1, t2, t3) = mpc_hmac_sha256(
+                    &mpc_public(pads),
+                    (&message.0, &message.1, &message.2),
+                    p1,
+                    p2,
+                    p3,
+                );
+                (t1.to_vec(), t2.to_vec(), t3.to_vec())
+            },
+            |p, p_next| {
+                let message = (p.view.input.clone(), p_next.view.input.clone());
+                let (t, t_next) = mpc_hmac_sha256_verify(
+                    &mpc_public_verify(pads),
+                    (&message.0, &message.1),
+                    p,
+                    p_next,
+                )?;
+                Ok((t.to_vec(), t_next.to_vec()))
+            },
+        );
+    }
+}
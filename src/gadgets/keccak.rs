@@ -0,0 +1,211 @@
+use crate::{
+    error::Error,
+    gadgets::{mpc_and, mpc_and_verify},
+    gf2_word::{BitUtils, GF2Word},
+    party::Party,
+};
+
+/// Number of rounds of Keccak-f[1600].
+pub const ROUNDS: usize = 24;
+
+/// Round constants of the iota step.
+const RC: [u64; ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of the rho step, indexed by `x + 5 * y`.
+const RHO: [usize; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// The 25 lanes of the Keccak state, where lane `(x, y)` is at `x + 5 * y`.
+pub type State = [GF2Word<u64>; 25];
+
+pub fn empty_state() -> State {
+    [0.into(); 25]
+}
+
+/// XOR `bytes`, read as little-endian lanes, into the leading lanes of
+/// `state`. The length of `bytes` is a multiple of 8.
+pub fn absorb(state: &mut State, bytes: &[u8]) {
+    for (lane, chunk) in state.iter_mut().zip(bytes.chunks(8)) {
+        *lane = *lane ^ u64::from_le_bytes(chunk.try_into().unwrap()).into();
+    }
+}
+
+/// Theta, rho and pi, which are linear and so applied to each share alone.
+fn theta_rho_pi(a: &State) -> State {
+    let mut c = [GF2Word::<u64>::from(0); 5];
+    for x in 0..5 {
+        c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+    }
+
+    let mut b = empty_state();
+    for y in 0..5 {
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].value.left_rotate(1).into();
+            let lane = (a[x + 5 * y] ^ d).value.left_rotate(RHO[x + 5 * y]);
+
+            // pi moves lane (x, y) to (y, 2x + 3y)
+            b[y + 5 * ((2 * x + 3 * y) % 5)] = lane.into();
+        }
+    }
+
+    b
+}
+
+/// Iota: XOR the public round constant into lane (0, 0).
+fn iota(a: &mut State, round: usize) {
+    a[0] = a[0] ^ RC[round].into();
+}
+
+/// Natively apply Keccak-f[1600] to `state`, as `mpc_keccak_f` does on shares.
+pub fn keccak_f(state: &State) -> State {
+    let mut a = *state;
+    for round in 0..ROUNDS {
+        let b = theta_rho_pi(&a);
+
+        // chi: a[x] = b[x] xor ((not b[x + 1]) and b[x + 2])
+        for y in 0..5 {
+            for x in 0..5 {
+                let (b1, b2) = (b[(x + 1) % 5 + 5 * y], b[(x + 2) % 5 + 5 * y]);
+                a[x + 5 * y] = b[x + 5 * y] ^ (b1 & b2) ^ b2;
+            }
+        }
+
+        iota(&mut a, round);
+    }
+
+    a
+}
+
+/// Keccak-f[1600] on shares of the state, with one mul gate for each lane of
+/// the chi step, so 25 per round.
+pub fn mpc_keccak_f(
+    state: &(State, State, State),
+    p1: &mut Party<u64>,
+    p2: &mut Party<u64>,
+    p3: &mut Party<u64>,
+) -> (State, State, State) {
+    let (mut a1, mut a2, mut a3) = *state;
+    for round in 0..ROUNDS {
+        let b1 = theta_rho_pi(&a1);
+        let b2 = theta_rho_pi(&a2);
+        let b3 = theta_rho_pi(&a3);
+
+        // chi: (not u) and v = (u and v) xor v
+        for y in 0..5 {
+            for x in 0..5 {
+                let (i, j, k) = (x + 5 * y, (x + 1) % 5 + 5 * y, (x + 2) % 5 + 5 * y);
+                let (o1, o2, o3) =
+                    mpc_and((b1[j], b1[k]), (b2[j], b2[k]), (b3[j], b3[k]), p1, p2, p3);
+                a1[i] = b1[i] ^ o1 ^ b1[k];
+                a2[i] = b2[i] ^ o2 ^ b2[k];
+                a3[i] = b3[i] ^ o3 ^ b3[k];
+            }
+        }
+
+        iota(&mut a1, round);
+        iota(&mut a2, round);
+        iota(&mut a3, round);
+    }
+
+    (a1, a2, a3)
+}
+
+pub fn mpc_keccak_f_verify(
+    state: &(State, State),
+    p: &mut Party<u64>,
+    p_next: &mut Party<u64>,
+) -> Result<(State, State), Error> {
+    let (mut a, mut a_next) = *state;
+    for round in 0..ROUNDS {
+        let b = theta_rho_pi(&a);
+        let b_next = theta_rho_pi(&a_next);
+
+        for y in 0..5 {
+            for x in 0..5 {
+                let (i, j, k) = (x + 5 * y, (x + 1) % 5 + 5 * y, (x + 2) % 5 + 5 * y);
+                let (o, o_next) = mpc_and_verify((b[j], b[k]), (b_next[j], b_next[k]), p, p_next)?;
+                a[i] = b[i] ^ o ^ b[k];
+                a_next[i] = b_next[i] ^ o_next ^ b_next[k];
+            }
+        }
+
+        iota(&mut a, round);
+        iota(&mut a_next, round);
+    }
+
+    Ok((a, a_next))
+}
+
+#[cfg(test)]
+mod test_keccak {
+    use super::{empty_state, keccak_f, mpc_keccak_f, mpc_keccak_f_verify, State};
+    use crate::{
+        circuits::keccak::MUL_GATES_PER_PERMUTATION,
+        gadgets::{prepare::generic_parse, test_utils::check_shares},
+    };
+
+    fn state(bytes: &[u8]) -> State {
+        generic_parse(bytes, 25).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_keccak_f() {
+        // first lanes of Keccak-f[1600] applied to the all zero state, from
+        // the Keccak team's known answers
+        let state = keccak_f(&empty_state());
+        assert_eq!(state[0].value, 0xf1258f7940e1dde7);
+        assert_eq!(state[1].value, 0x84d5ccf933c0478a);
+        assert_eq!(state[24].value, 0xeaf1ff7b5ceca249);
+    }
+
+    #[test]
+    fn test_mpc_keccak_f() {
+        let input: Vec<u8> = (0..200).collect();
+
+        check_shares(
+            &input,
+            MUL_GATES_PER_PERMUTATION,
+            |input| keccak_f(&state(input)).to_vec(),
+            |p1, p2, p3| {
+                let a = (
+                    state(&p1.view.input),
+                    state(&p2.view.input),
+                    state(&p3.view.input),
+                );
+                let (a1, a2, a3) = mpc_keccak_f(&a, p1, p2, p3);
+                (a1.to_vec(), a2.to_vec(), a3.to_vec())
+            },
+            |p, p_next| {
+                let a = (state(&p.view.input), state(&p_next.view.input));
+                let (a, a_next) = mpc_keccak_f_verify(&a, p, p_next)?;
+                Ok((a.to_vec(), a_next.to_vec()))
+            },
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use std::convert::Infallible;
+
+use crate::{
+    error::Error,
+    gadgets::{mpc_and, mpc_and_verify},
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+/// Parameters of a LowMC instance. Bits of blocks and keys are numbered from
+/// the most significant bit of the first byte, as in Picnic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    /// Bits of a block, a multiple of 8
+    pub block_bits: usize,
+    /// Bits of the key, a multiple of 8
+    pub key_bits: usize,
+    /// S-boxes of the partial layer, applied to the first `3 * sboxes` bits
+    pub sboxes: usize,
+    pub rounds: usize,
+}
+
+impl Params {
+    pub const PICNIC_L1: Self = Self::new(128, 128, 10, 20);
+    pub const PICNIC_L3: Self = Self::new(192, 192, 10, 30);
+    pub const PICNIC_L5: Self = Self::new(256, 256, 10, 38);
+
+    pub const fn new(block_bits: usize, key_bits: usize, sboxes: usize, rounds: usize) -> Self {
+        assert!(block_bits.is_multiple_of(8) && key_bits.is_multiple_of(8));
+        assert!(3 * sboxes <= block_bits);
+
+        Self {
+            block_bits,
+            key_bits,
+            sboxes,
+            rounds,
+        }
+    }
+
+    /// Bytes of a block.
+    pub fn block_len(&self) -> usize {
+        self.block_bits / 8
+    }
+
+    /// Bytes of the key.
+    pub fn key_len(&self) -> usize {
+        self.key_bits / 8
+    }
+
+    /// Mul gates of an encryption: in every round, the 3 ANDs of each group of
+    /// up to 32 S-boxes, whose input bits are packed in the lanes of 3 words.
+    pub fn num_of_mul_gates(&self) -> usize {
+        3 * self.sboxes.div_ceil(32) * self.rounds
+    }
+}
+
+/// Rows of bits, each in the layout of a block.
+type Matrix = Vec<Vec<u8>>;
+
+fn bit(bytes: &[u8], i: usize) -> u8 {
+    (bytes[i / 8] >> (7 - i % 8)) & 1
+}
+
+fn set_bit(bytes: &mut [u8], i: usize, value: u8) {
+    bytes[i / 8] = (bytes[i / 8] & !(1 << (7 - i % 8))) | (value << (7 - i % 8));
+}
+
+fn xor_into(bytes: &mut [u8], other: &[u8]) {
+    for (byte, other) in bytes.iter_mut().zip(other) {
+        *byte ^= other;
+    }
+}
+
+/// Product of `matrix` with the bit vector `v`, which is linear.
+fn mul(matrix: &Matrix, v: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; matrix.len().div_ceil(8)];
+    for (i, row) in matrix.iter().enumerate() {
+        let ones: u32 = row.iter().zip(v).map(|(r, v)| (r & v).count_ones()).sum();
+        set_bit(&mut out, i, (ones & 1) as u8);
+    }
+    out
+}
+
+fn rank(matrix: &Matrix, cols: usize) -> usize {
+    let mut rows = matrix.clone();
+    let mut rank = 0;
+    for col in 0..cols {
+        let Some(pivot) = (rank..rows.len()).find(|&i| bit(&rows[i], col) == 1) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+        let (above, below) = rows.split_at_mut(rank + 1);
+        for row in below.iter_mut().filter(|row| bit(row, col) == 1) {
+            xor_into(row, &above[rank]);
+        }
+        rank += 1;
+    }
+    rank
+}
+
+/// The self-shrinking Grain LFSR that the LowMC reference implementation
+/// draws its constants from.
+struct Grain {
+    state: [u8; 80],
+    index: usize,
+}
+
+impl Grain {
+    fn new() -> Self {
+        let mut grain = Self {
+            state: [1; 80],
+            index: 0,
+        };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> u8 {
+        let s = &self.state;
+        let i = self.index;
+        let bit = s[i]
+            ^ s[(i + 13) % 80]
+            ^ s[(i + 23) % 80]
+            ^ s[(i + 38) % 80]
+            ^ s[(i + 51) % 80]
+            ^ s[(i + 62) % 80];
+        self.state[i] = bit;
+        self.index = (i + 1) % 80;
+        bit
+    }
+
+    /// Of each pair of bits, the second is output if the first is set.
+    fn next_bit(&mut self) -> u8 {
+        loop {
+            let choice = self.step();
+            let bit = self.step();
+            if choice == 1 {
+                return bit;
+            }
+        }
+    }
+
+    fn bits(&mut self, len: usize) -> Vec<u8> {
+        let mut bytes = vec![0u8; len.div_ceil(8)];
+        for i in 0..len {
+            set_bit(&mut bytes, i, self.next_bit());
+        }
+        bytes
+    }
+
+    /// A matrix of rank min(rows, cols), drawn again until it has full rank.
+    fn matrix(&mut self, rows: usize, cols: usize) -> Matrix {
+        loop {
+            let matrix = (0..rows).map(|_| self.bits(cols)).collect();
+            if rank(&matrix, cols) == rows.min(cols) {
+                return matrix;
+            }
+        }
+    }
+}
+
+/// A LowMC instance: the linear layers, round constants and key matrices of
+/// the reference implementation, which Picnic also uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowMc {
+    params: Params,
+    linear_layers: Vec<Matrix>,
+    round_constants: Vec<Vec<u8>>,
+    key_matrices: Vec<Matrix>,
+}
+
+impl LowMc {
+    pub fn new(params: Params) -> Self {
+        let mut grain = Grain::new();
+        let n = params.block_bits;
+
+        let linear_layers = (0..params.rounds).map(|_| grain.matrix(n, n)).collect();
+        let round_constants = (0..params.rounds).map(|_| grain.bits(n)).collect();
+        let key_matrices = (0..=params.rounds)
+            .map(|_| grain.matrix(n, params.key_bits))
+            .collect();
+
+        Self {
+            params,
+            linear_layers,
+            round_constants,
+            key_matrices,
+        }
+    }
+
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Round keys of `key`, which are linear in the key.
+    pub fn round_keys(&self, key: &[u8]) -> Vec<Vec<u8>> {
+        assert_eq!(key.len(), self.params.key_len());
+        self.key_matrices
+            .iter()
+            .map(|matrix| mul(matrix, key))
+            .collect()
+    }
+
+    fn encrypt_with<const N: usize, E>(
+        &self,
+        key: [&[u8]; N],
+        plaintext: &[u8],
+        and: &mut impl FnMut(Shares<N>, Shares<N>) -> Result<Shares<N>, E>,
+    ) -> Result<[Vec<u8>; N], E> {
+        assert_eq!(plaintext.len(), self.params.block_len());
+
+        let round_keys = key.map(|key| self.round_keys(key));
+        let mut state: [Vec<u8>; N] = std::array::from_fn(|p| {
+            let mut state = plaintext.to_vec();
+            xor_into(&mut state, &round_keys[p][0]);
+            state
+        });
+
+        for r in 0..self.params.rounds {
+            sbox_layer_with(&mut state, self.params.sboxes, and)?;
+            for (state, round_keys) in state.iter_mut().zip(&round_keys) {
+                *state = mul(&self.linear_layers[r], state);
+                xor_into(state, &self.round_constants[r]);
+                xor_into(state, &round_keys[r + 1]);
+            }
+        }
+
+        Ok(state)
+    }
+
+    /// Natively encrypt `plaintext` under `key`.
+    pub fn encrypt(&self, key: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let Ok([ciphertext]) = self.encrypt_with([key], plaintext, &mut |a, b| {
+            Ok::<_, Infallible>([a[0] & b[0]])
+        });
+        ciphertext
+    }
+
+    /// Encrypt a public `plaintext` under shares of the key, with
+    /// `Params::num_of_mul_gates` mul gates.
+    pub fn mpc_encrypt(
+        &self,
+        key: (&[u8], &[u8], &[u8]),
+        plaintext: &[u8],
+        p1: &mut Party<u32>,
+        p2: &mut Party<u32>,
+        p3: &mut Party<u32>,
+    ) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let mut and = |a: Shares<3>, b: Shares<3>| -> Result<_, Infallible> {
+            let (o1, o2, o3) = mpc_and((a[0], b[0]), (a[1], b[1]), (a[2], b[2]), p1, p2, p3);
+            Ok([o1, o2, o3])
+        };
+        let Ok([c1, c2, c3]) = self.encrypt_with([key.0, key.1, key.2], plaintext, &mut and);
+        (c1, c2, c3)
+    }
+
+    pub fn mpc_encrypt_verify(
+        &self,
+        key: (&[u8], &[u8]),
+        plaintext: &[u8],
+        p: &mut Party<u32>,
+        p_next: &mut Party<u32>,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let [c, c_next] = self.encrypt_with([key.0, key.1], plaintext, &mut |a, b| {
+            let (o, o_next) = mpc_and_verify((a[0], b[0]), (a[1], b[1]), p, p_next)?;
+            Ok([o, o_next])
+        })?;
+        Ok((c, c_next))
+    }
+}
+
+/// One word in each of `N` shares: the plain value for N = 1, the three
+/// parties of the prover, or the two parties opened to the verifier.
+type Shares<const N: usize> = [GF2Word<u32>; N];
+
+/// Apply the S-box (a, b, c) -> (a ^ bc, a ^ b ^ ca, a ^ b ^ c ^ ab) to the
+/// first `3 * sboxes` bits of the `N` shares of the state, where c is the
+/// first bit of each triple. The bits a, b and c of up to 32 S-boxes are
+/// packed in the lanes of one word each, so that only `and` differs between
+/// the native and the MPC evaluations.
+fn sbox_layer_with<const N: usize, E>(
+    state: &mut [Vec<u8>; N],
+    sboxes: usize,
+    and: &mut impl FnMut(Shares<N>, Shares<N>) -> Result<Shares<N>, E>,
+) -> Result<(), E> {
+    for first in (0..sboxes).step_by(32) {
+        let lanes = first..sboxes.min(first + 32);
+        let pack = |offset: usize| -> Shares<N> {
+            std::array::from_fn(|p| {
+                let mut word = 0u32;
+                for (lane, s) in lanes.clone().enumerate() {
+                    word |= (bit(&state[p], 3 * s + offset) as u32) << lane;
+                }
+                word.into()
+            })
+        };
+        let (c, b, a) = (pack(0), pack(1), pack(2));
+
+        let ab = and(a, b)?;
+        let bc = and(b, c)?;
+        let ca = and(c, a)?;
+
+        for (p, state) in state.iter_mut().enumerate() {
+            let a_out = a[p] ^ bc[p];
+            let b_out = a[p] ^ b[p] ^ ca[p];
+            let c_out = a[p] ^ b[p] ^ c[p] ^ ab[p];
+            for (lane, s) in lanes.clone().enumerate() {
+                set_bit(state, 3 * s, ((c_out.value >> lane) & 1) as u8);
+                set_bit(state, 3 * s + 1, ((b_out.value >> lane) & 1) as u8);
+                set_bit(state, 3 * s + 2, ((a_out.value >> lane) & 1) as u8);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_lowmc {
+    use super::{LowMc, Params};
+    use crate::{
+        circuit::Output,
+        gadgets::{prepare::generic_parse, test_utils::check_shares},
+    };
+
+    fn words(block: &[u8]) -> Output<u32> {
+        generic_parse(block, block.len().div_ceil(4)).unwrap()
+    }
+
+    #[test]
+    fn test_encrypt() {
+        // the LowMC test vectors of the Picnic reference implementation
+        let lowmc = LowMc::new(Params::PICNIC_L1);
+        let mut key = [0u8; 16];
+        let mut plaintext = [0u8; 16];
+
+        key[0] = 0x80;
+        plaintext[..2].copy_from_slice(&[0xab, 0xff]);
+        assert_eq!(
+            lowmc.encrypt(&key, &plaintext),
+            [
+                0x0e, 0x30, 0x72, 0x0b, 0x9f, 0x64, 0xd5, 0xc2, 0xa7, 0x77, 0x1c, 0x8c, 0x23, 0x8d,
+                0x8f, 0x70
+            ]
+        );
+
+        key[..4].copy_from_slice(&[0xb5, 0xdf, 0x53, 0x7b]);
+        plaintext[..4].copy_from_slice(&[0xf7, 0x7d, 0xb5, 0x7b]);
+        assert_eq!(
+            lowmc.encrypt(&key, &plaintext),
+            [
+                0x0e, 0x59, 0x61, 0xe9, 0x99, 0x21, 0x53, 0xb1, 0x32, 0x45, 0xaf, 0x24, 0x3d, 0xd7,
+                0xdd, 0xc0
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mpc_encrypt() {
+        // a partial S-box layer, and one of more S-boxes than lanes in a word
+        for params in [Params::PICNIC_L1, Params::new(128, 128, 42, 4)] {
+            let lowmc = LowMc::new(params);
+            let key: Vec<u8> = (0..params.key_len() as u8).collect();
+            let plaintext: Vec<u8> = (0..params.block_len()).map(|i| 0x11 * i as u8).collect();
+
+            check_shares(
+                &key,
+                params.num_of_mul_gates(),
+                |key| words(&lowmc.encrypt(key, &plaintext)),
+                |p1, p2, p3| {
+                    let key = (
+                        p1.view.input.clone(),
+                        p2.view.input.clone(),
+                        p3.view.input.clone(),
+                    );
+                    let (c1, c2, c3) =
+                        lowmc.mpc_encrypt((&key.0, &key.1, &key.2), &plaintext, p1, p2, p3);
+                    (words(&c1), words(&c2), words(&c3))
+                },
+                |p, p_next| {
+                    let key = (p.view.input.clone(), p_next.view.input.clone());
+                    let (c, c_next) =
+                        lowmc.mpc_encrypt_verify((&key.0, &key.1), &plaintext, p, p_next)?;
+                    Ok((words(&c), words(&c_next)))
+                },
+            );
+        }
+    }
+}
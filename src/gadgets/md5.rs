@@ -0,0 +1,380 @@
+use crate::{
+    error::Error,
+    gadgets::{
+        add_mod::{add_mod_verify, add_mod_verify_k, mpc_add_mod, mpc_add_mod_k},
+        mpc_and, mpc_and_verify,
+    },
+    gf2_word::{BitUtils, GF2Word},
+    party::Party,
+};
+
+/// MD5 pads its message exactly like RIPEMD-160, with a little-endian bit
+/// length, and reads it as little-endian words.
+pub use crate::gadgets::ripemd160::padding;
+
+const MD5_IV: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// K[i] := floor(2^32 * abs(sin(i + 1)))
+const K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// Left rotations, which repeat every 4 steps of a round.
+const S: [[usize; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+/// All ones, to complement a word with a XOR.
+const ONES: u32 = u32::MAX;
+
+/// Index of the message word of step `i`.
+fn g(i: usize) -> usize {
+    match i / 16 {
+        0 => i,
+        1 => (5 * i + 1) % 16,
+        2 => (3 * i + 5) % 16,
+        _ => (7 * i) % 16,
+    }
+}
+
+fn s(i: usize) -> usize {
+    S[i / 16][i % 4]
+}
+
+/// Chaining value of MD5.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    h: [GF2Word<u32>; 4],
+}
+
+impl State {
+    pub fn to_vec(&self) -> Vec<GF2Word<u32>> {
+        self.h.to_vec()
+    }
+
+    /// Read a state from its little-endian encoding, as in a digest.
+    pub fn from_bytes(bytes: &[u8; 16]) -> Self {
+        bytes
+            .chunks(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()).into())
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Little-endian encoding of the state, which is the digest once all
+    /// blocks are compressed.
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        for (chunk, word) in bytes.chunks_mut(4).zip(self.h.iter()) {
+            chunk.copy_from_slice(&word.value.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl From<Vec<GF2Word<u32>>> for State {
+    fn from(value: Vec<GF2Word<u32>>) -> Self {
+        Self {
+            h: value.try_into().unwrap(),
+        }
+    }
+}
+
+pub fn init_iv() -> State {
+    State {
+        h: MD5_IV.map(GF2Word::from),
+    }
+}
+
+/// Operands `(l, r, o)` of the round functions that cost one AND, which are
+/// `(l and r) xor o`:
+/// - F := (b and c) or ((not b) and d) = b and (c xor d) xor d
+/// - G := (b and d) or (c and (not d)) = d and (b xor c) xor c
+/// - I := c xor (b or (not d)) = (not b) and d xor c xor 1
+///
+/// H := b xor c xor d is linear.
+fn and_operands(i: usize, v: &[GF2Word<u32>; 4]) -> (GF2Word<u32>, GF2Word<u32>, GF2Word<u32>) {
+    let [_, b, c, d] = *v;
+    match i / 16 {
+        0 => (b, c ^ d, d),
+        1 => (d, b ^ c, c),
+        3 => (b ^ ONES.into(), d, c ^ ONES.into()),
+        _ => unreachable!("H is linear"),
+    }
+}
+
+/// Natively compress one block of 16 little-endian words into `state`, as
+/// `mpc_md5_block` does on shares.
+pub fn md5_block(input: &[GF2Word<u32>; 16], state: &State) -> State {
+    let mut v = state.h;
+    for i in 0..64 {
+        let [a, b, c, d] = v;
+        let f = if i / 16 == 2 {
+            b ^ c ^ d
+        } else {
+            let (l, r, o) = and_operands(i, &v);
+            (l & r) ^ o
+        };
+        let temp = a
+            .value
+            .wrapping_add(f.value)
+            .wrapping_add(K[i])
+            .wrapping_add(input[g(i)].value);
+
+        v = [d, b.value.wrapping_add(temp.left_rotate(s(i))).into(), b, c];
+    }
+
+    let mut h = state.h;
+    for (h, v) in h.iter_mut().zip(v) {
+        *h = h.value.wrapping_add(v.value).into();
+    }
+    State { h }
+}
+
+/// Run the 64 steps of the compression function on shares of the working
+/// variables `[a, b, c, d]`. F, G and I take one mul gate, H none, and each
+/// step adds 4 words.
+pub fn mpc_compression(
+    input_p1: &[GF2Word<u32>; 16],
+    input_p2: &[GF2Word<u32>; 16],
+    input_p3: &[GF2Word<u32>; 16],
+    state: &(State, State, State),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let mut v1 = state.0.h;
+    let mut v2 = state.1.h;
+    let mut v3 = state.2.h;
+
+    for i in 0..64 {
+        let (f_1, f_2, f_3) = if i / 16 == 2 {
+            (
+                v1[1] ^ v1[2] ^ v1[3],
+                v2[1] ^ v2[2] ^ v2[3],
+                v3[1] ^ v3[2] ^ v3[3],
+            )
+        } else {
+            let (l1, r1, o1) = and_operands(i, &v1);
+            let (l2, r2, o2) = and_operands(i, &v2);
+            let (l3, r3, o3) = and_operands(i, &v3);
+            let (and1, and2, and3) = mpc_and((l1, r1), (l2, r2), (l3, r3), p1, p2, p3);
+            (and1 ^ o1, and2 ^ o2, and3 ^ o3)
+        };
+
+        // temp := a + f + k[i] + m[g]
+        let (temp_1, temp_2, temp_3) = {
+            let (o1, o2, o3) = mpc_add_mod((v1[0], f_1), (v2[0], f_2), (v3[0], f_3), p1, p2, p3);
+            let (o1, o2, o3) = mpc_add_mod_k(o1, o2, o3, K[i].into(), p1, p2, p3);
+            mpc_add_mod(
+                (o1, input_p1[g(i)]),
+                (o2, input_p2[g(i)]),
+                (o3, input_p3[g(i)]),
+                p1,
+                p2,
+                p3,
+            )
+        };
+
+        // b := b + (temp leftrotate s[i])
+        let (b_1, b_2, b_3) = mpc_add_mod(
+            (v1[1], temp_1.value.left_rotate(s(i)).into()),
+            (v2[1], temp_2.value.left_rotate(s(i)).into()),
+            (v3[1], temp_3.value.left_rotate(s(i)).into()),
+            p1,
+            p2,
+            p3,
+        );
+
+        // a := d, d := c, c := b
+        for (v, b) in [(&mut v1, b_1), (&mut v2, b_2), (&mut v3, b_3)] {
+            v.rotate_right(1);
+            v[1] = b;
+        }
+    }
+
+    (State { h: v1 }, State { h: v2 }, State { h: v3 })
+}
+
+pub fn mpc_compression_verify(
+    input_p: &[GF2Word<u32>; 16],
+    input_p_next: &[GF2Word<u32>; 16],
+    state: &(State, State),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let mut v = state.0.h;
+    let mut v_next = state.1.h;
+
+    for i in 0..64 {
+        let (f, f_next) = if i / 16 == 2 {
+            (v[1] ^ v[2] ^ v[3], v_next[1] ^ v_next[2] ^ v_next[3])
+        } else {
+            let (l, r, o) = and_operands(i, &v);
+            let (l_next, r_next, o_next) = and_operands(i, &v_next);
+            let (and, and_next) = mpc_and_verify((l, r), (l_next, r_next), p, p_next)?;
+            (and ^ o, and_next ^ o_next)
+        };
+
+        // temp := a + f + k[i] + m[g]
+        let (temp, temp_next) = {
+            let (o, o_next) = add_mod_verify((v[0], f), (v_next[0], f_next), p, p_next)?;
+            let (o, o_next) = add_mod_verify_k(o, o_next, K[i].into(), p, p_next)?;
+            add_mod_verify((o, input_p[g(i)]), (o_next, input_p_next[g(i)]), p, p_next)?
+        };
+
+        // b := b + (temp leftrotate s[i])
+        let (b, b_next) = add_mod_verify(
+            (v[1], temp.value.left_rotate(s(i)).into()),
+            (v_next[1], temp_next.value.left_rotate(s(i)).into()),
+            p,
+            p_next,
+        )?;
+
+        for (v, b) in [(&mut v, b), (&mut v_next, b_next)] {
+            v.rotate_right(1);
+            v[1] = b;
+        }
+    }
+
+    Ok((State { h: v }, State { h: v_next }))
+}
+
+/// Add the compressed working variables to the state, with 4 mul gates.
+pub fn mpc_update_state(
+    v: &(State, State, State),
+    state: &(State, State, State),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let mut h1 = state.0.h;
+    let mut h2 = state.1.h;
+    let mut h3 = state.2.h;
+    for i in 0..4 {
+        (h1[i], h2[i], h3[i]) = mpc_add_mod(
+            (v.0.h[i], h1[i]),
+            (v.1.h[i], h2[i]),
+            (v.2.h[i], h3[i]),
+            p1,
+            p2,
+            p3,
+        );
+    }
+
+    (State { h: h1 }, State { h: h2 }, State { h: h3 })
+}
+
+pub fn mpc_update_state_verify(
+    v: &(State, State),
+    state: &(State, State),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let mut h = state.0.h;
+    let mut h_next = state.1.h;
+    for i in 0..4 {
+        (h[i], h_next[i]) = add_mod_verify((v.0.h[i], h[i]), (v.1.h[i], h_next[i]), p, p_next)?;
+    }
+
+    Ok((State { h }, State { h: h_next }))
+}
+
+pub fn mpc_md5_block(
+    input_p1: &[GF2Word<u32>; 16],
+    input_p2: &[GF2Word<u32>; 16],
+    input_p3: &[GF2Word<u32>; 16],
+    state: &(State, State, State),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let v = mpc_compression(input_p1, input_p2, input_p3, state, p1, p2, p3);
+    mpc_update_state(&v, state, p1, p2, p3)
+}
+
+pub fn mpc_md5_block_verify(
+    input_p: &[GF2Word<u32>; 16],
+    input_p_next: &[GF2Word<u32>; 16],
+    state: &(State, State),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let v = mpc_compression_verify(input_p, input_p_next, state, p, p_next)?;
+    mpc_update_state_verify(&v, state, p, p_next)
+}
+
+#[cfg(test)]
+mod test_md5 {
+    use super::{init_iv, md5_block, mpc_md5_block, mpc_md5_block_verify, padding, State};
+    use crate::{
+        circuits::md5::MUL_GATES_PER_BLOCK,
+        gadgets::{
+            mpc_public, mpc_public_verify, prepare::generic_parse, test_utils::check_shares,
+        },
+        gf2_word::GF2Word,
+    };
+
+    fn words(bytes: &[u8]) -> [GF2Word<u32>; 16] {
+        generic_parse(bytes, 16).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_md5_block() {
+        // RFC 1321 example for "abc"
+        let padded_input = padding(b"abc");
+        let state = md5_block(&padded_input.try_into().unwrap(), &init_iv());
+
+        let digest = [
+            0x90, 0x01, 0x50, 0x98, 0x3c, 0xd2, 0x4f, 0xb0, 0xd6, 0x96, 0x3f, 0x7d, 0x28, 0xe1,
+            0x7f, 0x72,
+        ];
+        assert_eq!(state, State::from_bytes(&digest));
+        assert_eq!(state.to_bytes(), digest);
+    }
+
+    #[test]
+    fn test_mpc_md5_block() {
+        let input: Vec<u8> = (0..64).collect();
+        // a midstate, so that the state words are not all the IV
+        let state = md5_block(&padding(b"abc").try_into().unwrap(), &init_iv());
+
+        check_shares(
+            &input,
+            MUL_GATES_PER_BLOCK,
+            |input| md5_block(&words(input), &state).to_vec(),
+            |p1, p2, p3| {
+                let (s1, s2, s3) = mpc_md5_block(
+                    &words(&p1.view.input),
+                    &words(&p2.view.input),
+                    &words(&p3.view.input),
+                    &mpc_public(state),
+                    p1,
+                    p2,
+                    p3,
+                );
+                (s1.to_vec(), s2.to_vec(), s3.to_vec())
+            },
+            |p, p_next| {
+                let (s, s_next) = mpc_md5_block_verify(
+                    &words(&p.view.input),
+                    &words(&p_next.view.input),
+                    &mpc_public_verify(state),
+                    p,
+                    p_next,
+                )?;
+                Ok((s.to_vec(), s_next.to_vec()))
+            },
+        );
+    }
+}
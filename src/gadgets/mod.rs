@@ -1,14 +1,47 @@
 pub mod add_mod;
+pub mod aes;
+pub mod blake2s;
+pub mod blake3;
+pub mod chacha20;
+pub mod hmac;
+pub mod keccak;
+pub mod lowmc;
+pub mod md5;
 pub mod prepare;
+pub mod ripemd160;
+pub mod sha1;
 pub mod sha256;
+pub mod sha512;
+pub mod simon;
+pub mod speck;
 pub mod verifier;
 
+#[cfg(test)]
+pub(crate) mod test_utils;
+
 use crate::{
     error::Error,
     gf2_word::{GF2Word, Value},
     party::Party,
 };
 
+/// Tapes of the prover are sized by `Circuit::num_of_mul_gates`, so running out
+/// of randomness while proving is a bug in the circuit rather than bad input.
+pub(crate) const TAPE_LEN_MSG: &str = "random tape shorter than the number of mul gates";
+
+/// Shares of a public `value`, of which every party holds a full copy: three
+/// copies XOR to the value itself. Constants, padding and public inputs enter
+/// a computation this way without mul gates, and XORing them into a share,
+/// like any linear map, is then done on each share on its own.
+pub fn mpc_public<S: Clone>(value: S) -> (S, S, S) {
+    (value.clone(), value.clone(), value)
+}
+
+/// The copies of a public `value` held by the two simulated parties.
+pub fn mpc_public_verify<S: Clone>(value: S) -> (S, S) {
+    (value.clone(), value)
+}
+
 pub fn mpc_xor<T: Value>(
     input_p1: (GF2Word<T>, GF2Word<T>),
     input_p2: (GF2Word<T>, GF2Word<T>),
@@ -29,9 +62,9 @@ pub fn mpc_and<T: Value>(
     p2: &mut Party<T>,
     p3: &mut Party<T>,
 ) -> (GF2Word<T>, GF2Word<T>, GF2Word<T>) {
-    let r1 = p1.read_tape();
-    let r2 = p2.read_tape();
-    let r3 = p3.read_tape();
+    let r1 = p1.read_tape().expect(TAPE_LEN_MSG);
+    let r2 = p2.read_tape().expect(TAPE_LEN_MSG);
+    let r3 = p3.read_tape().expect(TAPE_LEN_MSG);
 
     let output_p1 = (input_p1.0 & input_p1.1)
         ^ (input_p1.0 & input_p2.1)
@@ -59,8 +92,8 @@ pub fn mpc_and_verify<T: Value>(
     p: &mut Party<T>,
     p_next: &mut Party<T>,
 ) -> Result<(GF2Word<T>, GF2Word<T>), Error> {
-    let ri = p.read_tape();
-    let ri_next = p_next.read_tape();
+    let ri = p.read_tape()?;
+    let ri_next = p_next.read_tape()?;
 
     let output_p = (input_p.0 & input_p.1)
         ^ (input_p.0 & input_p_next.1)
@@ -73,5 +106,5 @@ pub fn mpc_and_verify<T: Value>(
     */
     p.view.send_msg(output_p);
 
-    Ok((output_p, p_next.read_view()))
+    Ok((output_p, p_next.read_view()?))
 }
@@ -1,9 +1,19 @@
-use crate::gf2_word::{GF2Word, Value};
+use crate::{
+    error::Error,
+    gf2_word::{GF2Word, Value},
+};
 
-pub fn generic_parse<T: Value>(bytes: &[u8], number_of_words: usize) -> Vec<GF2Word<T>> {
-    assert_eq!(bytes.len(), number_of_words * T::bytes_len());
-    bytes
+pub fn generic_parse<T: Value>(
+    bytes: &[u8],
+    number_of_words: usize,
+) -> Result<Vec<GF2Word<T>>, Error> {
+    let expected_len = number_of_words * T::bytes_len();
+    if bytes.len() != expected_len {
+        return Err(Error::InputLengthError(expected_len, bytes.len()));
+    }
+
+    Ok(bytes
         .chunks(T::bytes_len())
         .map(|chunk| T::from_le_bytes(chunk).into())
-        .collect()
+        .collect())
 }
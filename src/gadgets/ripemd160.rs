@@ -0,0 +1,433 @@
+use crate::{
+    error::Error,
+    gadgets::{
+        add_mod::{add_mod_verify, add_mod_verify_k, mpc_add_mod, mpc_add_mod_k},
+        mpc_and, mpc_and_verify,
+    },
+    gf2_word::{BitUtils, GF2Word},
+    party::Party,
+};
+
+const IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// A block of 16 little-endian message words.
+pub type Block = [GF2Word<u32>; 16];
+
+/// The five working variables `[a, b, c, d, e]` of a line.
+type Words = [GF2Word<u32>; 5];
+
+/// The two parallel lines of the compression function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line {
+    Left,
+    Right,
+}
+
+impl Line {
+    /// Index of the message word of step `j`.
+    fn r(&self, j: usize) -> usize {
+        const R: [usize; 80] = [
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 7, 4, 13, 1, 10, 6, 15, 3, 12, 0,
+            9, 5, 2, 14, 11, 8, 3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, 1, 9, 11, 10,
+            0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, 4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6,
+            15, 13,
+        ];
+        const R_PRIME: [usize; 80] = [
+            5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, 6, 11, 3, 7, 0, 13, 5, 10, 14,
+            15, 8, 12, 4, 9, 1, 2, 15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, 8, 6, 4,
+            1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, 12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14,
+            0, 3, 9, 11,
+        ];
+        match self {
+            Line::Left => R[j],
+            Line::Right => R_PRIME[j],
+        }
+    }
+
+    /// Left rotation of step `j`.
+    fn s(&self, j: usize) -> usize {
+        const S: [usize; 80] = [
+            11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, 7, 6, 8, 13, 11, 9, 7, 15, 7,
+            12, 15, 9, 11, 7, 13, 12, 11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, 11,
+            12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, 9, 15, 5, 11, 6, 8, 13, 12, 5, 12,
+            13, 14, 11, 8, 5, 6,
+        ];
+        const S_PRIME: [usize; 80] = [
+            8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, 9, 13, 15, 7, 12, 8, 9, 11, 7,
+            7, 12, 7, 6, 15, 13, 11, 9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, 15, 5,
+            8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, 8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6,
+            5, 15, 13, 11, 11,
+        ];
+        match self {
+            Line::Left => S[j],
+            Line::Right => S_PRIME[j],
+        }
+    }
+
+    /// Constant of step `j`, which is 0 in the first round of the left line
+    /// and the last round of the right line.
+    fn k(&self, j: usize) -> u32 {
+        const K: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+        const K_PRIME: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+        match self {
+            Line::Left => K[j / 16],
+            Line::Right => K_PRIME[j / 16],
+        }
+    }
+
+    /// Boolean function of step `j`: the left line uses f1 to f5 and the
+    /// right line f5 to f1.
+    fn f(&self, j: usize) -> usize {
+        match self {
+            Line::Left => j / 16,
+            Line::Right => 4 - j / 16,
+        }
+    }
+}
+
+/// Chaining value of RIPEMD-160.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    h: Words,
+}
+
+impl State {
+    pub fn to_vec(&self) -> Vec<GF2Word<u32>> {
+        self.h.to_vec()
+    }
+
+    /// Read a state from its little-endian encoding, as in a digest.
+    pub fn from_bytes(bytes: &[u8; 20]) -> Self {
+        let mut h = [GF2Word::from(0u32); 5];
+        for (h, chunk) in h.iter_mut().zip(bytes.chunks(4)) {
+            *h = u32::from_le_bytes(chunk.try_into().unwrap()).into();
+        }
+        Self { h }
+    }
+
+    /// Little-endian encoding of the state, which is the digest once all
+    /// blocks are compressed.
+    pub fn to_bytes(&self) -> [u8; 20] {
+        let mut bytes = [0u8; 20];
+        for (chunk, word) in bytes.chunks_mut(4).zip(self.h.iter()) {
+            chunk.copy_from_slice(&word.value.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+pub fn init_iv() -> State {
+    State {
+        h: IV.map(GF2Word::from),
+    }
+}
+
+/// Pad `input` like MD4 does, with a little-endian bit length, and read it as
+/// little-endian words.
+pub fn padding(input: &[u8]) -> Vec<GF2Word<u32>> {
+    let mut msg = input.to_vec();
+    let length_u64 = (8 * input.len()) as u64;
+    msg.push(0x80);
+
+    while !(msg.len() + 8).is_multiple_of(64) {
+        msg.push(0x00);
+    }
+    msg.extend_from_slice(&length_u64.to_le_bytes());
+
+    msg.chunks(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()).into())
+        .collect()
+}
+
+/// All ones, to complement a word with a XOR.
+const ONES: u32 = u32::MAX;
+
+/// The boolean functions, each with at most one AND:
+/// - f1 = x xor y xor z
+/// - f2 = (x and y) or (not x and z) = x and (y xor z) xor z
+/// - f3 = (x or not y) xor z = (x and y) xor y xor z xor 1
+/// - f4 = (x and z) or (y and not z) = z and (x xor y) xor y
+/// - f5 = x xor (y or not z) = x xor (y and z) xor z xor 1
+fn f(i: usize, x: GF2Word<u32>, y: GF2Word<u32>, z: GF2Word<u32>) -> GF2Word<u32> {
+    match i {
+        0 => x ^ y ^ z,
+        1 => (x & (y ^ z)) ^ z,
+        2 => (x & y) ^ y ^ z ^ ONES.into(),
+        3 => (z & (x ^ y)) ^ y,
+        _ => x ^ (y & z) ^ z ^ ONES.into(),
+    }
+}
+
+/// The operands of the AND of `f_i`, and the linear part added to its output.
+type Operands = (GF2Word<u32>, GF2Word<u32>, GF2Word<u32>);
+
+fn f_operands(i: usize, x: GF2Word<u32>, y: GF2Word<u32>, z: GF2Word<u32>) -> Operands {
+    match i {
+        1 => (x, y ^ z, z),
+        2 => (x, y, y ^ z ^ ONES.into()),
+        3 => (z, x ^ y, y),
+        4 => (y, z, x ^ z ^ ONES.into()),
+        _ => unreachable!("f1 is linear"),
+    }
+}
+
+fn mpc_f(
+    i: usize,
+    v: (&Words, &Words, &Words),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (GF2Word<u32>, GF2Word<u32>, GF2Word<u32>) {
+    let (v1, v2, v3) = v;
+    if i == 0 {
+        return (
+            v1[1] ^ v1[2] ^ v1[3],
+            v2[1] ^ v2[2] ^ v2[3],
+            v3[1] ^ v3[2] ^ v3[3],
+        );
+    }
+
+    let (l1, r1, o1) = f_operands(i, v1[1], v1[2], v1[3]);
+    let (l2, r2, o2) = f_operands(i, v2[1], v2[2], v2[3]);
+    let (l3, r3, o3) = f_operands(i, v3[1], v3[2], v3[3]);
+    let (and1, and2, and3) = mpc_and((l1, r1), (l2, r2), (l3, r3), p1, p2, p3);
+
+    (and1 ^ o1, and2 ^ o2, and3 ^ o3)
+}
+
+fn mpc_f_verify(
+    i: usize,
+    v: (&Words, &Words),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(GF2Word<u32>, GF2Word<u32>), Error> {
+    let (v, v_next) = v;
+    if i == 0 {
+        return Ok((v[1] ^ v[2] ^ v[3], v_next[1] ^ v_next[2] ^ v_next[3]));
+    }
+
+    let (l, r, o) = f_operands(i, v[1], v[2], v[3]);
+    let (l_next, r_next, o_next) = f_operands(i, v_next[1], v_next[2], v_next[3]);
+    let (and, and_next) = mpc_and_verify((l, r), (l_next, r_next), p, p_next)?;
+
+    Ok((and ^ o, and_next ^ o_next))
+}
+
+/// [a, b, c, d, e] := [e, t, b, c leftrotate 10, d]
+fn shift(v: &Words, t: GF2Word<u32>) -> Words {
+    [v[4], t, v[1], v[2].value.left_rotate(10).into(), v[3]]
+}
+
+fn line(line: Line, x: &Block, state: &State) -> Words {
+    let mut v = state.h;
+    for j in 0..80 {
+        let t = v[0]
+            .value
+            .wrapping_add(f(line.f(j), v[1], v[2], v[3]).value)
+            .wrapping_add(x[line.r(j)].value)
+            .wrapping_add(line.k(j))
+            .left_rotate(line.s(j))
+            .wrapping_add(v[4].value);
+        v = shift(&v, t.into());
+    }
+    v
+}
+
+/// Steps of one line on shares: 4 additions per step, or 3 when the constant
+/// is 0, and one AND unless the function is f1.
+fn mpc_line(
+    line: Line,
+    x: (&Block, &Block, &Block),
+    state: &(State, State, State),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (Words, Words, Words) {
+    let (mut v1, mut v2, mut v3) = (state.0.h, state.1.h, state.2.h);
+    for j in 0..80 {
+        let (f1, f2, f3) = mpc_f(line.f(j), (&v1, &v2, &v3), p1, p2, p3);
+
+        // t := ((a + f + x[r] + k) leftrotate s) + e
+        let (t1, t2, t3) = mpc_add_mod((v1[0], f1), (v2[0], f2), (v3[0], f3), p1, p2, p3);
+        let r = line.r(j);
+        let (mut t1, mut t2, mut t3) =
+            mpc_add_mod((t1, x.0[r]), (t2, x.1[r]), (t3, x.2[r]), p1, p2, p3);
+        if line.k(j) != 0 {
+            (t1, t2, t3) = mpc_add_mod_k(t1, t2, t3, line.k(j).into(), p1, p2, p3);
+        }
+        let rotl = |t: GF2Word<u32>| -> GF2Word<u32> { t.value.left_rotate(line.s(j)).into() };
+        let (t1, t2, t3) = mpc_add_mod(
+            (rotl(t1), v1[4]),
+            (rotl(t2), v2[4]),
+            (rotl(t3), v3[4]),
+            p1,
+            p2,
+            p3,
+        );
+
+        (v1, v2, v3) = (shift(&v1, t1), shift(&v2, t2), shift(&v3, t3));
+    }
+
+    (v1, v2, v3)
+}
+
+fn mpc_line_verify(
+    line: Line,
+    x: (&Block, &Block),
+    state: &(State, State),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(Words, Words), Error> {
+    let (mut v, mut v_next) = (state.0.h, state.1.h);
+    for j in 0..80 {
+        let (f, f_next) = mpc_f_verify(line.f(j), (&v, &v_next), p, p_next)?;
+
+        let (t, t_next) = add_mod_verify((v[0], f), (v_next[0], f_next), p, p_next)?;
+        let r = line.r(j);
+        let (mut t, mut t_next) = add_mod_verify((t, x.0[r]), (t_next, x.1[r]), p, p_next)?;
+        if line.k(j) != 0 {
+            (t, t_next) = add_mod_verify_k(t, t_next, line.k(j).into(), p, p_next)?;
+        }
+        let rotl = |t: GF2Word<u32>| -> GF2Word<u32> { t.value.left_rotate(line.s(j)).into() };
+        let (t, t_next) = add_mod_verify((rotl(t), v[4]), (rotl(t_next), v_next[4]), p, p_next)?;
+
+        (v, v_next) = (shift(&v, t), shift(&v_next, t_next));
+    }
+
+    Ok((v, v_next))
+}
+
+/// Indices `(i, j, k)` of the final combination h'[n] := h[i] + left[j] + right[k].
+const COMBINE: [(usize, usize, usize); 5] = [(1, 2, 3), (2, 3, 4), (3, 4, 0), (4, 0, 1), (0, 1, 2)];
+
+/// Natively compress one block of 16 little-endian words into `state`, as
+/// `mpc_ripemd160_block` does on shares.
+pub fn ripemd160_block(input: &Block, state: &State) -> State {
+    let left = line(Line::Left, input, state);
+    let right = line(Line::Right, input, state);
+
+    let mut h = state.h;
+    for (h, (i, j, k)) in h.iter_mut().zip(COMBINE) {
+        *h = state.h[i]
+            .value
+            .wrapping_add(left[j].value)
+            .wrapping_add(right[k].value)
+            .into();
+    }
+    State { h }
+}
+
+pub fn mpc_ripemd160_block(
+    input_p1: &Block,
+    input_p2: &Block,
+    input_p3: &Block,
+    state: &(State, State, State),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let x = (input_p1, input_p2, input_p3);
+    let (l1, l2, l3) = mpc_line(Line::Left, x, state, p1, p2, p3);
+    let (r1, r2, r3) = mpc_line(Line::Right, x, state, p1, p2, p3);
+
+    let (mut h1, mut h2, mut h3) = (state.0.h, state.1.h, state.2.h);
+    for (n, (i, j, k)) in COMBINE.into_iter().enumerate() {
+        let (o1, o2, o3) = mpc_add_mod(
+            (state.0.h[i], l1[j]),
+            (state.1.h[i], l2[j]),
+            (state.2.h[i], l3[j]),
+            p1,
+            p2,
+            p3,
+        );
+        (h1[n], h2[n], h3[n]) = mpc_add_mod((o1, r1[k]), (o2, r2[k]), (o3, r3[k]), p1, p2, p3);
+    }
+
+    (State { h: h1 }, State { h: h2 }, State { h: h3 })
+}
+
+pub fn mpc_ripemd160_block_verify(
+    input_p: &Block,
+    input_p_next: &Block,
+    state: &(State, State),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let x = (input_p, input_p_next);
+    let (l, l_next) = mpc_line_verify(Line::Left, x, state, p, p_next)?;
+    let (r, r_next) = mpc_line_verify(Line::Right, x, state, p, p_next)?;
+
+    let (mut h, mut h_next) = (state.0.h, state.1.h);
+    for (n, (i, j, k)) in COMBINE.into_iter().enumerate() {
+        let (o, o_next) =
+            add_mod_verify((state.0.h[i], l[j]), (state.1.h[i], l_next[j]), p, p_next)?;
+        (h[n], h_next[n]) = add_mod_verify((o, r[k]), (o_next, r_next[k]), p, p_next)?;
+    }
+
+    Ok((State { h }, State { h: h_next }))
+}
+
+#[cfg(test)]
+mod test_ripemd160 {
+    use super::{
+        init_iv, mpc_ripemd160_block, mpc_ripemd160_block_verify, padding, ripemd160_block, Block,
+    };
+    use crate::{
+        circuits::ripemd160::MUL_GATES_PER_BLOCK,
+        gadgets::{
+            mpc_public, mpc_public_verify, prepare::generic_parse, test_utils::check_shares,
+        },
+    };
+
+    fn words(bytes: &[u8]) -> Block {
+        generic_parse(bytes, 16).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_ripemd160_block() {
+        // example for "abc" of the RIPEMD-160 authors
+        let state = ripemd160_block(&padding(b"abc").try_into().unwrap(), &init_iv());
+        assert_eq!(
+            state.to_bytes(),
+            [
+                0x8e, 0xb2, 0x08, 0xf7, 0xe0, 0x5d, 0x98, 0x7a, 0x9b, 0x04, 0x4a, 0x8e, 0x98, 0xc6,
+                0xb0, 0x87, 0xf1, 0x5a, 0x0b, 0xfc
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mpc_ripemd160_block() {
+        let input: Vec<u8> = (0..64).collect();
+        // a midstate, so that the state words are not all the IV
+        let state = ripemd160_block(&padding(b"abc").try_into().unwrap(), &init_iv());
+
+        check_shares(
+            &input,
+            MUL_GATES_PER_BLOCK,
+            |input| ripemd160_block(&words(input), &state).to_vec(),
+            |p1, p2, p3| {
+                let (s1, s2, s3) = mpc_ripemd160_block(
+                    &words(&p1.view.input),
+                    &words(&p2.view.input),
+                    &words(&p3.view.input),
+                    &mpc_public(state),
+                    p1,
+                    p2,
+                    p3,
+                );
+                (s1.to_vec(), s2.to_vec(), s3.to_vec())
+            },
+            |p, p_next| {
+                let (s, s_next) = mpc_ripemd160_block_verify(
+                    &words(&p.view.input),
+                    &words(&p_next.view.input),
+                    &mpc_public_verify(state),
+                    p,
+                    p_next,
+                )?;
+                Ok((s.to_vec(), s_next.to_vec()))
+            },
+        );
+    }
+}
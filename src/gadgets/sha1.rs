@@ -0,0 +1,380 @@
+use crate::{
+    error::Error,
+    gadgets::{
+        add_mod::{add_mod_verify, add_mod_verify_k, mpc_add_mod, mpc_add_mod_k},
+        mpc_and, mpc_and_verify,
+    },
+    gf2_word::{BitUtils, GF2Word},
+    party::Party,
+};
+
+/// SHA-1 pads its message exactly like SHA-256.
+pub use crate::gadgets::sha256::{padding, padding_after};
+
+const SHA1_IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// Round constants, one for each group of 20 rounds.
+const K: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+/// Chaining value of SHA-1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    h: [GF2Word<u32>; 5],
+}
+
+impl State {
+    pub fn to_vec(&self) -> Vec<GF2Word<u32>> {
+        self.h.to_vec()
+    }
+
+    /// Read a state from its big-endian encoding, as in a digest.
+    pub fn from_bytes(bytes: &[u8; 20]) -> Self {
+        bytes
+            .chunks(4)
+            .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()).into())
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Big-endian encoding of the state, which is the digest once all blocks
+    /// are compressed.
+    pub fn to_bytes(&self) -> [u8; 20] {
+        let mut bytes = [0u8; 20];
+        for (chunk, word) in bytes.chunks_mut(4).zip(self.h.iter()) {
+            chunk.copy_from_slice(&word.value.to_be_bytes());
+        }
+        bytes
+    }
+}
+
+impl From<Vec<GF2Word<u32>>> for State {
+    fn from(value: Vec<GF2Word<u32>>) -> Self {
+        Self {
+            h: value.try_into().unwrap(),
+        }
+    }
+}
+
+pub fn init_iv() -> State {
+    State {
+        h: SHA1_IV.map(GF2Word::from),
+    }
+}
+
+/// One party's message schedule: the 80 words `w[0..79]`.
+pub type MsgSchedule = [GF2Word<u32>; 80];
+
+/// w[i] := (w[i-3] xor w[i-8] xor w[i-14] xor w[i-16]) leftrotate 1
+///
+/// The expansion is linear, so every party computes it on its own share.
+pub fn msg_schedule(input: &[GF2Word<u32>; 16]) -> MsgSchedule {
+    let mut w = input.to_vec();
+    for i in 16..80 {
+        let word = w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16];
+        w.push(word.value.left_rotate(1).into());
+    }
+
+    w.try_into().unwrap()
+}
+
+/// Natively compress one block of 16 words into `state`, as `mpc_sha1_block`
+/// does on shares.
+pub fn sha1_block(input: &[GF2Word<u32>; 16], state: &State) -> State {
+    let w = msg_schedule(input);
+
+    let mut v = state.h;
+    for i in 0..80 {
+        let [a, b, c, d, e] = v;
+        let f = match i / 20 {
+            0 => (b & (c ^ d)) ^ d,
+            2 => ((b ^ c) & (b ^ d)) ^ b,
+            _ => b ^ c ^ d,
+        };
+        let temp = a
+            .value
+            .left_rotate(5)
+            .wrapping_add(f.value)
+            .wrapping_add(e.value)
+            .wrapping_add(K[i / 20])
+            .wrapping_add(w[i].value);
+
+        v = [temp.into(), a, b.value.left_rotate(30).into(), c, d];
+    }
+
+    let mut h = state.h;
+    for (h, v) in h.iter_mut().zip(v) {
+        *h = h.value.wrapping_add(v.value).into();
+    }
+    State { h }
+}
+
+/// Run the 80 rounds of the compression function on shares of the working
+/// variables `[a, b, c, d, e]`. Ch and Maj take one mul gate, Parity none,
+/// and each round adds 4 words.
+pub fn mpc_compression(
+    w_p1: &MsgSchedule,
+    w_p2: &MsgSchedule,
+    w_p3: &MsgSchedule,
+    state: &(State, State, State),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let mut v1 = state.0.h;
+    let mut v2 = state.1.h;
+    let mut v3 = state.2.h;
+
+    for i in 0..80 {
+        let (f_1, f_2, f_3) = match i / 20 {
+            // Ch := (b and c) xor ((not b) and d) = b and (c xor d) xor d
+            0 => {
+                let (o1, o2, o3) = mpc_and(
+                    (v1[1], v1[2] ^ v1[3]),
+                    (v2[1], v2[2] ^ v2[3]),
+                    (v3[1], v3[2] ^ v3[3]),
+                    p1,
+                    p2,
+                    p3,
+                );
+                (o1 ^ v1[3], o2 ^ v2[3], o3 ^ v3[3])
+            }
+            // Maj := (b and c) xor (b and d) xor (c and d) = (b xor c) and (b xor d) xor b
+            2 => {
+                let (o1, o2, o3) = mpc_and(
+                    (v1[1] ^ v1[2], v1[1] ^ v1[3]),
+                    (v2[1] ^ v2[2], v2[1] ^ v2[3]),
+                    (v3[1] ^ v3[2], v3[1] ^ v3[3]),
+                    p1,
+                    p2,
+                    p3,
+                );
+                (o1 ^ v1[1], o2 ^ v2[1], o3 ^ v3[1])
+            }
+            // Parity := b xor c xor d
+            _ => (
+                v1[1] ^ v1[2] ^ v1[3],
+                v2[1] ^ v2[2] ^ v2[3],
+                v3[1] ^ v3[2] ^ v3[3],
+            ),
+        };
+
+        // temp := (a leftrotate 5) + f + e + k + w[i]
+        let (temp_1, temp_2, temp_3) = {
+            let (o1, o2, o3) = mpc_add_mod(
+                (v1[0].value.left_rotate(5).into(), f_1),
+                (v2[0].value.left_rotate(5).into(), f_2),
+                (v3[0].value.left_rotate(5).into(), f_3),
+                p1,
+                p2,
+                p3,
+            );
+            let (o1, o2, o3) = mpc_add_mod((o1, v1[4]), (o2, v2[4]), (o3, v3[4]), p1, p2, p3);
+            let (o1, o2, o3) = mpc_add_mod_k(o1, o2, o3, K[i / 20].into(), p1, p2, p3);
+            mpc_add_mod((o1, w_p1[i]), (o2, w_p2[i]), (o3, w_p3[i]), p1, p2, p3)
+        };
+
+        // e := d, d := c, c := b leftrotate 30, b := a, a := temp
+        for (v, temp) in [(&mut v1, temp_1), (&mut v2, temp_2), (&mut v3, temp_3)] {
+            v.rotate_right(1);
+            v[2] = v[2].value.left_rotate(30).into();
+            v[0] = temp;
+        }
+    }
+
+    (State { h: v1 }, State { h: v2 }, State { h: v3 })
+}
+
+pub fn mpc_compression_verify(
+    w_p: &MsgSchedule,
+    w_p_next: &MsgSchedule,
+    state: &(State, State),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let mut v = state.0.h;
+    let mut v_next = state.1.h;
+
+    for i in 0..80 {
+        let (f, f_next) = match i / 20 {
+            // Ch := b and (c xor d) xor d
+            0 => {
+                let (o, o_next) = mpc_and_verify(
+                    (v[1], v[2] ^ v[3]),
+                    (v_next[1], v_next[2] ^ v_next[3]),
+                    p,
+                    p_next,
+                )?;
+                (o ^ v[3], o_next ^ v_next[3])
+            }
+            // Maj := (b xor c) and (b xor d) xor b
+            2 => {
+                let (o, o_next) = mpc_and_verify(
+                    (v[1] ^ v[2], v[1] ^ v[3]),
+                    (v_next[1] ^ v_next[2], v_next[1] ^ v_next[3]),
+                    p,
+                    p_next,
+                )?;
+                (o ^ v[1], o_next ^ v_next[1])
+            }
+            // Parity := b xor c xor d
+            _ => (v[1] ^ v[2] ^ v[3], v_next[1] ^ v_next[2] ^ v_next[3]),
+        };
+
+        // temp := (a leftrotate 5) + f + e + k + w[i]
+        let (temp, temp_next) = {
+            let (o, o_next) = add_mod_verify(
+                (v[0].value.left_rotate(5).into(), f),
+                (v_next[0].value.left_rotate(5).into(), f_next),
+                p,
+                p_next,
+            )?;
+            let (o, o_next) = add_mod_verify((o, v[4]), (o_next, v_next[4]), p, p_next)?;
+            let (o, o_next) = add_mod_verify_k(o, o_next, K[i / 20].into(), p, p_next)?;
+            add_mod_verify((o, w_p[i]), (o_next, w_p_next[i]), p, p_next)?
+        };
+
+        for (v, temp) in [(&mut v, temp), (&mut v_next, temp_next)] {
+            v.rotate_right(1);
+            v[2] = v[2].value.left_rotate(30).into();
+            v[0] = temp;
+        }
+    }
+
+    Ok((State { h: v }, State { h: v_next }))
+}
+
+/// Add the compressed working variables to the state, with 5 mul gates.
+pub fn mpc_update_state(
+    v: &(State, State, State),
+    state: &(State, State, State),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let mut h1 = state.0.h;
+    let mut h2 = state.1.h;
+    let mut h3 = state.2.h;
+    for i in 0..5 {
+        (h1[i], h2[i], h3[i]) = mpc_add_mod(
+            (v.0.h[i], h1[i]),
+            (v.1.h[i], h2[i]),
+            (v.2.h[i], h3[i]),
+            p1,
+            p2,
+            p3,
+        );
+    }
+
+    (State { h: h1 }, State { h: h2 }, State { h: h3 })
+}
+
+pub fn mpc_update_state_verify(
+    v: &(State, State),
+    state: &(State, State),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let mut h = state.0.h;
+    let mut h_next = state.1.h;
+    for i in 0..5 {
+        (h[i], h_next[i]) = add_mod_verify((v.0.h[i], h[i]), (v.1.h[i], h_next[i]), p, p_next)?;
+    }
+
+    Ok((State { h }, State { h: h_next }))
+}
+
+pub fn mpc_sha1_block(
+    input_p1: &[GF2Word<u32>; 16],
+    input_p2: &[GF2Word<u32>; 16],
+    input_p3: &[GF2Word<u32>; 16],
+    state: &(State, State, State),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let w_1 = msg_schedule(input_p1);
+    let w_2 = msg_schedule(input_p2);
+    let w_3 = msg_schedule(input_p3);
+
+    let v = mpc_compression(&w_1, &w_2, &w_3, state, p1, p2, p3);
+    mpc_update_state(&v, state, p1, p2, p3)
+}
+
+pub fn mpc_sha1_block_verify(
+    input_p: &[GF2Word<u32>; 16],
+    input_p_next: &[GF2Word<u32>; 16],
+    state: &(State, State),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let w = msg_schedule(input_p);
+    let w_next = msg_schedule(input_p_next);
+
+    let v = mpc_compression_verify(&w, &w_next, state, p, p_next)?;
+    mpc_update_state_verify(&v, state, p, p_next)
+}
+
+#[cfg(test)]
+mod test_sha1 {
+    use super::{init_iv, mpc_sha1_block, mpc_sha1_block_verify, padding, sha1_block, State};
+    use crate::{
+        circuits::sha1::MUL_GATES_PER_BLOCK,
+        gadgets::{
+            mpc_public, mpc_public_verify, prepare::generic_parse, test_utils::check_shares,
+        },
+        gf2_word::GF2Word,
+    };
+
+    fn words(bytes: &[u8]) -> [GF2Word<u32>; 16] {
+        generic_parse(bytes, 16).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_sha1_block() {
+        // FIPS 180 example for "abc"
+        let padded_input = padding(b"abc");
+        let state = sha1_block(&padded_input.try_into().unwrap(), &init_iv());
+
+        let digest = [
+            0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e, 0x25, 0x71, 0x78, 0x50,
+            0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d,
+        ];
+        assert_eq!(state, State::from_bytes(&digest));
+        assert_eq!(state.to_bytes(), digest);
+    }
+
+    #[test]
+    fn test_mpc_sha1_block() {
+        let input: Vec<u8> = (0..64).collect();
+        // a midstate, so that the state words are not all the IV
+        let state = sha1_block(&padding(b"abc").try_into().unwrap(), &init_iv());
+
+        check_shares(
+            &input,
+            MUL_GATES_PER_BLOCK,
+            |input| sha1_block(&words(input), &state).to_vec(),
+            |p1, p2, p3| {
+                let (s1, s2, s3) = mpc_sha1_block(
+                    &words(&p1.view.input),
+                    &words(&p2.view.input),
+                    &words(&p3.view.input),
+                    &mpc_public(state),
+                    p1,
+                    p2,
+                    p3,
+                );
+                (s1.to_vec(), s2.to_vec(), s3.to_vec())
+            },
+            |p, p_next| {
+                let (s, s_next) = mpc_sha1_block_verify(
+                    &words(&p.view.input),
+                    &words(&p_next.view.input),
+                    &mpc_public_verify(state),
+                    p,
+                    p_next,
+                )?;
+                Ok((s.to_vec(), s_next.to_vec()))
+            },
+        );
+    }
+}
@@ -3,22 +3,33 @@ mod final_digest;
 mod iv;
 mod msg_schedule;
 mod padding;
+mod secret_padding;
 
-mod test_vectors;
+#[cfg(test)]
+pub(crate) mod test_vectors;
 
-use crate::{error::Error, gf2_word::GF2Word, party::Party};
+use crate::{
+    error::Error,
+    gadgets::{mpc_public, mpc_public_verify},
+    gf2_word::GF2Word,
+    party::Party,
+};
 use std::ops::Deref;
 
 use self::{
-    compression::{mpc_compression, mpc_compression_verify},
-    final_digest::{mpc_update_state, mpc_update_state_verify},
-    msg_schedule::{mpc_msg_schedule, mpc_msg_schedule_verify},
+    compression::{compression, mpc_compression, mpc_compression_verify},
+    final_digest::{digest, mpc_update_state, mpc_update_state_verify},
+    msg_schedule::{mpc_msg_schedule, mpc_msg_schedule_verify, msg_schedule},
+};
+pub use iv::{init_iv, init_iv_224};
+pub use padding::{padding, padding_after};
+pub use secret_padding::{
+    mpc_secret_padding, mpc_secret_padding_verify, mpc_select_state, mpc_select_state_verify,
+    SecretPadding,
 };
-pub use iv::init_iv;
-pub use padding::padding;
 
 /// TODO: Doc
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct State {
     h0: GF2Word<u32>,
     h1: GF2Word<u32>,
@@ -37,6 +48,25 @@ impl State {
         ]
         .to_vec()
     }
+
+    /// Read a state from its big-endian encoding, as in a digest.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        bytes
+            .chunks(4)
+            .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()).into())
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Big-endian encoding of the state, which is the digest once all blocks
+    /// are compressed.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (chunk, word) in bytes.chunks_mut(4).zip(self.to_vec()) {
+            chunk.copy_from_slice(&word.value.to_be_bytes());
+        }
+        bytes
+    }
 }
 
 impl From<Vec<GF2Word<u32>>> for State {
@@ -168,6 +198,15 @@ impl Deref for H {
     }
 }
 
+/// Natively compress one block of 16 words into `state`, as `mpc_sha256_block`
+/// does on shares.
+pub fn sha256_block(input: &[GF2Word<u32>; 16], state: &State) -> State {
+    let msg_schedule = msg_schedule(input);
+    let working_variables = compression(&msg_schedule, state);
+
+    digest(&working_variables.try_into().unwrap(), state).into()
+}
+
 pub fn mpc_sha256_block(
     input_p1: &[GF2Word<u32>; 16],
     input_p2: &[GF2Word<u32>; 16],
@@ -208,6 +247,27 @@ pub fn mpc_sha256_block(
     )
 }
 
+/// Compress one block starting from a public `midstate`.
+pub fn mpc_sha256_block_from_midstate(
+    input_p1: &[GF2Word<u32>; 16],
+    input_p2: &[GF2Word<u32>; 16],
+    input_p3: &[GF2Word<u32>; 16],
+    midstate: &State,
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    mpc_sha256_block(
+        input_p1,
+        input_p2,
+        input_p3,
+        &mpc_public(*midstate),
+        p1,
+        p2,
+        p3,
+    )
+}
+
 pub fn mpc_sha256_block_verify(
     input_p: &[GF2Word<u32>; 16],
     input_p_next: &[GF2Word<u32>; 16],
@@ -216,7 +276,7 @@ pub fn mpc_sha256_block_verify(
     p_next: &mut Party<u32>,
 ) -> Result<(State, State), Error> {
     let (msg_schedule_p, msg_schedule_p_next) =
-        mpc_msg_schedule_verify(input_p, input_p_next, p, p_next);
+        mpc_msg_schedule_verify(input_p, input_p_next, p, p_next)?;
 
     // Initialise working variables to current state
     let working_variables = (state.0.to_vec().into(), state.1.to_vec().into());
@@ -229,196 +289,85 @@ pub fn mpc_sha256_block_verify(
         p_next,
     )?;
 
-    Ok(mpc_update_state_verify(
+    mpc_update_state_verify(
         &compression_output_p.try_into().unwrap(),
         &compression_output_p_next.try_into().unwrap(),
         state,
         p,
         p_next,
-    ))
+    )
 }
 
-#[cfg(test)]
-mod test_sha256 {
-
-    use rand::{rngs::ThreadRng, thread_rng};
-    use rand_chacha::ChaCha20Rng;
-    use sha2::{Digest, Sha256};
-    use sha3::Keccak256;
-
-    use crate::{
-        circuit::{Circuit, Output},
-        error::Error,
-        gadgets::sha256::padding::padding,
-        gf2_word::GF2Word,
-        party::Party,
-        prover::Prover,
-        verifier::Verifier,
-    };
-
-    use super::{iv::init_iv, *};
-
-    pub struct Sha256Circuit {
-        preimage: String,
-    }
-
-    impl Circuit<u32> for Sha256Circuit {
-        fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
-            assert_eq!(input.len(), 0);
-            // create a Sha256 object
-            let mut hasher = Sha256::new();
-
-            // write input message
-            hasher.update(self.preimage.as_bytes());
-
-            // read hash digest and consume hasher
-            let digest = hasher.finalize().to_vec();
-
-            let mut res = Vec::with_capacity(8);
-            for i in 0..8 {
-                let word = u32::from_be_bytes(digest[(4 * i)..(4 * i + 4)].try_into().unwrap());
-                res.push(word.into())
-            }
-
-            res
-        }
-
-        fn compute_23_decomposition(
-            &self,
-            p1: &mut Party<u32>,
-            p2: &mut Party<u32>,
-            p3: &mut Party<u32>,
-        ) -> (Vec<GF2Word<u32>>, Vec<GF2Word<u32>>, Vec<GF2Word<u32>>) {
-            let p1_words = padding(&p1.view.input);
-            let p2_words = padding(&p2.view.input);
-            let p3_words = padding(&p3.view.input);
-
-            // Initialize state
-            let mut p1_state: State = init_iv().to_vec().into();
-            let mut p2_state: State = init_iv().to_vec().into();
-            let mut p3_state: State = init_iv().to_vec().into();
-
-            // Process padded input chunk by chunk
-            let iter_chunks = p1_words
-                .chunks(16)
-                .zip(p2_words.chunks(16))
-                .zip(p3_words.chunks(16));
-
-            for ((p1_words, p2_words), p3_words) in iter_chunks {
-                (p1_state, p2_state, p3_state) = mpc_sha256_block(
-                    &p1_words.try_into().unwrap(),
-                    &p2_words.try_into().unwrap(),
-                    &p3_words.try_into().unwrap(),
-                    &(p1_state, p2_state, p3_state),
-                    p1,
-                    p2,
-                    p3,
-                );
-            }
-
-            (p1_state.to_vec(), p2_state.to_vec(), p3_state.to_vec())
-        }
-
-        fn simulate_two_parties(
-            &self,
-            p: &mut Party<u32>,
-            p_next: &mut Party<u32>,
-        ) -> Result<(Output<u32>, Output<u32>), Error> {
-            let p_words = padding(&p.view.input);
-            let p_next_words = padding(&p_next.view.input);
-
-            // Initialize state
-            let mut p_state: State = init_iv().to_vec().into();
-            let mut p_next_state: State = init_iv().to_vec().into();
-
-            // Process padded input chunk by chunk
-            let iter_chunks = p_words.chunks(16).zip(p_next_words.chunks(16));
-
-            for (p_words, p_next_words) in iter_chunks {
-                (p_state, p_next_state) = mpc_sha256_block_verify(
-                    &p_words.try_into().unwrap(),
-                    &p_next_words.try_into().unwrap(),
-                    &(p_state.to_vec().into(), p_next_state.to_vec().into()),
-                    p,
-                    p_next,
-                )?;
-            }
-
-            Ok((p_state.to_vec(), p_next_state.to_vec()))
-        }
-
-        fn party_input_len(&self) -> usize {
-            16
-        }
-
-        fn party_output_len(&self) -> usize {
-            8
-        }
-
-        fn num_of_mul_gates(&self) -> usize {
-            let num_chunks = padding(self.preimage.as_bytes()).len();
-
-            let msg_schedule = 3 * 48;
-            let compression = 9 * 64;
-            let digest = 8;
-
-            (msg_schedule + compression + digest) * num_chunks
-        }
-    }
-
-    #[test]
-    fn test_short_input() {
-        let mut rng = thread_rng();
-        const SIGMA: usize = 80;
-
-        let preimage = String::from("abc");
-
-        let circuit = Sha256Circuit {
-            preimage: preimage.clone(),
-        };
-
-        let output = circuit.compute(&[]);
-        let expected_output = crate::gadgets::sha256::test_vectors::short::DIGEST_OUTPUT;
-        for (&word, &expected_word) in output.iter().zip(expected_output.iter()) {
-            assert_eq!(word.value, expected_word);
-        }
+pub fn mpc_sha256_block_from_midstate_verify(
+    input_p: &[GF2Word<u32>; 16],
+    input_p_next: &[GF2Word<u32>; 16],
+    midstate: &State,
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    mpc_sha256_block_verify(
+        input_p,
+        input_p_next,
+        &mpc_public_verify(*midstate),
+        p,
+        p_next,
+    )
+}
 
-        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove::<ThreadRng, SIGMA>(
-            &mut rng,
-            preimage.as_bytes(),
-            &circuit,
-            &output,
-        )
-        .unwrap();
+/// Natively compress padded `words`, a whole number of blocks, into `state`.
+pub fn sha256_blocks(words: &[GF2Word<u32>], state: &State) -> State {
+    words.chunks(16).fold(*state, |state, block| {
+        sha256_block(&block.try_into().unwrap(), &state)
+    })
+}
 
-        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output).unwrap();
+/// Compress shares of padded words, a whole number of blocks, into shares of
+/// `state`, one block after the other.
+pub fn mpc_sha256_blocks(
+    words_p1: &[GF2Word<u32>],
+    words_p2: &[GF2Word<u32>],
+    words_p3: &[GF2Word<u32>],
+    state: &(State, State, State),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let iter_chunks = words_p1
+        .chunks(16)
+        .zip(words_p2.chunks(16))
+        .zip(words_p3.chunks(16));
+
+    let mut state = *state;
+    for ((p1_words, p2_words), p3_words) in iter_chunks {
+        state = mpc_sha256_block(
+            &p1_words.try_into().unwrap(),
+            &p2_words.try_into().unwrap(),
+            &p3_words.try_into().unwrap(),
+            &state,
+            p1,
+            p2,
+            p3,
+        );
     }
+    state
+}
 
-    #[test]
-    fn test_long_input() {
-        let mut rng = thread_rng();
-        const SIGMA: usize = 80;
-
-        let preimage = String::from("abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu");
-
-        let circuit = Sha256Circuit {
-            preimage: preimage.clone(),
-        };
-
-        let output = circuit.compute(&[]);
-        let expected_output = crate::gadgets::sha256::test_vectors::long::DIGEST_OUTPUT;
-        for (&word, &expected_word) in output.iter().zip(expected_output.iter()) {
-            assert_eq!(word.value, expected_word);
-        }
-
-        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove::<ThreadRng, SIGMA>(
-            &mut rng,
-            preimage.as_bytes(),
-            &circuit,
-            &output,
-        )
-        .unwrap();
-
-        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output).unwrap();
+pub fn mpc_sha256_blocks_verify(
+    words_p: &[GF2Word<u32>],
+    words_p_next: &[GF2Word<u32>],
+    state: &(State, State),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let mut state = *state;
+    for (p_words, p_next_words) in words_p.chunks(16).zip(words_p_next.chunks(16)) {
+        state = mpc_sha256_block_verify(
+            &p_words.try_into().unwrap(),
+            &p_next_words.try_into().unwrap(),
+            &state,
+            p,
+            p_next,
+        )?;
     }
+    Ok(state)
 }
@@ -74,17 +74,19 @@ pub fn ch_verify(
 #[cfg(test)]
 mod test_ch {
 
-    use rand::{rngs::ThreadRng, thread_rng};
+    use rand::thread_rng;
     use rand_chacha::ChaCha20Rng;
     use sha3::Keccak256;
 
     use crate::{
         circuit::{Circuit, Output},
         error::Error,
+        fs::DomainSeparator,
         gadgets::prepare::generic_parse,
         gf2_word::GF2Word,
         party::Party,
         prover::Prover,
+        security::SecurityParams,
         verifier::Verifier,
     };
 
@@ -94,7 +96,7 @@ mod test_ch {
 
     impl Circuit<u32> for ChCircuit {
         fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
-            let words = generic_parse(input, self.party_input_len());
+            let words = generic_parse(input, self.party_input_len()).unwrap();
             let res = ch(words[0].value, words[1].value, words[2].value);
             vec![res.into()]
         }
@@ -105,9 +107,9 @@ mod test_ch {
             p2: &mut Party<u32>,
             p3: &mut Party<u32>,
         ) -> (Vec<GF2Word<u32>>, Vec<GF2Word<u32>>, Vec<GF2Word<u32>>) {
-            let p1_words = generic_parse(&p1.view.input, self.party_input_len());
-            let p2_words = generic_parse(&p2.view.input, self.party_input_len());
-            let p3_words = generic_parse(&p3.view.input, self.party_input_len());
+            let p1_words = generic_parse(&p1.view.input, self.party_input_len()).unwrap();
+            let p2_words = generic_parse(&p2.view.input, self.party_input_len()).unwrap();
+            let p3_words = generic_parse(&p3.view.input, self.party_input_len()).unwrap();
 
             let input_p1 = (p1_words[0], p1_words[1], p1_words[2]);
             let input_p2 = (p2_words[0], p2_words[1], p2_words[2]);
@@ -122,8 +124,8 @@ mod test_ch {
             p: &mut Party<u32>,
             p_next: &mut Party<u32>,
         ) -> Result<(Output<u32>, Output<u32>), Error> {
-            let p_words = generic_parse(&p.view.input, self.party_input_len());
-            let p_next_words = generic_parse(&p_next.view.input, self.party_input_len());
+            let p_words = generic_parse(&p.view.input, self.party_input_len())?;
+            let p_next_words = generic_parse(&p_next.view.input, self.party_input_len())?;
 
             let input_p = (p_words[0], p_words[1], p_words[2]);
             let input_p_next = (p_next_words[0], p_next_words[1], p_next_words[2]);
@@ -133,6 +135,10 @@ mod test_ch {
             Ok((vec![o1], vec![o2]))
         }
 
+        fn description(&self) -> Vec<u8> {
+            b"sha256/ch".to_vec()
+        }
+
         fn party_output_len(&self) -> usize {
             1
         }
@@ -149,7 +155,7 @@ mod test_ch {
     #[test]
     fn test_circuit() {
         let mut rng = thread_rng();
-        const SIGMA: usize = 80;
+        let params = SecurityParams::BITS_80;
 
         let input: Vec<u8> = [
             381321u32.to_le_bytes(),
@@ -164,11 +170,13 @@ mod test_ch {
 
         let output = circuit.compute(&input);
 
-        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove::<ThreadRng, SIGMA>(
-            &mut rng, &input, &circuit, &output,
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &input, &circuit, &output, &params, &dst,
         )
         .unwrap();
 
-        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output).unwrap();
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
     }
 }
@@ -1,11 +1,13 @@
 use crate::{
     error::Error,
-    gadgets::add_mod::{add_mod_verify, add_mod_verify_k, adder, mpc_add_mod, mpc_add_mod_k},
+    gadgets::add_mod::{add_mod_verify, add_mod_verify_k, mpc_add_mod, mpc_add_mod_k},
     gf2_word::GF2Word,
     party::Party,
 };
 
 pub fn temp1(h: u32, s1: u32, ch: u32, w_i: u32, k_i: u32) -> u32 {
+    use crate::gadgets::add_mod::adder;
+
     let var_1 = adder(h, s1);
     let var_2 = adder(var_1, ch);
     let var_3 = adder(var_2, k_i);
@@ -64,7 +66,7 @@ pub fn mpc_temp1_verify(
 ) -> Result<(GF2Word<u32>, GF2Word<u32>), Error> {
     // first_var = h + S1
     let (first_var_p, first_var_p_next) =
-        add_mod_verify((h_p, s1_p), (h_p_next, s1_p_next), p, p_next);
+        add_mod_verify((h_p, s1_p), (h_p_next, s1_p_next), p, p_next)?;
 
     // second_var = first_var + ch
     let (second_var_p, second_var_p_next) = add_mod_verify(
@@ -72,7 +74,7 @@ pub fn mpc_temp1_verify(
         (first_var_p_next, ch_p_next),
         p,
         p_next,
-    );
+    )?;
 
     // third_var = second_var + wi
     let (third_var_p, third_var_p_next) = add_mod_verify(
@@ -80,10 +82,11 @@ pub fn mpc_temp1_verify(
         (second_var_p_next, wi_p_next),
         p,
         p_next,
-    );
+    )?;
 
     // output = third_var + ki
-    let (output_p, output_p_next) = add_mod_verify_k(third_var_p, third_var_p_next, k_i, p, p_next);
+    let (output_p, output_p_next) =
+        add_mod_verify_k(third_var_p, third_var_p_next, k_i, p, p_next)?;
 
     Ok((output_p, output_p_next))
 }
@@ -91,17 +94,19 @@ pub fn mpc_temp1_verify(
 #[cfg(test)]
 mod test_temp1 {
 
-    use rand::{rngs::ThreadRng, thread_rng};
+    use rand::thread_rng;
     use rand_chacha::ChaCha20Rng;
     use sha3::Keccak256;
 
     use crate::{
         circuit::{Circuit, Output},
         error::Error,
+        fs::DomainSeparator,
         gadgets::prepare::generic_parse,
         gf2_word::GF2Word,
         party::Party,
         prover::Prover,
+        security::SecurityParams,
         verifier::Verifier,
     };
 
@@ -113,7 +118,7 @@ mod test_temp1 {
 
     impl Circuit<u32> for Temp1Circuit {
         fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
-            let input = generic_parse(input, self.num_of_mul_gates());
+            let input = generic_parse(input, self.num_of_mul_gates()).unwrap();
             let res = temp1(
                 input[0].value,
                 input[1].value,
@@ -130,9 +135,9 @@ mod test_temp1 {
             p2: &mut Party<u32>,
             p3: &mut Party<u32>,
         ) -> (Vec<GF2Word<u32>>, Vec<GF2Word<u32>>, Vec<GF2Word<u32>>) {
-            let words_p1 = generic_parse(&p1.view.input, self.party_input_len());
-            let words_p2 = generic_parse(&p2.view.input, self.party_input_len());
-            let words_p3 = generic_parse(&p3.view.input, self.party_input_len());
+            let words_p1 = generic_parse(&p1.view.input, self.party_input_len()).unwrap();
+            let words_p2 = generic_parse(&p2.view.input, self.party_input_len()).unwrap();
+            let words_p3 = generic_parse(&p3.view.input, self.party_input_len()).unwrap();
 
             let input_p1 = (words_p1[0], words_p1[1], words_p1[2], words_p1[3]);
             let input_p2 = (words_p2[0], words_p2[1], words_p2[2], words_p2[3]);
@@ -147,8 +152,8 @@ mod test_temp1 {
             p: &mut Party<u32>,
             p_next: &mut Party<u32>,
         ) -> Result<(Output<u32>, Output<u32>), Error> {
-            let words_p = generic_parse(&p.view.input, self.party_input_len());
-            let words_p_next = generic_parse(&p_next.view.input, self.party_input_len());
+            let words_p = generic_parse(&p.view.input, self.party_input_len())?;
+            let words_p_next = generic_parse(&p_next.view.input, self.party_input_len())?;
             let input_p = (words_p[0], words_p[1], words_p[2], words_p[3]);
             let input_p_next = (
                 words_p_next[0],
@@ -162,6 +167,10 @@ mod test_temp1 {
             Ok((vec![o1], vec![o2]))
         }
 
+        fn description(&self) -> Vec<u8> {
+            [b"sha256/temp1".as_slice(), &self.k.value.to_le_bytes()].concat()
+        }
+
         fn party_output_len(&self) -> usize {
             1
         }
@@ -178,7 +187,7 @@ mod test_temp1 {
     #[test]
     fn test_circuit() {
         let mut rng = thread_rng();
-        const SIGMA: usize = 80;
+        let params = SecurityParams::BITS_80;
 
         let input: Vec<u8> = [
             381321u32.to_le_bytes(),
@@ -196,11 +205,13 @@ mod test_temp1 {
 
         let output = circuit.compute(&input);
 
-        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove::<ThreadRng, SIGMA>(
-            &mut rng, &input, &circuit, &output,
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &input, &circuit, &output, &params, &dst,
         )
         .unwrap();
 
-        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output).unwrap();
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
     }
 }
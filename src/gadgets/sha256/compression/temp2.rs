@@ -1,12 +1,14 @@
 use crate::{
     error::Error,
-    gadgets::add_mod::{add_mod_verify, adder, mpc_add_mod},
+    gadgets::add_mod::{add_mod_verify, mpc_add_mod},
     gf2_word::GF2Word,
     party::Party,
 };
 
 /// temp2 := S0 + maj
 pub fn temp2(s0: u32, maj: u32) -> u32 {
+    use crate::gadgets::add_mod::adder;
+
     adder(s0, maj)
 }
 
@@ -30,7 +32,7 @@ pub fn mpc_temp2_verify(
 ) -> Result<(GF2Word<u32>, GF2Word<u32>), Error> {
     // output = s0 + maj
     let (output_p, output_p_next) =
-        add_mod_verify((s0_p, maj_p), (s0_p_next, maj_p_next), p, p_next);
+        add_mod_verify((s0_p, maj_p), (s0_p_next, maj_p_next), p, p_next)?;
 
     Ok((output_p, output_p_next))
 }
@@ -38,17 +40,19 @@ pub fn mpc_temp2_verify(
 #[cfg(test)]
 mod test_temp2 {
 
-    use rand::{rngs::ThreadRng, thread_rng};
+    use rand::thread_rng;
     use rand_chacha::ChaCha20Rng;
     use sha3::Keccak256;
 
     use crate::{
         circuit::{Circuit, Output},
         error::Error,
+        fs::DomainSeparator,
         gadgets::prepare::generic_parse,
         gf2_word::GF2Word,
         party::Party,
         prover::Prover,
+        security::SecurityParams,
         verifier::Verifier,
     };
 
@@ -58,7 +62,7 @@ mod test_temp2 {
 
     impl Circuit<u32> for Temp2Circuit {
         fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
-            let input = generic_parse(input, self.party_input_len());
+            let input = generic_parse(input, self.party_input_len()).unwrap();
             let res = temp2(input[0].value, input[1].value);
             vec![res.into()]
         }
@@ -69,9 +73,9 @@ mod test_temp2 {
             p2: &mut Party<u32>,
             p3: &mut Party<u32>,
         ) -> (Vec<GF2Word<u32>>, Vec<GF2Word<u32>>, Vec<GF2Word<u32>>) {
-            let p1_words = generic_parse(&p1.view.input, self.party_input_len());
-            let p2_words = generic_parse(&p2.view.input, self.party_input_len());
-            let p3_words = generic_parse(&p3.view.input, self.party_input_len());
+            let p1_words = generic_parse(&p1.view.input, self.party_input_len()).unwrap();
+            let p2_words = generic_parse(&p2.view.input, self.party_input_len()).unwrap();
+            let p3_words = generic_parse(&p3.view.input, self.party_input_len()).unwrap();
 
             let input_p1 = (p1_words[0], p1_words[1]);
             let input_p2 = (p2_words[0], p2_words[1]);
@@ -86,8 +90,8 @@ mod test_temp2 {
             p: &mut Party<u32>,
             p_next: &mut Party<u32>,
         ) -> Result<(Output<u32>, Output<u32>), Error> {
-            let p_words = generic_parse(&p.view.input, self.party_input_len());
-            let p_next_words = generic_parse(&p_next.view.input, self.party_input_len());
+            let p_words = generic_parse(&p.view.input, self.party_input_len())?;
+            let p_next_words = generic_parse(&p_next.view.input, self.party_input_len())?;
 
             let input_p = (p_words[0], p_words[1]);
             let input_p_next = (p_next_words[0], p_next_words[1]);
@@ -97,6 +101,10 @@ mod test_temp2 {
             Ok((vec![o1], vec![o2]))
         }
 
+        fn description(&self) -> Vec<u8> {
+            b"sha256/temp2".to_vec()
+        }
+
         fn party_output_len(&self) -> usize {
             1
         }
@@ -113,7 +121,7 @@ mod test_temp2 {
     #[test]
     fn test_circuit() {
         let mut rng = thread_rng();
-        const SIGMA: usize = 80;
+        let params = SecurityParams::BITS_80;
 
         let input: Vec<u8> = [381321u32.to_le_bytes(), 32131u32.to_le_bytes()]
             .into_iter()
@@ -124,11 +132,13 @@ mod test_temp2 {
 
         let output = circuit.compute(&input);
 
-        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove::<ThreadRng, SIGMA>(
-            &mut rng, &input, &circuit, &output,
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &input, &circuit, &output, &params, &dst,
         )
         .unwrap();
 
-        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output).unwrap();
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
     }
 }
@@ -1,19 +1,21 @@
 use crate::{
-    gadgets::add_mod::{add_mod_verify, adder, mpc_add_mod},
+    error::Error,
+    gadgets::add_mod::{add_mod_verify, mpc_add_mod},
     gf2_word::GF2Word,
     party::Party,
 };
 
-use super::{iv::init_iv, State};
+use super::State;
 
-pub fn digest(compression_output: &[GF2Word<u32>; 8]) -> Vec<GF2Word<u32>> {
-    let hs = init_iv().to_vec();
+/// Add the compression output to `state` natively.
+pub fn digest(compression_output: &[GF2Word<u32>; 8], state: &State) -> Vec<GF2Word<u32>> {
+    use crate::gadgets::add_mod::adder;
+
+    let hs = state.to_vec();
     hs.into_iter()
         .zip(compression_output.iter())
         .map(|(hs, &output)| adder(hs.value, output.value).into())
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap()
+        .collect()
 }
 
 pub fn mpc_update_state(
@@ -57,7 +59,7 @@ pub fn mpc_update_state_verify(
     state: &(State, State),
     p: &mut Party<u32>,
     p_next: &mut Party<u32>,
-) -> (State, State) {
+) -> Result<(State, State), Error> {
     let hs_p = state.0.to_vec();
     let hs_p_next = state.1.to_vec();
 
@@ -70,29 +72,31 @@ pub fn mpc_update_state_verify(
             (compression_output_p_next[i], hs_p_next[i]),
             p,
             p_next,
-        );
+        )?;
 
         output_p.push(o1);
         output_p_next.push(o2);
     }
 
-    (output_p.into(), output_p_next.into())
+    Ok((output_p.into(), output_p_next.into()))
 }
 
 #[cfg(test)]
 mod test_digest {
 
-    use rand::{rngs::ThreadRng, thread_rng};
+    use rand::thread_rng;
     use rand_chacha::ChaCha20Rng;
     use sha3::Keccak256;
 
     use crate::{
         circuit::{Circuit, Output},
         error::Error,
-        gadgets::prepare::generic_parse,
+        fs::DomainSeparator,
+        gadgets::{prepare::generic_parse, sha256::iv::init_iv},
         gf2_word::GF2Word,
         party::Party,
         prover::Prover,
+        security::SecurityParams,
         verifier::Verifier,
     };
 
@@ -102,8 +106,8 @@ mod test_digest {
 
     impl Circuit<u32> for DigestCircuit {
         fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
-            let input = generic_parse(input, self.party_input_len());
-            digest(&input.try_into().unwrap())
+            let input = generic_parse(input, self.party_input_len()).unwrap();
+            digest(&input.try_into().unwrap(), &init_iv().to_vec().into())
         }
 
         fn compute_23_decomposition(
@@ -112,9 +116,9 @@ mod test_digest {
             p2: &mut Party<u32>,
             p3: &mut Party<u32>,
         ) -> (Vec<GF2Word<u32>>, Vec<GF2Word<u32>>, Vec<GF2Word<u32>>) {
-            let p1_words = generic_parse(&p1.view.input, self.party_input_len());
-            let p2_words = generic_parse(&p2.view.input, self.party_input_len());
-            let p3_words = generic_parse(&p3.view.input, self.party_input_len());
+            let p1_words = generic_parse(&p1.view.input, self.party_input_len()).unwrap();
+            let p2_words = generic_parse(&p2.view.input, self.party_input_len()).unwrap();
+            let p3_words = generic_parse(&p3.view.input, self.party_input_len()).unwrap();
 
             let state_1 = init_iv();
             let state_2 = init_iv();
@@ -142,8 +146,8 @@ mod test_digest {
             p: &mut Party<u32>,
             p_next: &mut Party<u32>,
         ) -> Result<(Output<u32>, Output<u32>), Error> {
-            let p_words = generic_parse(&p.view.input, self.party_input_len());
-            let p_next_words = generic_parse(&p_next.view.input, self.party_input_len());
+            let p_words = generic_parse(&p.view.input, self.party_input_len())?;
+            let p_next_words = generic_parse(&p_next.view.input, self.party_input_len())?;
 
             let state_p = init_iv();
             let state_p_next = init_iv();
@@ -154,7 +158,7 @@ mod test_digest {
                 &(state_p.to_vec().into(), state_p_next.to_vec().into()),
                 p,
                 p_next,
-            );
+            )?;
 
             Ok((o1.to_vec(), o2.to_vec()))
         }
@@ -163,6 +167,10 @@ mod test_digest {
             8
         }
 
+        fn description(&self) -> Vec<u8> {
+            b"sha256/update_state".to_vec()
+        }
+
         fn party_output_len(&self) -> usize {
             8
         }
@@ -175,7 +183,7 @@ mod test_digest {
     #[test]
     fn test_circuit() {
         let mut rng = thread_rng();
-        const SIGMA: usize = 80;
+        let params = SecurityParams::BITS_80;
         let input: Vec<u8> = crate::gadgets::sha256::test_vectors::short::COMPRESSION_OUTPUT
             .iter()
             .flat_map(|v| v.to_le_bytes())
@@ -189,11 +197,13 @@ mod test_digest {
             assert_eq!(word.value, expected_word);
         }
 
-        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove::<ThreadRng, SIGMA>(
-            &mut rng, &input, &circuit, &output,
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &input, &circuit, &output, &params, &dst,
         )
         .unwrap();
 
-        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output).unwrap();
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
     }
 }
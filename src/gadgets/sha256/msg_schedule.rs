@@ -1,6 +1,7 @@
 use crate::{
+    error::Error,
     gadgets::{
-        add_mod::{add_mod_verify, adder, mpc_add_mod},
+        add_mod::{add_mod_verify, mpc_add_mod},
         Party,
     },
     gf2_word::{BitUtils, GF2Word},
@@ -22,7 +23,12 @@ fn s1(i: usize, w: &[GF2Word<u32>]) -> GF2Word<u32> {
     .into()
 }
 
-pub fn msg_schedule(input: &[GF2Word<u32>; 16]) -> [GF2Word<u32>; 64] {
+/// One party's message schedule: the 64 words `w[0..63]`.
+pub type MsgSchedule = [GF2Word<u32>; 64];
+
+pub fn msg_schedule(input: &[GF2Word<u32>; 16]) -> MsgSchedule {
+    use crate::gadgets::add_mod::adder;
+
     let mut w = input[..].to_vec();
 
     // extend words
@@ -51,7 +57,7 @@ pub fn mpc_msg_schedule(
     p1: &mut Party<u32>,
     p2: &mut Party<u32>,
     p3: &mut Party<u32>,
-) -> ([GF2Word<u32>; 64], [GF2Word<u32>; 64], [GF2Word<u32>; 64]) {
+) -> (MsgSchedule, MsgSchedule, MsgSchedule) {
     let mut w_1 = input_p1[..].to_vec();
     let mut w_2 = input_p2[..].to_vec();
     let mut w_3 = input_p3[..].to_vec();
@@ -102,7 +108,7 @@ pub fn mpc_msg_schedule_verify(
     input_p_next: &[GF2Word<u32>; 16],
     p: &mut Party<u32>,
     p_next: &mut Party<u32>,
-) -> ([GF2Word<u32>; 64], [GF2Word<u32>; 64]) {
+) -> Result<(MsgSchedule, MsgSchedule), Error> {
     let mut w = input_p[..].to_vec();
     let mut w_next = input_p_next[..].to_vec();
 
@@ -120,38 +126,40 @@ pub fn mpc_msg_schedule_verify(
             (w_next[i - 16], s0(i, &w_next)),
             p,
             p_next,
-        );
+        )?;
 
         let (rhs, rhs_next) = add_mod_verify(
             (w[i - 7], s1(i, &w)),
             (w_next[i - 7], s1(i, &w_next)),
             p,
             p_next,
-        );
+        )?;
 
-        let (o, o_next) = add_mod_verify((lhs, rhs), (lhs_next, rhs_next), p, p_next);
+        let (o, o_next) = add_mod_verify((lhs, rhs), (lhs_next, rhs_next), p, p_next)?;
 
         w.push(o);
         w_next.push(o_next);
     }
 
-    (w.try_into().unwrap(), w_next.try_into().unwrap())
+    Ok((w.try_into().unwrap(), w_next.try_into().unwrap()))
 }
 
 #[cfg(test)]
 mod test_msg_schedule {
 
-    use rand::{rngs::ThreadRng, thread_rng};
+    use rand::thread_rng;
     use rand_chacha::ChaCha20Rng;
     use sha3::Keccak256;
 
     use crate::{
         circuit::{Circuit, Output},
         error::Error,
+        fs::DomainSeparator,
         gadgets::prepare::generic_parse,
         gf2_word::GF2Word,
         party::Party,
         prover::Prover,
+        security::SecurityParams,
         verifier::Verifier,
     };
 
@@ -161,7 +169,7 @@ mod test_msg_schedule {
 
     impl Circuit<u32> for MsgScheduleCircuit {
         fn compute(&self, input: &[u8]) -> Vec<GF2Word<u32>> {
-            let words = generic_parse(input, self.party_input_len());
+            let words = generic_parse(input, self.party_input_len()).unwrap();
             let res = msg_schedule(&words.try_into().unwrap());
             res.to_vec()
         }
@@ -172,9 +180,9 @@ mod test_msg_schedule {
             p2: &mut Party<u32>,
             p3: &mut Party<u32>,
         ) -> (Vec<GF2Word<u32>>, Vec<GF2Word<u32>>, Vec<GF2Word<u32>>) {
-            let p1_words = generic_parse(&p1.view.input, self.party_input_len());
-            let p2_words = generic_parse(&p2.view.input, self.party_input_len());
-            let p3_words = generic_parse(&p3.view.input, self.party_input_len());
+            let p1_words = generic_parse(&p1.view.input, self.party_input_len()).unwrap();
+            let p2_words = generic_parse(&p2.view.input, self.party_input_len()).unwrap();
+            let p3_words = generic_parse(&p3.view.input, self.party_input_len()).unwrap();
 
             let (o1, o2, o3) = mpc_msg_schedule(
                 &p1_words.try_into().unwrap(),
@@ -192,15 +200,15 @@ mod test_msg_schedule {
             p: &mut Party<u32>,
             p_next: &mut Party<u32>,
         ) -> Result<(Output<u32>, Output<u32>), Error> {
-            let p_words = generic_parse(&p.view.input, self.party_input_len());
-            let p_next_words = generic_parse(&p_next.view.input, self.party_input_len());
+            let p_words = generic_parse(&p.view.input, self.party_input_len())?;
+            let p_next_words = generic_parse(&p_next.view.input, self.party_input_len())?;
 
             let (o1, o2) = mpc_msg_schedule_verify(
                 &p_words.try_into().unwrap(),
                 &p_next_words.try_into().unwrap(),
                 p,
                 p_next,
-            );
+            )?;
 
             Ok((o1.to_vec(), o2.to_vec()))
         }
@@ -209,6 +217,10 @@ mod test_msg_schedule {
             16
         }
 
+        fn description(&self) -> Vec<u8> {
+            b"sha256/msg_schedule".to_vec()
+        }
+
         fn party_output_len(&self) -> usize {
             64
         }
@@ -221,7 +233,7 @@ mod test_msg_schedule {
     #[test]
     fn test_circuit() {
         let mut rng = thread_rng();
-        const SIGMA: usize = 80;
+        let params = SecurityParams::BITS_80;
         let input: Vec<u8> = crate::gadgets::sha256::test_vectors::short::TEST_INPUT
             .iter()
             .flat_map(|&vi| vi.to_le_bytes())
@@ -235,11 +247,13 @@ mod test_msg_schedule {
             assert_eq!(word.value, expected_word);
         }
 
-        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove::<ThreadRng, SIGMA>(
-            &mut rng, &input, &circuit, &output,
+        let dst = DomainSeparator::new::<ChaCha20Rng, Keccak256>(b"zkboo/tests", &params);
+        let proof = Prover::<u32, ChaCha20Rng, Keccak256>::prove(
+            &mut rng, &input, &circuit, &output, &params, &dst,
         )
         .unwrap();
 
-        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output).unwrap();
+        Verifier::<u32, ChaCha20Rng, Keccak256>::verify(&proof, &circuit, &output, &params, &dst)
+            .unwrap();
     }
 }
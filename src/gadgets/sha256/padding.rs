@@ -1,16 +1,24 @@
 use crate::gf2_word::GF2Word;
 
 pub fn padding(input: &[u8]) -> Vec<GF2Word<u32>> {
+    padding_after(input, 0)
+}
+
+/// Pad the part of a message that follows `compressed_len` bytes already
+/// compressed into a midstate, which must be a whole number of blocks.
+pub fn padding_after(input: &[u8], compressed_len: usize) -> Vec<GF2Word<u32>> {
+    assert!(compressed_len.is_multiple_of(64));
+
     let mut msg = input.to_vec();
-    let length_u64 = (8 * input.len()) as u64; // msg len in bits
+    let length_u64 = (8 * (compressed_len + input.len())) as u64; // msg len in bits
     msg.push(0x80); // append one 1 bit and seven 0 bits
 
-    while (msg.len() * 8 + 64) % 512 != 0 {
+    while !(msg.len() * 8 + 64).is_multiple_of(512) {
         msg.push(0x00);
     }
     msg.extend_from_slice(&length_u64.to_be_bytes());
 
-    assert!(msg.len() * 8 % 512 == 0);
+    assert!((msg.len() * 8).is_multiple_of(512));
     msg.chunks(4)
         .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()).into())
         .collect()
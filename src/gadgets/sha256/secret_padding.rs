@@ -0,0 +1,325 @@
+use crate::{
+    error::Error,
+    gadgets::{
+        add_mod::{add_mod_verify_k, mpc_add_mod_k},
+        mpc_and, mpc_and_verify,
+    },
+    gf2_word::GF2Word,
+    party::Party,
+};
+
+use super::State;
+
+const ZEROS: u32 = 0;
+const ONES: u32 = u32::MAX;
+
+/// One party's share of a message padded from a secret length.
+#[derive(Debug, Clone)]
+pub struct SecretPadding {
+    /// Words of the padded message, over all blocks the buffer may need
+    pub words: Vec<GF2Word<u32>>,
+    /// Per block, all ones if the length block of the message is in it
+    pub selectors: Vec<GF2Word<u32>>,
+    /// All ones if the length fits in the buffer
+    pub in_range: GF2Word<u32>,
+}
+
+/// Public constant `k` such that the top bit of `len + k` is `[i < len]`, for
+/// lengths and positions below 2^31.
+fn less_than_k(i: usize) -> GF2Word<u32> {
+    ((1u32 << 31) - 1 - i as u32).into()
+}
+
+/// Spread the top bit of a share to all its bits, which commutes with xor.
+fn top_bit_mask(word: GF2Word<u32>) -> GF2Word<u32> {
+    0u32.wrapping_sub(word.value >> 31).into()
+}
+
+/// Share of `[i < len]` from the shares of `[j < len]` for `j <= max_len`.
+/// Positions before the message are below any length and positions past the
+/// buffer are only below lengths which are out of range anyway. Constants are
+/// the same in all shares, as an odd number of them xor to the constant.
+fn less_than(masks: &[GF2Word<u32>], i: isize) -> GF2Word<u32> {
+    if i < 0 {
+        ONES.into()
+    } else {
+        masks.get(i as usize).copied().unwrap_or(ZEROS.into())
+    }
+}
+
+/// Spread byte masks over the 4 bytes of word `w`, big-endian.
+fn word_mask(w: usize, byte_mask: impl Fn(isize) -> GF2Word<u32>) -> GF2Word<u32> {
+    (0..4).fold(ZEROS.into(), |mask, j| {
+        let byte = byte_mask((4 * w + j) as isize).value & (0xff00_0000 >> (8 * j));
+        mask ^ byte.into()
+    })
+}
+
+/// Word `w` of a share of the buffer, past which the message is zero.
+fn buffer_word(buffer: &[u8], w: usize) -> GF2Word<u32> {
+    let mut bytes = [0u8; 4];
+    for (j, byte) in bytes.iter_mut().enumerate() {
+        *byte = buffer.get(4 * w + j).copied().unwrap_or(0);
+    }
+    u32::from_be_bytes(bytes).into()
+}
+
+/// Everything linear in one party's share once `[i < len]` is known: the
+/// masks of the message bytes, the `0x80` byte, the length words and
+/// block selectors.
+struct LinearPadding {
+    message_masks: Vec<GF2Word<u32>>,
+    marker: Vec<GF2Word<u32>>,
+    selectors: Vec<GF2Word<u32>>,
+    in_range: GF2Word<u32>,
+}
+
+impl LinearPadding {
+    fn new(less_than_masks: &[GF2Word<u32>], max_len: usize, num_of_blocks: usize) -> Self {
+        let lt = |i: isize| less_than(less_than_masks, i);
+        let eq = |i: isize| lt(i - 1) ^ lt(i);
+
+        let num_of_words = 16 * num_of_blocks;
+        let message_masks = (0..max_len.div_ceil(4)).map(|w| word_mask(w, lt)).collect();
+        let marker = (0..num_of_words)
+            .map(|w| word_mask(w, eq) & 0x8080_8080.into())
+            .collect();
+
+        // the length goes in the block holding byte `len + 8`
+        let selectors = (0..num_of_blocks as isize)
+            .map(|j| lt(64 * j - 9) ^ lt(64 * j + 55))
+            .collect();
+        let in_range = lt(max_len as isize) ^ ONES.into();
+
+        Self {
+            message_masks,
+            marker,
+            selectors,
+            in_range,
+        }
+    }
+}
+
+/// Pad the message held in the first `len` bytes of `buffer`, where `len` is
+/// secret and at most the public length of the buffer. Lengths must be below
+/// 2^16 so that the bit length fits in the last word of a block.
+pub fn mpc_secret_padding(
+    (buffer_p1, buffer_p2, buffer_p3): (&[u8], &[u8], &[u8]),
+    (len_p1, len_p2, len_p3): (GF2Word<u32>, GF2Word<u32>, GF2Word<u32>),
+    num_of_blocks: usize,
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (SecretPadding, SecretPadding, SecretPadding) {
+    let max_len = buffer_p1.len();
+
+    let mut lt_p1 = Vec::with_capacity(max_len + 1);
+    let mut lt_p2 = Vec::with_capacity(max_len + 1);
+    let mut lt_p3 = Vec::with_capacity(max_len + 1);
+    for i in 0..=max_len {
+        let (o1, o2, o3) = mpc_add_mod_k(len_p1, len_p2, len_p3, less_than_k(i), p1, p2, p3);
+        lt_p1.push(top_bit_mask(o1));
+        lt_p2.push(top_bit_mask(o2));
+        lt_p3.push(top_bit_mask(o3));
+    }
+
+    let linear_p1 = LinearPadding::new(&lt_p1, max_len, num_of_blocks);
+    let linear_p2 = LinearPadding::new(&lt_p2, max_len, num_of_blocks);
+    let linear_p3 = LinearPadding::new(&lt_p3, max_len, num_of_blocks);
+
+    let mut words_p1 = linear_p1.marker.clone();
+    let mut words_p2 = linear_p2.marker.clone();
+    let mut words_p3 = linear_p3.marker.clone();
+
+    for w in 0..max_len.div_ceil(4) {
+        let (o1, o2, o3) = mpc_and(
+            (buffer_word(buffer_p1, w), linear_p1.message_masks[w]),
+            (buffer_word(buffer_p2, w), linear_p2.message_masks[w]),
+            (buffer_word(buffer_p3, w), linear_p3.message_masks[w]),
+            p1,
+            p2,
+            p3,
+        );
+        words_p1[w] = words_p1[w] ^ o1;
+        words_p2[w] = words_p2[w] ^ o2;
+        words_p3[w] = words_p3[w] ^ o3;
+    }
+
+    // the bit length is below 2^19, so only the last word of a block is set
+    for j in 0..num_of_blocks {
+        let (o1, o2, o3) = mpc_and(
+            (linear_p1.selectors[j], (len_p1.value << 3).into()),
+            (linear_p2.selectors[j], (len_p2.value << 3).into()),
+            (linear_p3.selectors[j], (len_p3.value << 3).into()),
+            p1,
+            p2,
+            p3,
+        );
+        words_p1[16 * j + 15] = words_p1[16 * j + 15] ^ o1;
+        words_p2[16 * j + 15] = words_p2[16 * j + 15] ^ o2;
+        words_p3[16 * j + 15] = words_p3[16 * j + 15] ^ o3;
+    }
+
+    (
+        SecretPadding {
+            words: words_p1,
+            selectors: linear_p1.selectors,
+            in_range: linear_p1.in_range,
+        },
+        SecretPadding {
+            words: words_p2,
+            selectors: linear_p2.selectors,
+            in_range: linear_p2.in_range,
+        },
+        SecretPadding {
+            words: words_p3,
+            selectors: linear_p3.selectors,
+            in_range: linear_p3.in_range,
+        },
+    )
+}
+
+pub fn mpc_secret_padding_verify(
+    (buffer_p, buffer_p_next): (&[u8], &[u8]),
+    (len_p, len_p_next): (GF2Word<u32>, GF2Word<u32>),
+    num_of_blocks: usize,
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(SecretPadding, SecretPadding), Error> {
+    let max_len = buffer_p.len();
+
+    let mut lt_p = Vec::with_capacity(max_len + 1);
+    let mut lt_p_next = Vec::with_capacity(max_len + 1);
+    for i in 0..=max_len {
+        let (o, o_next) = add_mod_verify_k(len_p, len_p_next, less_than_k(i), p, p_next)?;
+        lt_p.push(top_bit_mask(o));
+        lt_p_next.push(top_bit_mask(o_next));
+    }
+
+    let linear_p = LinearPadding::new(&lt_p, max_len, num_of_blocks);
+    let linear_p_next = LinearPadding::new(&lt_p_next, max_len, num_of_blocks);
+
+    let mut words_p = linear_p.marker.clone();
+    let mut words_p_next = linear_p_next.marker.clone();
+
+    for w in 0..max_len.div_ceil(4) {
+        let (o, o_next) = mpc_and_verify(
+            (buffer_word(buffer_p, w), linear_p.message_masks[w]),
+            (
+                buffer_word(buffer_p_next, w),
+                linear_p_next.message_masks[w],
+            ),
+            p,
+            p_next,
+        )?;
+        words_p[w] = words_p[w] ^ o;
+        words_p_next[w] = words_p_next[w] ^ o_next;
+    }
+
+    for j in 0..num_of_blocks {
+        let (o, o_next) = mpc_and_verify(
+            (linear_p.selectors[j], (len_p.value << 3).into()),
+            (linear_p_next.selectors[j], (len_p_next.value << 3).into()),
+            p,
+            p_next,
+        )?;
+        words_p[16 * j + 15] = words_p[16 * j + 15] ^ o;
+        words_p_next[16 * j + 15] = words_p_next[16 * j + 15] ^ o_next;
+    }
+
+    Ok((
+        SecretPadding {
+            words: words_p,
+            selectors: linear_p.selectors,
+            in_range: linear_p.in_range,
+        },
+        SecretPadding {
+            words: words_p_next,
+            selectors: linear_p_next.selectors,
+            in_range: linear_p_next.in_range,
+        },
+    ))
+}
+
+/// Select the state after the block holding the length, as the xor of every
+/// state masked by its selector, and zero it if the length is out of range.
+pub fn mpc_select_state(
+    states: &[(State, State, State)],
+    padding: (&SecretPadding, &SecretPadding, &SecretPadding),
+    p1: &mut Party<u32>,
+    p2: &mut Party<u32>,
+    p3: &mut Party<u32>,
+) -> (State, State, State) {
+    let mut selected_p1 = vec![GF2Word::from(ZEROS); 8];
+    let mut selected_p2 = vec![GF2Word::from(ZEROS); 8];
+    let mut selected_p3 = vec![GF2Word::from(ZEROS); 8];
+
+    for (j, (state_p1, state_p2, state_p3)) in states.iter().enumerate() {
+        let iter_words = state_p1
+            .to_vec()
+            .into_iter()
+            .zip(state_p2.to_vec())
+            .zip(state_p3.to_vec());
+        for (i, ((h_p1, h_p2), h_p3)) in iter_words.enumerate() {
+            let (o1, o2, o3) = mpc_and(
+                (h_p1, padding.0.selectors[j]),
+                (h_p2, padding.1.selectors[j]),
+                (h_p3, padding.2.selectors[j]),
+                p1,
+                p2,
+                p3,
+            );
+            selected_p1[i] = selected_p1[i] ^ o1;
+            selected_p2[i] = selected_p2[i] ^ o2;
+            selected_p3[i] = selected_p3[i] ^ o3;
+        }
+    }
+
+    for i in 0..8 {
+        (selected_p1[i], selected_p2[i], selected_p3[i]) = mpc_and(
+            (selected_p1[i], padding.0.in_range),
+            (selected_p2[i], padding.1.in_range),
+            (selected_p3[i], padding.2.in_range),
+            p1,
+            p2,
+            p3,
+        );
+    }
+
+    (selected_p1.into(), selected_p2.into(), selected_p3.into())
+}
+
+pub fn mpc_select_state_verify(
+    states: &[(State, State)],
+    padding: (&SecretPadding, &SecretPadding),
+    p: &mut Party<u32>,
+    p_next: &mut Party<u32>,
+) -> Result<(State, State), Error> {
+    let mut selected_p = vec![GF2Word::from(ZEROS); 8];
+    let mut selected_p_next = vec![GF2Word::from(ZEROS); 8];
+
+    for (j, (state_p, state_p_next)) in states.iter().enumerate() {
+        let iter_words = state_p.to_vec().into_iter().zip(state_p_next.to_vec());
+        for (i, (h_p, h_p_next)) in iter_words.enumerate() {
+            let (o, o_next) = mpc_and_verify(
+                (h_p, padding.0.selectors[j]),
+                (h_p_next, padding.1.selectors[j]),
+                p,
+                p_next,
+            )?;
+            selected_p[i] = selected_p[i] ^ o;
+            selected_p_next[i] = selected_p_next[i] ^ o_next;
+        }
+    }
+
+    for i in 0..8 {
+        (selected_p[i], selected_p_next[i]) = mpc_and_verify(
+            (selected_p[i], padding.0.in_range),
+            (selected_p_next[i], padding.1.in_range),
+            p,
+            p_next,
+        )?;
+    }
+
+    Ok((selected_p.into(), selected_p_next.into()))
+}
@@ -115,3 +115,7 @@ pub const MSG_SCHEDULE_TEST_OUTPUT: [[u32; 64]; 2] = [
 pub const DIGEST_OUTPUT: [u32; 8] = [
     0xcf5b16a7, 0x78af8380, 0x036ce59e, 0x7b049237, 0x0b249b11, 0xe8f07a51, 0xafac4503, 0x7afee9d1,
 ];
+
+pub const DIGEST_OUTPUT_224: [u32; 7] = [
+    0xc97ca9a5, 0x59850ce9, 0x7a04a96d, 0xef6d99a9, 0xe0e0e2ab, 0x14e6b8df, 0x265fc0b3,
+];
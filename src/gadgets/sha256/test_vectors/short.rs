@@ -99,3 +99,7 @@ pub const DIGEST_OUTPUT: [u32; 8] = [
     0b10110100000100001111111101100001,
     0b11110010000000000001010110101101,
 ];
+
+pub const DIGEST_OUTPUT_224: [u32; 7] = [
+    0x23097d22, 0x3405d822, 0x8642a477, 0xbda255b3, 0x2aadbce4, 0xbda0b3f7, 0xe36c9da7,
+];
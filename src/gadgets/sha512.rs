@@ -0,0 +1,477 @@
+mod iv;
+mod padding;
+
+#[cfg(test)]
+pub(crate) mod test_vectors;
+
+use crate::{
+    error::Error,
+    gadgets::{
+        add_mod::{add_mod_verify, add_mod_verify_k, mpc_add_mod, mpc_add_mod_k},
+        mpc_and, mpc_and_verify,
+    },
+    gf2_word::{BitUtils, GF2Word},
+    party::Party,
+};
+
+use self::iv::K;
+pub use iv::{init_iv, init_iv_384, init_iv_512_256};
+pub use padding::padding;
+
+/// Chaining value of SHA-512 and its truncated variants, which only differ
+/// in their initial value and in how many words of the digest they keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    h: [GF2Word<u64>; 8],
+}
+
+impl State {
+    pub fn from_words(words: [u64; 8]) -> Self {
+        Self {
+            h: words.map(GF2Word::from),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<GF2Word<u64>> {
+        self.h.to_vec()
+    }
+
+    /// Big-endian encoding of the state, which is the digest once all blocks
+    /// are compressed.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        for (chunk, word) in bytes.chunks_mut(8).zip(self.h.iter()) {
+            chunk.copy_from_slice(&word.value.to_be_bytes());
+        }
+        bytes
+    }
+}
+
+impl From<Vec<GF2Word<u64>>> for State {
+    fn from(value: Vec<GF2Word<u64>>) -> Self {
+        Self {
+            h: value.try_into().unwrap(),
+        }
+    }
+}
+
+/// One party's message schedule: the 80 words `w[0..79]`.
+pub type MsgSchedule = [GF2Word<u64>; 80];
+
+/// s0 := (w[i-15] rightrotate 1) xor (w[i-15] rightrotate 8) xor (w[i-15] rightshift 7)
+fn s0(i: usize, w: &[GF2Word<u64>]) -> GF2Word<u64> {
+    (w[i - 15].value.right_rotate(1)
+        ^ w[i - 15].value.right_rotate(8)
+        ^ w[i - 15].value.right_shift(7))
+    .into()
+}
+
+/// s1 := (w[i-2] rightrotate 19) xor (w[i-2] rightrotate 61) xor (w[i-2] rightshift 6)
+fn s1(i: usize, w: &[GF2Word<u64>]) -> GF2Word<u64> {
+    (w[i - 2].value.right_rotate(19)
+        ^ w[i - 2].value.right_rotate(61)
+        ^ w[i - 2].value.right_shift(6))
+    .into()
+}
+
+/// S0 := (a rightrotate 28) xor (a rightrotate 34) xor (a rightrotate 39)
+fn sigma_0(a: GF2Word<u64>) -> GF2Word<u64> {
+    (a.value.right_rotate(28) ^ a.value.right_rotate(34) ^ a.value.right_rotate(39)).into()
+}
+
+/// S1 := (e rightrotate 14) xor (e rightrotate 18) xor (e rightrotate 41)
+fn sigma_1(e: GF2Word<u64>) -> GF2Word<u64> {
+    (e.value.right_rotate(14) ^ e.value.right_rotate(18) ^ e.value.right_rotate(41)).into()
+}
+
+/// Natively compress one block of 16 words into `state`, as `mpc_sha512_block`
+/// does on shares.
+pub fn sha512_block(input: &[GF2Word<u64>; 16], state: &State) -> State {
+    let mut w = input.to_vec();
+    for i in 16..80 {
+        let word = w[i - 16]
+            .value
+            .wrapping_add(s0(i, &w).value)
+            .wrapping_add(w[i - 7].value)
+            .wrapping_add(s1(i, &w).value);
+        w.push(word.into());
+    }
+
+    let mut v = state.h;
+    for i in 0..80 {
+        let ch = (v[4] & (v[5] ^ v[6])) ^ v[6];
+        let temp1 = v[7]
+            .value
+            .wrapping_add(sigma_1(v[4]).value)
+            .wrapping_add(ch.value)
+            .wrapping_add(K[i])
+            .wrapping_add(w[i].value);
+        let maj = ((v[0] ^ v[1]) & (v[0] ^ v[2])) ^ v[0];
+        let temp2 = sigma_0(v[0]).value.wrapping_add(maj.value);
+
+        v.rotate_right(1);
+        v[4] = v[4].value.wrapping_add(temp1).into();
+        v[0] = temp1.wrapping_add(temp2).into();
+    }
+
+    let mut h = state.h;
+    for (h, v) in h.iter_mut().zip(v) {
+        *h = h.value.wrapping_add(v.value).into();
+    }
+    State { h }
+}
+
+/// Extend the first 16 words into the remaining 64 words w[16..79] of the message schedule array
+pub fn mpc_msg_schedule(
+    input_p1: &[GF2Word<u64>; 16],
+    input_p2: &[GF2Word<u64>; 16],
+    input_p3: &[GF2Word<u64>; 16],
+    p1: &mut Party<u64>,
+    p2: &mut Party<u64>,
+    p3: &mut Party<u64>,
+) -> (MsgSchedule, MsgSchedule, MsgSchedule) {
+    let mut w_1 = input_p1[..].to_vec();
+    let mut w_2 = input_p2[..].to_vec();
+    let mut w_3 = input_p3[..].to_vec();
+
+    for i in 16..80 {
+        // w[i] = w[i - 16] + s0 + w[i - 7] + s1, with 3 additions
+        let (lhs_1, lhs_2, lhs_3) = mpc_add_mod(
+            (w_1[i - 16], s0(i, &w_1)),
+            (w_2[i - 16], s0(i, &w_2)),
+            (w_3[i - 16], s0(i, &w_3)),
+            p1,
+            p2,
+            p3,
+        );
+
+        let (rhs_1, rhs_2, rhs_3) = mpc_add_mod(
+            (w_1[i - 7], s1(i, &w_1)),
+            (w_2[i - 7], s1(i, &w_2)),
+            (w_3[i - 7], s1(i, &w_3)),
+            p1,
+            p2,
+            p3,
+        );
+
+        let (o1, o2, o3) = mpc_add_mod((lhs_1, rhs_1), (lhs_2, rhs_2), (lhs_3, rhs_3), p1, p2, p3);
+
+        w_1.push(o1);
+        w_2.push(o2);
+        w_3.push(o3);
+    }
+
+    (
+        w_1.try_into().unwrap(),
+        w_2.try_into().unwrap(),
+        w_3.try_into().unwrap(),
+    )
+}
+
+pub fn mpc_msg_schedule_verify(
+    input_p: &[GF2Word<u64>; 16],
+    input_p_next: &[GF2Word<u64>; 16],
+    p: &mut Party<u64>,
+    p_next: &mut Party<u64>,
+) -> Result<(MsgSchedule, MsgSchedule), Error> {
+    let mut w = input_p[..].to_vec();
+    let mut w_next = input_p_next[..].to_vec();
+
+    for i in 16..80 {
+        // w[i] = w[i - 16] + s0 + w[i - 7] + s1, with 3 additions
+        let (lhs, lhs_next) = add_mod_verify(
+            (w[i - 16], s0(i, &w)),
+            (w_next[i - 16], s0(i, &w_next)),
+            p,
+            p_next,
+        )?;
+
+        let (rhs, rhs_next) = add_mod_verify(
+            (w[i - 7], s1(i, &w)),
+            (w_next[i - 7], s1(i, &w_next)),
+            p,
+            p_next,
+        )?;
+
+        let (o, o_next) = add_mod_verify((lhs, rhs), (lhs_next, rhs_next), p, p_next)?;
+
+        w.push(o);
+        w_next.push(o_next);
+    }
+
+    Ok((w.try_into().unwrap(), w_next.try_into().unwrap()))
+}
+
+/// Run the 80 rounds of the compression function on shares of the working
+/// variables `[a, b, c, d, e, f, g, h]`, with 9 mul gates per round.
+pub fn mpc_compression(
+    w_p1: &MsgSchedule,
+    w_p2: &MsgSchedule,
+    w_p3: &MsgSchedule,
+    state: &(State, State, State),
+    p1: &mut Party<u64>,
+    p2: &mut Party<u64>,
+    p3: &mut Party<u64>,
+) -> (State, State, State) {
+    let mut v1 = state.0.h;
+    let mut v2 = state.1.h;
+    let mut v3 = state.2.h;
+
+    for i in 0..80 {
+        // ch := (e and f) xor ((not e) and g) = e and (f xor g) xor g
+        let (ch_1, ch_2, ch_3) = {
+            let (o1, o2, o3) = mpc_and(
+                (v1[4], v1[5] ^ v1[6]),
+                (v2[4], v2[5] ^ v2[6]),
+                (v3[4], v3[5] ^ v3[6]),
+                p1,
+                p2,
+                p3,
+            );
+            (o1 ^ v1[6], o2 ^ v2[6], o3 ^ v3[6])
+        };
+
+        // temp1 := h + S1 + ch + K[i] + w[i]
+        let (temp1_1, temp1_2, temp1_3) = {
+            let (o1, o2, o3) = mpc_add_mod(
+                (v1[7], sigma_1(v1[4])),
+                (v2[7], sigma_1(v2[4])),
+                (v3[7], sigma_1(v3[4])),
+                p1,
+                p2,
+                p3,
+            );
+            let (o1, o2, o3) = mpc_add_mod((o1, ch_1), (o2, ch_2), (o3, ch_3), p1, p2, p3);
+            let (o1, o2, o3) = mpc_add_mod_k(o1, o2, o3, K[i].into(), p1, p2, p3);
+            mpc_add_mod((o1, w_p1[i]), (o2, w_p2[i]), (o3, w_p3[i]), p1, p2, p3)
+        };
+
+        // maj := (a and b) xor (a and c) xor (b and c) = (a xor b) and (a xor c) xor a
+        let (maj_1, maj_2, maj_3) = {
+            let (o1, o2, o3) = mpc_and(
+                (v1[0] ^ v1[1], v1[0] ^ v1[2]),
+                (v2[0] ^ v2[1], v2[0] ^ v2[2]),
+                (v3[0] ^ v3[1], v3[0] ^ v3[2]),
+                p1,
+                p2,
+                p3,
+            );
+            (o1 ^ v1[0], o2 ^ v2[0], o3 ^ v3[0])
+        };
+
+        // temp2 := S0 + maj
+        let (temp2_1, temp2_2, temp2_3) = mpc_add_mod(
+            (sigma_0(v1[0]), maj_1),
+            (sigma_0(v2[0]), maj_2),
+            (sigma_0(v3[0]), maj_3),
+            p1,
+            p2,
+            p3,
+        );
+
+        // h := g, g := f, f := e, d := c, c := b, b := a
+        v1.rotate_right(1);
+        v2.rotate_right(1);
+        v3.rotate_right(1);
+
+        // e := d + temp1
+        (v1[4], v2[4], v3[4]) = mpc_add_mod(
+            (v1[4], temp1_1),
+            (v2[4], temp1_2),
+            (v3[4], temp1_3),
+            p1,
+            p2,
+            p3,
+        );
+
+        // a := temp1 + temp2
+        (v1[0], v2[0], v3[0]) = mpc_add_mod(
+            (temp1_1, temp2_1),
+            (temp1_2, temp2_2),
+            (temp1_3, temp2_3),
+            p1,
+            p2,
+            p3,
+        );
+    }
+
+    (State { h: v1 }, State { h: v2 }, State { h: v3 })
+}
+
+pub fn mpc_compression_verify(
+    w_p: &MsgSchedule,
+    w_p_next: &MsgSchedule,
+    state: &(State, State),
+    p: &mut Party<u64>,
+    p_next: &mut Party<u64>,
+) -> Result<(State, State), Error> {
+    let mut v = state.0.h;
+    let mut v_next = state.1.h;
+
+    for i in 0..80 {
+        // ch := e and (f xor g) xor g
+        let (ch, ch_next) = {
+            let (o, o_next) = mpc_and_verify(
+                (v[4], v[5] ^ v[6]),
+                (v_next[4], v_next[5] ^ v_next[6]),
+                p,
+                p_next,
+            )?;
+            (o ^ v[6], o_next ^ v_next[6])
+        };
+
+        // temp1 := h + S1 + ch + K[i] + w[i]
+        let (temp1, temp1_next) = {
+            let (o, o_next) = add_mod_verify(
+                (v[7], sigma_1(v[4])),
+                (v_next[7], sigma_1(v_next[4])),
+                p,
+                p_next,
+            )?;
+            let (o, o_next) = add_mod_verify((o, ch), (o_next, ch_next), p, p_next)?;
+            let (o, o_next) = add_mod_verify_k(o, o_next, K[i].into(), p, p_next)?;
+            add_mod_verify((o, w_p[i]), (o_next, w_p_next[i]), p, p_next)?
+        };
+
+        // maj := (a xor b) and (a xor c) xor a
+        let (maj, maj_next) = {
+            let (o, o_next) = mpc_and_verify(
+                (v[0] ^ v[1], v[0] ^ v[2]),
+                (v_next[0] ^ v_next[1], v_next[0] ^ v_next[2]),
+                p,
+                p_next,
+            )?;
+            (o ^ v[0], o_next ^ v_next[0])
+        };
+
+        // temp2 := S0 + maj
+        let (temp2, temp2_next) = add_mod_verify(
+            (sigma_0(v[0]), maj),
+            (sigma_0(v_next[0]), maj_next),
+            p,
+            p_next,
+        )?;
+
+        v.rotate_right(1);
+        v_next.rotate_right(1);
+
+        // e := d + temp1
+        (v[4], v_next[4]) = add_mod_verify((v[4], temp1), (v_next[4], temp1_next), p, p_next)?;
+
+        // a := temp1 + temp2
+        (v[0], v_next[0]) = add_mod_verify((temp1, temp2), (temp1_next, temp2_next), p, p_next)?;
+    }
+
+    Ok((State { h: v }, State { h: v_next }))
+}
+
+pub fn mpc_sha512_block(
+    input_p1: &[GF2Word<u64>; 16],
+    input_p2: &[GF2Word<u64>; 16],
+    input_p3: &[GF2Word<u64>; 16],
+    state: &(State, State, State),
+    p1: &mut Party<u64>,
+    p2: &mut Party<u64>,
+    p3: &mut Party<u64>,
+) -> (State, State, State) {
+    let (w_1, w_2, w_3) = mpc_msg_schedule(input_p1, input_p2, input_p3, p1, p2, p3);
+    let (v1, v2, v3) = mpc_compression(&w_1, &w_2, &w_3, state, p1, p2, p3);
+
+    // add the compressed working variables to the state
+    let mut h1 = state.0.h;
+    let mut h2 = state.1.h;
+    let mut h3 = state.2.h;
+    for i in 0..8 {
+        (h1[i], h2[i], h3[i]) = mpc_add_mod(
+            (v1.h[i], h1[i]),
+            (v2.h[i], h2[i]),
+            (v3.h[i], h3[i]),
+            p1,
+            p2,
+            p3,
+        );
+    }
+
+    (State { h: h1 }, State { h: h2 }, State { h: h3 })
+}
+
+pub fn mpc_sha512_block_verify(
+    input_p: &[GF2Word<u64>; 16],
+    input_p_next: &[GF2Word<u64>; 16],
+    state: &(State, State),
+    p: &mut Party<u64>,
+    p_next: &mut Party<u64>,
+) -> Result<(State, State), Error> {
+    let (w, w_next) = mpc_msg_schedule_verify(input_p, input_p_next, p, p_next)?;
+    let (v, v_next) = mpc_compression_verify(&w, &w_next, state, p, p_next)?;
+
+    let mut h = state.0.h;
+    let mut h_next = state.1.h;
+    for i in 0..8 {
+        (h[i], h_next[i]) = add_mod_verify((v.h[i], h[i]), (v_next.h[i], h_next[i]), p, p_next)?;
+    }
+
+    Ok((State { h }, State { h: h_next }))
+}
+
+#[cfg(test)]
+mod test_sha512 {
+    use super::{
+        init_iv, mpc_sha512_block, mpc_sha512_block_verify, padding, sha512_block, test_vectors,
+    };
+    use crate::{
+        circuits::sha512::MUL_GATES_PER_BLOCK,
+        gadgets::{
+            mpc_public, mpc_public_verify, prepare::generic_parse, test_utils::check_shares,
+        },
+        gf2_word::GF2Word,
+    };
+
+    fn words(bytes: &[u8]) -> [GF2Word<u64>; 16] {
+        generic_parse(bytes, 16).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_sha512_block() {
+        let padded_input = padding(test_vectors::SHORT_INPUT);
+        let state = sha512_block(&padded_input.try_into().unwrap(), &init_iv());
+
+        for (word, &expected_word) in state.to_vec().iter().zip(test_vectors::SHORT_SHA512.iter()) {
+            assert_eq!(word.value, expected_word);
+        }
+    }
+
+    #[test]
+    fn test_mpc_sha512_block() {
+        let input: Vec<u8> = (0..128).collect();
+        let state = init_iv();
+
+        check_shares(
+            &input,
+            MUL_GATES_PER_BLOCK,
+            |input| sha512_block(&words(input), &state).to_vec(),
+            |p1, p2, p3| {
+                let (s1, s2, s3) = mpc_sha512_block(
+                    &words(&p1.view.input),
+                    &words(&p2.view.input),
+                    &words(&p3.view.input),
+                    &mpc_public(state),
+                    p1,
+                    p2,
+                    p3,
+                );
+                (s1.to_vec(), s2.to_vec(), s3.to_vec())
+            },
+            |p, p_next| {
+                let (s, s_next) = mpc_sha512_block_verify(
+                    &words(&p.view.input),
+                    &words(&p_next.view.input),
+                    &mpc_public_verify(state),
+                    p,
+                    p_next,
+                )?;
+                Ok((s.to_vec(), s_next.to_vec()))
+            },
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use crate::gf2_word::GF2Word;
+
+pub fn padding(input: &[u8]) -> Vec<GF2Word<u64>> {
+    let mut msg = input.to_vec();
+    let length_u128 = (8 * input.len()) as u128; // msg len in bits
+    msg.push(0x80); // append one 1 bit and seven 0 bits
+
+    while !(msg.len() * 8 + 128).is_multiple_of(1024) {
+        msg.push(0x00);
+    }
+    msg.extend_from_slice(&length_u128.to_be_bytes());
+
+    assert!((msg.len() * 8).is_multiple_of(1024));
+    msg.chunks(8)
+        .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()).into())
+        .collect()
+}
+
+#[cfg(test)]
+mod test_padding {
+    use super::padding;
+
+    #[test]
+    fn test_padding() {
+        let padded_input = padding(b"abc");
+        assert_eq!(padded_input.len(), 16);
+        assert_eq!(padded_input[0].value, 0x6162638000000000);
+        assert_eq!(padded_input[15].value, 24);
+
+        // 112 bytes no longer fit the length in the first block
+        assert_eq!(padding(&[0u8; 111]).len(), 16);
+        assert_eq!(padding(&[0u8; 112]).len(), 32);
+    }
+}
@@ -0,0 +1,59 @@
+//! Digests of the NIST examples for "abc" and for the 112 byte message of
+//! the two block example.
+
+pub const SHORT_INPUT: &[u8] = b"abc";
+pub const LONG_INPUT: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+pub const SHORT_SHA512: [u64; 8] = [
+    0xddaf35a193617aba,
+    0xcc417349ae204131,
+    0x12e6fa4e89a97ea2,
+    0x0a9eeee64b55d39a,
+    0x2192992a274fc1a8,
+    0x36ba3c23a3feebbd,
+    0x454d4423643ce80e,
+    0x2a9ac94fa54ca49f,
+];
+
+pub const SHORT_SHA384: [u64; 6] = [
+    0xcb00753f45a35e8b,
+    0xb5a03d699ac65007,
+    0x272c32ab0eded163,
+    0x1a8b605a43ff5bed,
+    0x8086072ba1e7cc23,
+    0x58baeca134c825a7,
+];
+
+pub const SHORT_SHA512_256: [u64; 4] = [
+    0x53048e2681941ef9,
+    0x9b2e29b76b4c7dab,
+    0xe4c2d0c634fc6d46,
+    0xe0e2f13107e7af23,
+];
+
+pub const LONG_SHA512: [u64; 8] = [
+    0x8e959b75dae313da,
+    0x8cf4f72814fc143f,
+    0x8f7779c6eb9f7fa1,
+    0x7299aeadb6889018,
+    0x501d289e4900f7e4,
+    0x331b99dec4b5433a,
+    0xc7d329eeb6dd2654,
+    0x5e96e55b874be909,
+];
+
+pub const LONG_SHA384: [u64; 6] = [
+    0x09330c33f71147e8,
+    0x3d192fc782cd1b47,
+    0x53111b173b3b05d2,
+    0x2fa08086e3b0f712,
+    0xfcc7c71a557e2db9,
+    0x66c3e9fa91746039,
+];
+
+pub const LONG_SHA512_256: [u64; 4] = [
+    0x3928e184fb8690f8,
+    0x40da3988121d31be,
+    0x65cb9d3ef83ee614,
+    0x6feac861e19b563a,
+];
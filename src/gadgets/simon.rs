@@ -0,0 +1,253 @@
+use std::marker::PhantomData;
+
+use crate::{
+    error::Error,
+    gadgets::{mpc_and, mpc_and_verify},
+    gf2_word::{GF2Word, Value},
+    party::Party,
+};
+
+/// The constant sequences z_0 to z_4 of the key schedule, where bit `j` of
+/// each word is the `j`-th term of the sequence of period 62.
+const Z: [u64; 5] = [
+    0x19c3522fb386a45f,
+    0x16864fb8ad0c9f71,
+    0x3369f885192c0ef5,
+    0x3c2ce51207a635db,
+    0x3dc94c3a046d678b,
+];
+
+/// Parameters of Simon over words of type `T`: the key has `key_words` words,
+/// the cipher `rounds` rounds, and the key schedule uses the sequence `z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params<T> {
+    pub key_words: usize,
+    pub rounds: usize,
+    pub z: usize,
+    word: PhantomData<T>,
+}
+
+impl Params<u32> {
+    pub const SIMON_64_96: Self = Self::new(3, 42, 2);
+    pub const SIMON_64_128: Self = Self::new(4, 44, 3);
+}
+
+impl Params<u64> {
+    pub const SIMON_128_128: Self = Self::new(2, 68, 2);
+    pub const SIMON_128_192: Self = Self::new(3, 69, 3);
+    pub const SIMON_128_256: Self = Self::new(4, 72, 4);
+}
+
+impl<T> Params<T> {
+    pub const fn new(key_words: usize, rounds: usize, z: usize) -> Self {
+        assert!(key_words >= 2 && key_words <= 4);
+        assert!(z < Z.len());
+
+        Self {
+            key_words,
+            rounds,
+            z,
+            word: PhantomData,
+        }
+    }
+}
+
+impl<T: Value> Params<T> {
+    /// Bytes of the key.
+    pub fn key_len(&self) -> usize {
+        self.key_words * T::bytes_len()
+    }
+
+    /// Bytes of a block of two words.
+    pub fn block_len(&self) -> usize {
+        2 * T::bytes_len()
+    }
+
+    /// Mul gates of an encryption: the AND of every round, as the key schedule
+    /// is linear.
+    pub fn num_of_mul_gates(&self) -> usize {
+        self.rounds
+    }
+}
+
+/// A block `(x, y)`.
+pub type Block<T> = (GF2Word<T>, GF2Word<T>);
+
+/// Words of a key or of its round keys.
+pub type Words<T> = [GF2Word<T>];
+
+/// Round keys, one word per round.
+pub type RoundKeys<T> = Vec<GF2Word<T>>;
+
+fn rotr<T: Value>(x: GF2Word<T>, n: usize) -> GF2Word<T> {
+    x.value.right_rotate(n).into()
+}
+
+fn rotl<T: Value>(x: GF2Word<T>, n: usize) -> GF2Word<T> {
+    x.value.left_rotate(n).into()
+}
+
+/// Expand `key`, the words `k_0, .., k_{m-1}`, into the round keys. The key
+/// schedule is linear, so it applies to each share of the key on its own.
+pub fn expand_key<T: Value>(key: &Words<T>, params: &Params<T>) -> RoundKeys<T> {
+    assert_eq!(key.len(), params.key_words);
+
+    let m = params.key_words;
+    let mut k = key.to_vec();
+    for i in m..params.rounds {
+        let mut tmp = rotr(k[i - 1], 3);
+        if m == 4 {
+            tmp = tmp ^ k[i - 3];
+        }
+        tmp = tmp ^ rotr(tmp, 1);
+
+        let z = ((Z[params.z] >> ((i - m) % 62)) & 1) as u8;
+        let c = !T::from(3) ^ T::from(z);
+        k.push(k[i - m] ^ tmp ^ c.into());
+    }
+    k
+}
+
+/// The terms `S^1 x` and `S^8 x` of the AND, and the linear term `S^2 x`, of
+/// the round function.
+fn f_operands<T: Value>(x: GF2Word<T>) -> (GF2Word<T>, GF2Word<T>, GF2Word<T>) {
+    (rotl(x, 1), rotl(x, 8), rotl(x, 2))
+}
+
+/// Natively encrypt `block` with the round keys.
+pub fn encrypt<T: Value>(round_keys: &Words<T>, block: Block<T>) -> Block<T> {
+    round_keys.iter().fold(block, |(x, y), &k| {
+        let (a, b, c) = f_operands(x);
+        (y ^ (a & b) ^ c ^ k, x)
+    })
+}
+
+/// Encrypt shares of a block with shares of the round keys, with one mul gate
+/// per round.
+pub fn mpc_encrypt<T: Value>(
+    round_keys: (&Words<T>, &Words<T>, &Words<T>),
+    block: (Block<T>, Block<T>, Block<T>),
+    p1: &mut Party<T>,
+    p2: &mut Party<T>,
+    p3: &mut Party<T>,
+) -> (Block<T>, Block<T>, Block<T>) {
+    let ((mut x1, mut y1), (mut x2, mut y2), (mut x3, mut y3)) = block;
+
+    for i in 0..round_keys.0.len() {
+        let (a1, b1, c1) = f_operands(x1);
+        let (a2, b2, c2) = f_operands(x2);
+        let (a3, b3, c3) = f_operands(x3);
+        let (and1, and2, and3) = mpc_and((a1, b1), (a2, b2), (a3, b3), p1, p2, p3);
+
+        (x1, y1) = (y1 ^ and1 ^ c1 ^ round_keys.0[i], x1);
+        (x2, y2) = (y2 ^ and2 ^ c2 ^ round_keys.1[i], x2);
+        (x3, y3) = (y3 ^ and3 ^ c3 ^ round_keys.2[i], x3);
+    }
+
+    ((x1, y1), (x2, y2), (x3, y3))
+}
+
+pub fn mpc_encrypt_verify<T: Value>(
+    round_keys: (&Words<T>, &Words<T>),
+    block: (Block<T>, Block<T>),
+    p: &mut Party<T>,
+    p_next: &mut Party<T>,
+) -> Result<(Block<T>, Block<T>), Error> {
+    let ((mut x, mut y), (mut x_next, mut y_next)) = block;
+
+    for i in 0..round_keys.0.len() {
+        let (a, b, c) = f_operands(x);
+        let (a_next, b_next, c_next) = f_operands(x_next);
+        let (and, and_next) = mpc_and_verify((a, b), (a_next, b_next), p, p_next)?;
+
+        (x, y) = (y ^ and ^ c ^ round_keys.0[i], x);
+        (x_next, y_next) = (y_next ^ and_next ^ c_next ^ round_keys.1[i], x_next);
+    }
+
+    Ok(((x, y), (x_next, y_next)))
+}
+
+#[cfg(test)]
+mod test_simon {
+    use super::{encrypt, expand_key, mpc_encrypt, mpc_encrypt_verify, Block, Params};
+    use crate::{
+        gadgets::{
+            mpc_public, mpc_public_verify, prepare::generic_parse, test_utils::check_shares,
+        },
+        gf2_word::{GF2Word, Value},
+    };
+
+    /// Encrypt a public `plaintext` with a secret key of `params` on shares.
+    fn check_mpc_encrypt<T: Value + PartialEq>(params: Params<T>, plaintext: Block<T>) {
+        let key: Vec<u8> = (0..params.key_len()).map(|i| i as u8).collect();
+        let key_words = |key: &[u8]| generic_parse(key, params.key_words).unwrap();
+
+        check_shares(
+            &key,
+            params.num_of_mul_gates(),
+            |key| {
+                let (x, y) = encrypt(&expand_key(&key_words(key), &params), plaintext);
+                vec![x, y]
+            },
+            |p1, p2, p3| {
+                let (c1, c2, c3) = mpc_encrypt(
+                    (
+                        &expand_key(&key_words(&p1.view.input), &params),
+                        &expand_key(&key_words(&p2.view.input), &params),
+                        &expand_key(&key_words(&p3.view.input), &params),
+                    ),
+                    mpc_public(plaintext),
+                    p1,
+                    p2,
+                    p3,
+                );
+                (vec![c1.0, c1.1], vec![c2.0, c2.1], vec![c3.0, c3.1])
+            },
+            |p, p_next| {
+                let (c, c_next) = mpc_encrypt_verify(
+                    (
+                        &expand_key(&key_words(&p.view.input), &params),
+                        &expand_key(&key_words(&p_next.view.input), &params),
+                    ),
+                    mpc_public_verify(plaintext),
+                    p,
+                    p_next,
+                )?;
+                Ok((vec![c.0, c.1], vec![c_next.0, c_next.1]))
+            },
+        );
+    }
+
+    #[test]
+    fn test_encrypt() {
+        // Simon64/128 and Simon128/256 from the Simon and Speck paper, with
+        // the key words from k_0 on
+        let key = [0x03020100u32, 0x0b0a0908, 0x13121110, 0x1b1a1918].map(GF2Word::from);
+        let round_keys = expand_key(&key, &Params::SIMON_64_128);
+        let (x, y) = encrypt(&round_keys, (0x656b696c.into(), 0x20646e75.into()));
+        assert_eq!((x.value, y.value), (0x44c8fc20, 0xb9dfa07a));
+
+        let key = [
+            0x0706050403020100u64,
+            0x0f0e0d0c0b0a0908,
+            0x1716151413121110,
+            0x1f1e1d1c1b1a1918,
+        ]
+        .map(GF2Word::from);
+        let round_keys = expand_key(&key, &Params::SIMON_128_256);
+        let (x, y) = encrypt(
+            &round_keys,
+            (0x74206e69206d6f6f.into(), 0x6d69732061207369.into()),
+        );
+        assert_eq!((x.value, y.value), (0x8d2b5579afc8a3a0, 0x3bf72a87efe7b868));
+    }
+
+    #[test]
+    fn test_mpc_encrypt() {
+        check_mpc_encrypt(Params::SIMON_64_96, (0x6f722067.into(), 0x6e696c63.into()));
+        check_mpc_encrypt(
+            Params::SIMON_128_256,
+            (0x74206e69206d6f6f.into(), 0x6d69732061207369.into()),
+        );
+    }
+}
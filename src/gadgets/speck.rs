@@ -0,0 +1,309 @@
+use std::marker::PhantomData;
+
+use crate::{
+    error::Error,
+    gadgets::add_mod::{add_mod_verify, adder, mpc_add_mod},
+    gf2_word::{GF2Word, Value},
+    party::Party,
+};
+
+/// Right rotation of `x`, and left rotation of `y`, in every round. These are
+/// the amounts for words of at least 32 bits.
+const ALPHA: usize = 8;
+const BETA: usize = 3;
+
+/// Parameters of Speck over words of type `T`: the key has `key_words` words
+/// and the cipher `rounds` rounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params<T> {
+    pub key_words: usize,
+    pub rounds: usize,
+    word: PhantomData<T>,
+}
+
+impl Params<u32> {
+    pub const SPECK_64_96: Self = Self::new(3, 26);
+    pub const SPECK_64_128: Self = Self::new(4, 27);
+}
+
+impl Params<u64> {
+    pub const SPECK_128_128: Self = Self::new(2, 32);
+    pub const SPECK_128_192: Self = Self::new(3, 33);
+    pub const SPECK_128_256: Self = Self::new(4, 34);
+}
+
+impl<T> Params<T> {
+    /// Only the presets are exposed, as `ALPHA` and `BETA` do not apply to
+    /// words of less than 32 bits.
+    const fn new(key_words: usize, rounds: usize) -> Self {
+        assert!(key_words >= 2 && key_words <= 4);
+
+        Self {
+            key_words,
+            rounds,
+            word: PhantomData,
+        }
+    }
+}
+
+impl<T: Value> Params<T> {
+    /// Bytes of the key.
+    pub fn key_len(&self) -> usize {
+        self.key_words * T::bytes_len()
+    }
+
+    /// Bytes of a block of two words.
+    pub fn block_len(&self) -> usize {
+        2 * T::bytes_len()
+    }
+
+    /// Mul gates of an encryption: one addition in every round of the cipher
+    /// and of the key schedule.
+    pub fn num_of_mul_gates(&self) -> usize {
+        2 * self.rounds - 1
+    }
+}
+
+/// A block `(x, y)`.
+pub type Block<T> = (GF2Word<T>, GF2Word<T>);
+
+/// Words of a key or of its round keys.
+pub type Words<T> = [GF2Word<T>];
+
+/// Round keys, one word per round.
+pub type RoundKeys<T> = Vec<GF2Word<T>>;
+
+fn rotr<T: Value>(x: GF2Word<T>, n: usize) -> GF2Word<T> {
+    x.value.right_rotate(n).into()
+}
+
+fn rotl<T: Value>(x: GF2Word<T>, n: usize) -> GF2Word<T> {
+    x.value.left_rotate(n).into()
+}
+
+fn add<T: Value>(a: GF2Word<T>, b: GF2Word<T>) -> GF2Word<T> {
+    adder(a.value, b.value).into()
+}
+
+/// The public round counter XORed into the key schedule.
+fn counter<T: Value>(i: usize) -> GF2Word<T> {
+    T::from(i as u8).into()
+}
+
+/// Natively expand `key`, the words `k_0, l_0, .., l_{m-2}`, into the round
+/// keys.
+pub fn expand_key<T: Value>(key: &Words<T>, params: &Params<T>) -> RoundKeys<T> {
+    assert_eq!(key.len(), params.key_words);
+
+    let mut k = vec![key[0]];
+    let mut l = key[1..].to_vec();
+    for i in 0..params.rounds - 1 {
+        l.push(add(k[i], rotr(l[i], ALPHA)) ^ counter(i));
+        k.push(rotl(k[i], BETA) ^ l[i + params.key_words - 1]);
+    }
+    k
+}
+
+/// Expand shares of the key into shares of the round keys, with one mul gate
+/// for every round but the last.
+pub fn mpc_expand_key<T: Value>(
+    key: (&Words<T>, &Words<T>, &Words<T>),
+    params: &Params<T>,
+    p1: &mut Party<T>,
+    p2: &mut Party<T>,
+    p3: &mut Party<T>,
+) -> (RoundKeys<T>, RoundKeys<T>, RoundKeys<T>) {
+    let (mut k1, mut k2, mut k3) = (vec![key.0[0]], vec![key.1[0]], vec![key.2[0]]);
+    let (mut l1, mut l2, mut l3) = (
+        key.0[1..].to_vec(),
+        key.1[1..].to_vec(),
+        key.2[1..].to_vec(),
+    );
+
+    for i in 0..params.rounds - 1 {
+        let (s1, s2, s3) = mpc_add_mod(
+            (k1[i], rotr(l1[i], ALPHA)),
+            (k2[i], rotr(l2[i], ALPHA)),
+            (k3[i], rotr(l3[i], ALPHA)),
+            p1,
+            p2,
+            p3,
+        );
+        l1.push(s1 ^ counter(i));
+        l2.push(s2 ^ counter(i));
+        l3.push(s3 ^ counter(i));
+
+        let j = i + params.key_words - 1;
+        k1.push(rotl(k1[i], BETA) ^ l1[j]);
+        k2.push(rotl(k2[i], BETA) ^ l2[j]);
+        k3.push(rotl(k3[i], BETA) ^ l3[j]);
+    }
+
+    (k1, k2, k3)
+}
+
+pub fn mpc_expand_key_verify<T: Value>(
+    key: (&Words<T>, &Words<T>),
+    params: &Params<T>,
+    p: &mut Party<T>,
+    p_next: &mut Party<T>,
+) -> Result<(RoundKeys<T>, RoundKeys<T>), Error> {
+    let (mut k, mut k_next) = (vec![key.0[0]], vec![key.1[0]]);
+    let (mut l, mut l_next) = (key.0[1..].to_vec(), key.1[1..].to_vec());
+
+    for i in 0..params.rounds - 1 {
+        let (s, s_next) = add_mod_verify(
+            (k[i], rotr(l[i], ALPHA)),
+            (k_next[i], rotr(l_next[i], ALPHA)),
+            p,
+            p_next,
+        )?;
+        l.push(s ^ counter(i));
+        l_next.push(s_next ^ counter(i));
+
+        let j = i + params.key_words - 1;
+        k.push(rotl(k[i], BETA) ^ l[j]);
+        k_next.push(rotl(k_next[i], BETA) ^ l_next[j]);
+    }
+
+    Ok((k, k_next))
+}
+
+/// Natively encrypt `block` with the round keys.
+pub fn encrypt<T: Value>(round_keys: &Words<T>, block: Block<T>) -> Block<T> {
+    round_keys.iter().fold(block, |(x, y), &k| {
+        let x = add(rotr(x, ALPHA), y) ^ k;
+        (x, rotl(y, BETA) ^ x)
+    })
+}
+
+/// Encrypt shares of a block with shares of the round keys, with one mul gate
+/// per round.
+pub fn mpc_encrypt<T: Value>(
+    round_keys: (&Words<T>, &Words<T>, &Words<T>),
+    block: (Block<T>, Block<T>, Block<T>),
+    p1: &mut Party<T>,
+    p2: &mut Party<T>,
+    p3: &mut Party<T>,
+) -> (Block<T>, Block<T>, Block<T>) {
+    let ((mut x1, mut y1), (mut x2, mut y2), (mut x3, mut y3)) = block;
+
+    for i in 0..round_keys.0.len() {
+        (x1, x2, x3) = mpc_add_mod(
+            (rotr(x1, ALPHA), y1),
+            (rotr(x2, ALPHA), y2),
+            (rotr(x3, ALPHA), y3),
+            p1,
+            p2,
+            p3,
+        );
+        (x1, x2, x3) = (
+            x1 ^ round_keys.0[i],
+            x2 ^ round_keys.1[i],
+            x3 ^ round_keys.2[i],
+        );
+        (y1, y2, y3) = (
+            rotl(y1, BETA) ^ x1,
+            rotl(y2, BETA) ^ x2,
+            rotl(y3, BETA) ^ x3,
+        );
+    }
+
+    ((x1, y1), (x2, y2), (x3, y3))
+}
+
+pub fn mpc_encrypt_verify<T: Value>(
+    round_keys: (&Words<T>, &Words<T>),
+    block: (Block<T>, Block<T>),
+    p: &mut Party<T>,
+    p_next: &mut Party<T>,
+) -> Result<(Block<T>, Block<T>), Error> {
+    let ((mut x, mut y), (mut x_next, mut y_next)) = block;
+
+    for i in 0..round_keys.0.len() {
+        (x, x_next) = add_mod_verify(
+            (rotr(x, ALPHA), y),
+            (rotr(x_next, ALPHA), y_next),
+            p,
+            p_next,
+        )?;
+        (x, x_next) = (x ^ round_keys.0[i], x_next ^ round_keys.1[i]);
+        (y, y_next) = (rotl(y, BETA) ^ x, rotl(y_next, BETA) ^ x_next);
+    }
+
+    Ok(((x, y), (x_next, y_next)))
+}
+
+#[cfg(test)]
+mod test_speck {
+    use super::{
+        encrypt, expand_key, mpc_encrypt, mpc_encrypt_verify, mpc_expand_key,
+        mpc_expand_key_verify, Block, Params,
+    };
+    use crate::{
+        gadgets::{
+            mpc_public, mpc_public_verify, prepare::generic_parse, test_utils::check_shares,
+        },
+        gf2_word::{GF2Word, Value},
+    };
+
+    /// Expand a secret key of `params` and encrypt a public `plaintext` with
+    /// it on shares.
+    fn check_mpc_encrypt<T: Value + PartialEq>(params: Params<T>, plaintext: Block<T>) {
+        let key: Vec<u8> = (0..params.key_len()).map(|i| i as u8).collect();
+        let key_words = |key: &[u8]| generic_parse(key, params.key_words).unwrap();
+
+        check_shares(
+            &key,
+            params.num_of_mul_gates(),
+            |key| {
+                let (x, y) = encrypt(&expand_key(&key_words(key), &params), plaintext);
+                vec![x, y]
+            },
+            |p1, p2, p3| {
+                let key = (
+                    key_words(&p1.view.input),
+                    key_words(&p2.view.input),
+                    key_words(&p3.view.input),
+                );
+                let (k1, k2, k3) = mpc_expand_key((&key.0, &key.1, &key.2), &params, p1, p2, p3);
+                let (c1, c2, c3) = mpc_encrypt((&k1, &k2, &k3), mpc_public(plaintext), p1, p2, p3);
+                (vec![c1.0, c1.1], vec![c2.0, c2.1], vec![c3.0, c3.1])
+            },
+            |p, p_next| {
+                let key = (key_words(&p.view.input), key_words(&p_next.view.input));
+                let (k, k_next) = mpc_expand_key_verify((&key.0, &key.1), &params, p, p_next)?;
+                let (c, c_next) =
+                    mpc_encrypt_verify((&k, &k_next), mpc_public_verify(plaintext), p, p_next)?;
+                Ok((vec![c.0, c.1], vec![c_next.0, c_next.1]))
+            },
+        );
+    }
+
+    #[test]
+    fn test_encrypt() {
+        // Speck64/128 and Speck128/128 from the Simon and Speck paper, with
+        // the key words from k_0 on
+        let key = [0x03020100u32, 0x0b0a0908, 0x13121110, 0x1b1a1918].map(GF2Word::from);
+        let round_keys = expand_key(&key, &Params::SPECK_64_128);
+        let (x, y) = encrypt(&round_keys, (0x3b726574.into(), 0x7475432d.into()));
+        assert_eq!((x.value, y.value), (0x8c6fa548, 0x454e028b));
+
+        let key = [0x0706050403020100u64, 0x0f0e0d0c0b0a0908].map(GF2Word::from);
+        let round_keys = expand_key(&key, &Params::SPECK_128_128);
+        let (x, y) = encrypt(
+            &round_keys,
+            (0x6c61766975716520.into(), 0x7469206564616d20.into()),
+        );
+        assert_eq!((x.value, y.value), (0xa65d985179783265, 0x7860fedf5c570d18));
+    }
+
+    #[test]
+    fn test_mpc_encrypt() {
+        check_mpc_encrypt(Params::SPECK_64_96, (0x74614620.into(), 0x736e6165.into()));
+        check_mpc_encrypt(
+            Params::SPECK_128_256,
+            (0x65736f6874206e49.into(), 0x202e72656e6f6f70.into()),
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Checks of the MPC gadgets against their native counterparts.
+
+use rand::{thread_rng, RngCore};
+use rand_chacha::ChaCha20Rng;
+
+use crate::{
+    circuit::{Output, TwoThreeDecOutput},
+    error::Error,
+    gf2_word::Value,
+    key::Key,
+    party::Party,
+    tape::Tape,
+};
+
+/// Split `input` into three random shares, run `mpc` on the parties holding
+/// them, with tapes of `tape_len` words, and check that the output shares XOR
+/// to `native(input)` and that every tape is read to the end. Then check that
+/// `verify` of each party and the next one, from the tape and share of the
+/// first and the view of the second, gives both output shares back and
+/// resends the messages and carries of the first.
+pub(crate) fn check_shares<T: Value + PartialEq>(
+    input: &[u8],
+    tape_len: usize,
+    native: impl Fn(&[u8]) -> Output<T>,
+    mpc: impl Fn(&mut Party<T>, &mut Party<T>, &mut Party<T>) -> TwoThreeDecOutput<T>,
+    verify: impl Fn(&mut Party<T>, &mut Party<T>) -> Result<(Output<T>, Output<T>), Error>,
+) {
+    let mut rng = thread_rng();
+
+    let mut share_1 = vec![0u8; input.len()];
+    let mut share_2 = vec![0u8; input.len()];
+    rng.fill_bytes(&mut share_1);
+    rng.fill_bytes(&mut share_2);
+    let share_3 = input
+        .iter()
+        .zip(share_1.iter().zip(&share_2))
+        .map(|(x, (s1, s2))| x ^ s1 ^ s2)
+        .collect();
+    let shares = [share_1, share_2, share_3];
+
+    let keys: [Key; 3] = std::array::from_fn(|_| {
+        let mut key = Key::default();
+        rng.fill_bytes(&mut key);
+        key
+    });
+
+    let [mut p1, mut p2, mut p3] = std::array::from_fn(|i| {
+        Party::<T>::new::<ChaCha20Rng>(shares[i].clone(), keys[i], tape_len)
+    });
+    let (o1, o2, o3) = mpc(&mut p1, &mut p2, &mut p3);
+
+    let output: Output<T> = o1
+        .iter()
+        .zip(o2.iter().zip(&o3))
+        .map(|(&a, (&b, &c))| a ^ b ^ c)
+        .collect();
+    assert_eq!(output, native(input));
+
+    let parties = [p1, p2, p3];
+    let outputs = [o1, o2, o3];
+    for party in parties.iter() {
+        assert_eq!(party.tape.values_read(), tape_len);
+    }
+
+    for i in 0..3 {
+        let next = (i + 1) % 3;
+        let mut p = Party::<T>::new::<ChaCha20Rng>(shares[i].clone(), keys[i], tape_len);
+        let mut p_next = Party::from_tape_and_view(
+            parties[next].view.clone(),
+            Tape::from_key::<ChaCha20Rng>(keys[next], tape_len),
+        );
+
+        let (o, o_next) = verify(&mut p, &mut p_next).unwrap();
+        assert_eq!(o, outputs[i]);
+        assert_eq!(o_next, outputs[next]);
+        assert_eq!(p.view.messages, parties[i].view.messages);
+        assert_eq!(p.view.carries, parties[i].view.carries);
+    }
+}
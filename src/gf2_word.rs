@@ -4,7 +4,7 @@ use std::{
 };
 
 use rand_core::{CryptoRng, RngCore};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 
 pub trait BytesUtils {
     fn to_bytes(&self) -> Vec<u8>;
@@ -41,6 +41,9 @@ pub trait Value:
     + BytesUtils
     + GenRand
     + Serialize
+    + DeserializeOwned
+    + Send
+    + Sync
 {
 }
 
@@ -95,11 +98,19 @@ pub trait BitUtils: BitTrait {
     /// Rotate left by `n` bits.
     fn left_rotate(&self, n: usize) -> Self {
         assert!(n <= Self::bits_len());
+        // a shift by the full width overflows
+        if n == 0 || n == Self::bits_len() {
+            return *self;
+        }
         (*self << n) | (*self >> (Self::bits_len() - n))
     }
     /// Rotate right by `n` bits.
     fn right_rotate(&self, n: usize) -> Self {
         assert!(n <= Self::bits_len());
+        // a shift by the full width overflows
+        if n == 0 || n == Self::bits_len() {
+            return *self;
+        }
         (*self >> n) | (*self << (Self::bits_len() - n))
     }
 
@@ -223,14 +234,14 @@ impl BytesUtils for u128 {
 
 impl GenRand for u128 {
     fn gen_rand<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
-        let hi: u128 = rng.next_u64().try_into().unwrap();
-        let low: u128 = rng.next_u64().try_into().unwrap();
+        let hi = u128::from(rng.next_u64());
+        let low = u128::from(rng.next_u64());
         (hi << 64) | low
     }
 }
 
 /// A wrapper type for which we implement `BitAnd`, `BitXor`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct GF2Word<T: Value> {
     /// The value represented by this GF2 word
     pub value: T,
@@ -238,6 +249,19 @@ pub struct GF2Word<T: Value> {
     pub size: usize,
 }
 
+/// Only `value` is encoded, `size` is derived from `T` when decoding.
+impl<T: Value> Serialize for GF2Word<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T: Value> Deserialize<'de> for GF2Word<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::from)
+    }
+}
+
 impl<T: Value> From<T> for GF2Word<T> {
     fn from(value: T) -> Self {
         GF2Word::<T> {
@@ -305,4 +329,14 @@ mod gf2_word_tests {
 
         assert_eq!((x ^ y).value, v1 ^ v2);
     }
+
+    #[test]
+    fn compact_encoding() {
+        let x = GF2Word::<u32>::from(0xdeadbeef);
+        let bytes = bincode::serialize(&x).unwrap();
+        assert_eq!(bytes, 0xdeadbeefu32.to_le_bytes());
+
+        let y: GF2Word<u32> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(x, y);
+    }
 }
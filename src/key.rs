@@ -1,8 +1,20 @@
 use rand::{CryptoRng, RngCore};
 
-use crate::config::KEY_LEN;
+use crate::{config::KEY_LEN, error::Error};
 
 pub type Key = [u8; KEY_LEN];
+
+/// Pad `key` with zeros to seed a tape, failing if it is longer than `KEY_LEN`.
+pub fn pad_key(key: &[u8]) -> Result<Key, Error> {
+    if key.len() > KEY_LEN {
+        return Err(Error::KeyLenError(KEY_LEN, key.len()));
+    }
+
+    let mut padded = Key::default();
+    padded[..key.len()].copy_from_slice(key);
+    Ok(padded)
+}
+
 pub struct KeyManager {
     pub keys_bytes: Vec<u8>,
     num_of_accessible_keys: usize,
@@ -10,9 +22,17 @@ pub struct KeyManager {
 }
 
 impl KeyManager {
-    pub fn new<R: RngCore + CryptoRng>(num_repetitions: usize, rng: &mut R) -> Self {
+    /// Sample 3 keys per repetition, each with `key_len` random bytes followed
+    /// by zero padding. `key_len` must be at most `KEY_LEN`.
+    pub fn new<R: RngCore + CryptoRng>(
+        num_repetitions: usize,
+        key_len: usize,
+        rng: &mut R,
+    ) -> Self {
         let mut keys_bytes = vec![0u8; 3 * num_repetitions * KEY_LEN];
-        rng.fill_bytes(&mut keys_bytes);
+        for key in keys_bytes.chunks_mut(KEY_LEN) {
+            rng.fill_bytes(&mut key[..key_len]);
+        }
         Self {
             keys_bytes,
             num_of_accessible_keys: 3 * num_repetitions,
@@ -20,21 +40,58 @@ impl KeyManager {
         }
     }
 
-    pub fn request_key(&mut self) -> Key {
-        let offset = self.offset;
-        if offset == self.num_of_accessible_keys {
-            panic!("No more keys left!");
+    pub fn request_key(&mut self) -> Result<Key, Error> {
+        let key = self.request_key_i(self.offset)?;
+        self.offset += 1;
+        Ok(key)
+    }
+
+    pub fn request_key_i(&self, pos: usize) -> Result<Key, Error> {
+        if pos >= self.num_of_accessible_keys {
+            return Err(Error::KeysExhaustedError);
         }
 
-        self.offset += 1;
-        self.keys_bytes[offset * KEY_LEN..(offset + 1) * KEY_LEN]
+        // safe to unwrap since the slice is exactly KEY_LEN bytes
+        Ok(self.keys_bytes[pos * KEY_LEN..(pos + 1) * KEY_LEN]
             .try_into()
-            .unwrap()
+            .unwrap())
     }
+}
 
-    pub fn request_key_i(&self, pos: usize) -> Key {
-        self.keys_bytes[pos * KEY_LEN..(pos + 1) * KEY_LEN]
-            .try_into()
-            .unwrap()
+#[cfg(test)]
+mod test_key_manager {
+    use rand::thread_rng;
+
+    use super::{pad_key, KeyManager};
+    use crate::error::Error;
+
+    #[test]
+    fn test_exhausted() {
+        let mut key_manager = KeyManager::new(1, 16, &mut thread_rng());
+        for _ in 0..3 {
+            key_manager.request_key().unwrap();
+        }
+
+        assert!(matches!(
+            key_manager.request_key(),
+            Err(Error::KeysExhaustedError)
+        ));
+        assert!(matches!(
+            key_manager.request_key_i(3),
+            Err(Error::KeysExhaustedError)
+        ));
+    }
+
+    #[test]
+    fn test_key_len() {
+        let key_manager = KeyManager::new(1, 16, &mut thread_rng());
+        let key = key_manager.request_key_i(0).unwrap();
+        assert_eq!(key[16..], [0u8; 16]);
+        assert_eq!(pad_key(&key[..16]).unwrap(), key);
+
+        assert!(matches!(
+            pad_key(&[0u8; 33]),
+            Err(Error::KeyLenError(32, 33))
+        ));
     }
 }
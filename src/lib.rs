@@ -1,13 +1,29 @@
+/// Iterate over `$e` in parallel when the `parallel` feature is enabled and
+/// sequentially otherwise.
+macro_rules! cfg_into_iter {
+    ($e:expr) => {{
+        #[cfg(feature = "parallel")]
+        let iter = rayon::iter::IntoParallelIterator::into_par_iter($e);
+        #[cfg(not(feature = "parallel"))]
+        let iter = IntoIterator::into_iter($e);
+        iter
+    }};
+}
+
 pub mod circuit;
+pub mod circuits;
 pub mod commitment;
 pub mod config;
 pub mod data_structures;
+#[cfg(any(test, feature = "diagnostics"))]
+pub mod diagnostics;
 pub mod error;
 pub mod fs;
 pub mod gf2_word;
 pub mod key;
 pub mod party;
 pub mod prover;
+pub mod security;
 pub mod tape;
 pub mod verifier;
 pub mod view;
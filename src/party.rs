@@ -1,6 +1,7 @@
 use rand::{CryptoRng, RngCore, SeedableRng};
 
 use crate::{
+    error::Error,
     gf2_word::{GF2Word, Value},
     key::Key,
     tape::Tape,
@@ -25,15 +26,28 @@ impl<T: Value> Party<T> {
         Self { view, tape }
     }
 
+    /// A party whose input share of `share_len` bytes is derived from `k`,
+    /// as done for the first two parties in ZKB++.
+    pub fn from_key<TapeR: SeedableRng<Seed = Key> + RngCore + CryptoRng>(
+        k: Key,
+        share_len: usize,
+        tape_len: usize,
+    ) -> Self {
+        let (share, tape) = Tape::<T>::from_key_with_share::<TapeR>(k, share_len, tape_len);
+        let view = View::new(share);
+
+        Self { view, tape }
+    }
+
     pub fn from_tape_and_view(view: View<T>, tape: Tape<T>) -> Self {
         Self { tape, view }
     }
 
-    pub fn read_tape(&mut self) -> GF2Word<T> {
+    pub fn read_tape(&mut self) -> Result<GF2Word<T>, Error> {
         self.tape.read_next()
     }
 
-    pub fn read_view(&mut self) -> GF2Word<T> {
+    pub fn read_view(&mut self) -> Result<GF2Word<T>, Error> {
         self.view.read_next()
     }
 }
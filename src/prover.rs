@@ -3,17 +3,20 @@ use rand_core::{CryptoRng, RngCore};
 use sha3::{digest::FixedOutputReset, Digest};
 use std::{fmt::Debug, marker::PhantomData};
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 use crate::{
-    circuit::{Circuit, TwoThreeDecOutput},
+    circuit::{Circuit, CircuitId, Output, TwoThreeDecOutput},
     commitment::Commitment,
-    config::HASH_LEN,
-    data_structures::{PartyExecution, Proof, PublicInput},
+    data_structures::{Mode, PartyExecution, Proof, PublicInput},
     error::Error,
-    fs::SigmaFS,
+    fs::{DomainSeparator, SigmaFS},
     gf2_word::{GF2Word, GenRand, Value},
     key::{Key, KeyManager},
-    num_of_repetitions_given_desired_security,
     party::Party,
+    security::SecurityParams,
+    tape::Tape,
     view::View,
 };
 
@@ -24,6 +27,16 @@ pub struct RepetitionOutput<T: Value> {
     pub party_views: (View<T>, View<T>, View<T>),
 }
 
+/// Views, outputs and commitments of all parties in all repetitions, in the
+/// order they are committed to.
+pub(crate) struct Execution<T: Value, D: Default + Digest> {
+    circuit_id: CircuitId,
+    key_manager: KeyManager,
+    pub(crate) views: Vec<View<T>>,
+    pub(crate) outputs: Vec<Output<T>>,
+    commitments: Vec<Commitment<D>>,
+}
+
 pub struct Prover<T: Value, TapeR, D>(PhantomData<(T, TapeR, D)>)
 where
     TapeR: SeedableRng<Seed = Key> + RngCore + CryptoRng,
@@ -32,7 +45,7 @@ where
 impl<T: Value, TapeR, D> Prover<T, TapeR, D>
 where
     TapeR: SeedableRng<Seed = Key> + RngCore + CryptoRng,
-    D: Debug + Default + Digest + FixedOutputReset,
+    D: Debug + Default + Digest + FixedOutputReset + Send + Sync,
 {
     pub fn share<R: RngCore + CryptoRng>(rng: &mut R, input: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
         let share_1: Vec<u8> = (0..input.len()).map(|_| u8::gen_rand(rng)).collect();
@@ -54,11 +67,41 @@ where
         keys: (Key, Key, Key),
         tape_len: usize,
     ) -> (Party<T>, Party<T>, Party<T>) {
-        let (share_1, share_2, share_3) = Self::share(rng, input);
+        Self::init_parties_from_shares(Self::share(rng, input), keys, tape_len)
+    }
+
+    pub fn init_parties_from_shares(
+        shares: (Vec<u8>, Vec<u8>, Vec<u8>),
+        keys: (Key, Key, Key),
+        tape_len: usize,
+    ) -> (Party<T>, Party<T>, Party<T>) {
+        let p1 = Party::new::<TapeR>(shares.0, keys.0, tape_len);
+        let p2 = Party::new::<TapeR>(shares.1, keys.1, tape_len);
+        let p3 = Party::new::<TapeR>(shares.2, keys.2, tape_len);
+
+        (p1, p2, p3)
+    }
+
+    /// Initialise parties as in ZKB++: the shares of the first two parties are
+    /// derived from their keys and the third party gets the remaining share.
+    pub fn init_parties_from_keys(
+        input: &[u8],
+        keys: (Key, Key, Key),
+        tape_len: usize,
+    ) -> (Party<T>, Party<T>, Party<T>) {
+        let p1 = Party::from_key::<TapeR>(keys.0, input.len(), tape_len);
+        let p2 = Party::from_key::<TapeR>(keys.1, input.len(), tape_len);
 
-        let p1 = Party::new::<TapeR>(share_1, keys.0, tape_len);
-        let p2 = Party::new::<TapeR>(share_2, keys.1, tape_len);
-        let p3 = Party::new::<TapeR>(share_3, keys.2, tape_len);
+        let share_3: Vec<u8> = input
+            .iter()
+            .zip(p1.view.input.iter())
+            .zip(p2.view.input.iter())
+            .map(|((&i1, &i2), &i3)| i1 ^ i2 ^ i3)
+            .collect();
+        let p3 = Party::from_tape_and_view(
+            View::new(share_3),
+            Tape::from_key::<TapeR>(keys.2, tape_len),
+        );
 
         (p1, p2, p3)
     }
@@ -68,77 +111,186 @@ where
         input: &[u8],
         keys: (Key, Key, Key),
         circuit: &impl Circuit<T>,
+        mode: Mode,
     ) -> RepetitionOutput<T> {
-        let (mut p1, mut p2, mut p3) =
-            Self::init_parties(rng, input, keys, circuit.num_of_mul_gates());
+        let shares = match mode {
+            Mode::ZKBoo => Some(Self::share(rng, input)),
+            Mode::ZKBPlusPlus => None,
+        };
+        Self::prove_repetition_from_shares(input, shares, keys, circuit, mode)
+    }
+
+    /// Run one repetition without touching the prover's rng: in ZKBoo the input
+    /// `shares` are sampled beforehand, in ZKB++ they are derived from `keys`.
+    pub fn prove_repetition_from_shares(
+        input: &[u8],
+        shares: Option<(Vec<u8>, Vec<u8>, Vec<u8>)>,
+        keys: (Key, Key, Key),
+        circuit: &impl Circuit<T>,
+        mode: Mode,
+    ) -> RepetitionOutput<T> {
+        let tape_len = circuit.num_of_mul_gates();
+        let (mut p1, mut p2, mut p3) = match shares {
+            Some(shares) => Self::init_parties_from_shares(shares, keys, tape_len),
+            None => Self::init_parties_from_keys(input, keys, tape_len),
+        };
         let party_outputs = circuit.compute_23_decomposition(&mut p1, &mut p2, &mut p3);
+
+        // shares derived from keys are neither committed to nor sent
+        for (party_index, party) in [&mut p1, &mut p2].into_iter().enumerate() {
+            if mode.derives_share(party_index) {
+                party.view.input.clear();
+            }
+        }
+
         RepetitionOutput {
             party_outputs,
             party_views: (p1.view, p2.view, p3.view),
         }
     }
 
-    pub fn prove<R: RngCore + CryptoRng, const SIGMA: usize>(
+    /// Prove with the original ZKBoo protocol.
+    pub fn prove<R: RngCore + CryptoRng>(
         rng: &mut R,
         witness: &[u8],
         circuit: &impl Circuit<T>,
         public_output: &Vec<GF2Word<T>>,
-    ) -> Result<Proof<T, D, SIGMA>, Error> {
-        let num_of_repetitions = num_of_repetitions_given_desired_security(SIGMA);
+        params: &SecurityParams,
+        dst: &DomainSeparator,
+    ) -> Result<Proof<T, D>, Error> {
+        Self::prove_with_mode(
+            rng,
+            witness,
+            circuit,
+            public_output,
+            params,
+            dst,
+            Mode::ZKBoo,
+        )
+    }
 
-        let mut key_manager = KeyManager::new(num_of_repetitions, rng);
+    pub fn prove_with_mode<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        witness: &[u8],
+        circuit: &impl Circuit<T>,
+        public_output: &Vec<GF2Word<T>>,
+        params: &SecurityParams,
+        dst: &DomainSeparator,
+        mode: Mode,
+    ) -> Result<Proof<T, D>, Error> {
+        let execution = Self::execute(rng, witness, circuit, params, dst, mode)?;
+        Self::open(execution, public_output, params, dst, mode)
+    }
 
-        let mut outputs = Vec::<Vec<GF2Word<T>>>::with_capacity(3 * num_of_repetitions);
-        let mut all_commitments = Vec::<Commitment<D>>::with_capacity(3 * num_of_repetitions);
-        let mut all_views = Vec::with_capacity(3 * num_of_repetitions);
+    /// Run and commit to all repetitions, before any of them are opened.
+    pub(crate) fn execute<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        witness: &[u8],
+        circuit: &impl Circuit<T>,
+        params: &SecurityParams,
+        dst: &DomainSeparator,
+        mode: Mode,
+    ) -> Result<Execution<T, D>, Error> {
+        dst.check::<TapeR, D>(params)?;
+        if witness.len() != circuit.witness_len() {
+            return Err(Error::InputLengthError(
+                circuit.witness_len(),
+                witness.len(),
+            ));
+        }
+
+        let circuit_id = CircuitId::new::<T, D>(circuit);
+        let num_of_repetitions = params.num_of_repetitions();
 
+        let mut key_manager = KeyManager::new(num_of_repetitions, params.key_len as usize, rng);
+
+        // all randomness is drawn up front and in order, so that proofs do not
+        // depend on how the repetitions are scheduled
+        let mut repetition_keys = Vec::with_capacity(num_of_repetitions);
+        let mut repetition_shares = Vec::with_capacity(num_of_repetitions);
         for _ in 0..num_of_repetitions {
-            let k1 = key_manager.request_key();
-            let k2 = key_manager.request_key();
-            let k3 = key_manager.request_key();
+            let k1 = key_manager.request_key()?;
+            let k2 = key_manager.request_key()?;
+            let k3 = key_manager.request_key()?;
+            repetition_keys.push((k1, k2, k3));
+
+            repetition_shares.push(match mode {
+                Mode::ZKBoo => Some(Self::share(rng, witness)),
+                Mode::ZKBPlusPlus => None,
+            });
+        }
+
+        let repetitions = cfg_into_iter!(repetition_keys)
+            .zip(repetition_shares)
+            .map(|(keys, shares)| {
+                let repetition_output =
+                    Self::prove_repetition_from_shares(witness, shares, keys, circuit, mode);
+                let (v1, v2, v3) = &repetition_output.party_views;
+
+                let mut commitments = Vec::with_capacity(3);
+                for (key, view) in [(&keys.0, v1), (&keys.1, v2), (&keys.2, v3)] {
+                    commitments.push(PartyExecution { key, view }.commit()?);
+                }
 
-            let repetition_output = Self::prove_repetition(rng, witness, (k1, k2, k3), circuit);
+                Ok((repetition_output, commitments))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
 
+        let mut outputs = Vec::<Vec<GF2Word<T>>>::with_capacity(3 * num_of_repetitions);
+        let mut commitments = Vec::<Commitment<D>>::with_capacity(3 * num_of_repetitions);
+        let mut views = Vec::with_capacity(3 * num_of_repetitions);
+
+        for (repetition_output, repetition_commitments) in repetitions {
             // record all outputs
             outputs.push(repetition_output.party_outputs.0);
             outputs.push(repetition_output.party_outputs.1);
             outputs.push(repetition_output.party_outputs.2);
 
             // record all views
-            all_views.push(repetition_output.party_views.0);
-            all_views.push(repetition_output.party_views.1);
-            all_views.push(repetition_output.party_views.2);
-
-            let views_len = all_views.len();
-
-            let p1_execution = PartyExecution {
-                key: &k1,
-                view: &all_views[views_len - 3],
-            };
-            let p2_execution = PartyExecution {
-                key: &k2,
-                view: &all_views[views_len - 2],
-            };
-            let p3_execution = PartyExecution {
-                key: &k3,
-                view: &all_views[views_len - 1],
-            };
-
-            for pi_execution in [p1_execution, p2_execution, p3_execution] {
-                let cmi = pi_execution.commit()?;
-                all_commitments.push(cmi);
-            }
+            views.push(repetition_output.party_views.0);
+            views.push(repetition_output.party_views.1);
+            views.push(repetition_output.party_views.2);
+
+            commitments.extend(repetition_commitments);
         }
 
+        Ok(Execution {
+            circuit_id,
+            key_manager,
+            views,
+            outputs,
+            commitments,
+        })
+    }
+
+    /// Derive the challenge from an `execution` and open two parties in every
+    /// repetition.
+    pub(crate) fn open(
+        execution: Execution<T, D>,
+        public_output: &Vec<GF2Word<T>>,
+        params: &SecurityParams,
+        dst: &DomainSeparator,
+        mode: Mode,
+    ) -> Result<Proof<T, D>, Error> {
+        let Execution {
+            circuit_id,
+            key_manager,
+            views: mut all_views,
+            outputs,
+            commitments: mut all_commitments,
+        } = execution;
+        let num_of_repetitions = params.num_of_repetitions();
+        let key_len = params.key_len as usize;
+
         let pi = PublicInput {
+            mode,
+            circuit_id: &circuit_id,
             outputs: &outputs,
             public_output,
-            hash_len: HASH_LEN,
-            security_param: SIGMA,
+            params,
         };
 
-        // TODO: remove hardcoded seed
-        let mut fs_oracle = SigmaFS::<D>::initialize(&[0u8]);
+        let mut fs_oracle = SigmaFS::<D>::initialize(dst)?;
         fs_oracle.digest_public_data(&pi)?;
         fs_oracle.digest_prover_message(&all_commitments)?;
 
@@ -147,7 +299,7 @@ where
         let mut claimed_trits = Vec::with_capacity(num_of_repetitions);
         let mut party_inputs = Vec::with_capacity(num_of_repetitions);
 
-        let mut keys = Vec::<Key>::with_capacity(2 * num_of_repetitions);
+        let mut keys = Vec::with_capacity(2 * num_of_repetitions);
         let mut views = Vec::with_capacity(num_of_repetitions);
         let mut commitments = Vec::with_capacity(2 * num_of_repetitions);
 
@@ -157,19 +309,23 @@ where
             let i1 = repetition * 3 + ((party_index + 1) % 3);
             let i2 = repetition * 3 + ((party_index + 2) % 3);
 
+            // in ZKB++ this is only non-empty when `i0` is the third party
             party_inputs.push(std::mem::take(&mut all_views[i0].input));
 
             claimed_trits.push(party_index as u8);
 
             views.push(std::mem::take(&mut all_views[i1]));
 
-            keys.push(key_manager.request_key_i(i0));
-            keys.push(key_manager.request_key_i(i1));
+            keys.push(key_manager.request_key_i(i0)?[..key_len].to_vec());
+            keys.push(key_manager.request_key_i(i1)?[..key_len].to_vec());
 
             commitments.push(std::mem::take(&mut all_commitments[i2]));
         }
 
         Ok(Proof {
+            params: *params,
+            mode,
+            circuit_id,
             party_inputs,
             commitments,
             views,
@@ -0,0 +1,159 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+use sha3::Digest;
+
+use crate::{config::KEY_LEN, error::Error, num_of_repetitions_given_desired_security};
+
+/// Security level of a proof, chosen at runtime and carried in the proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityParams {
+    /// Targeted security level in bits
+    pub bits: u32,
+    /// Whether `bits` must also hold against quantum adversaries. Grover search
+    /// halves the security of the Fiat-Shamir challenge and of the tape keys,
+    /// and collisions of an n-bit hash are found in 2^(n/3) quantum queries.
+    pub post_quantum: bool,
+    /// Output length in bytes of the digest used for commitments
+    pub hash_len: u32,
+    /// Length in bytes of the keys seeding the random tapes, at most `KEY_LEN`
+    pub key_len: u32,
+}
+
+impl SecurityParams {
+    pub const BITS_40: Self = Self::classical(40);
+    pub const BITS_80: Self = Self::classical(80);
+    pub const BITS_128: Self = Self::classical(128);
+    /// 128 bits against quantum adversaries, to be used with a 64 byte digest
+    /// such as `Sha3_512`.
+    pub const POST_QUANTUM_128: Self = Self {
+        bits: 128,
+        post_quantum: true,
+        hash_len: 64,
+        key_len: 32,
+    };
+
+    /// `bits` of classical security with a 32 byte digest and 16 byte keys.
+    pub const fn classical(bits: u32) -> Self {
+        Self {
+            bits,
+            post_quantum: false,
+            hash_len: 32,
+            key_len: 16,
+        }
+    }
+
+    /// Bits of soundness the repetitions must reach for `bits` of security.
+    pub fn soundness_bits(&self) -> usize {
+        if self.post_quantum {
+            2 * self.bits as usize
+        } else {
+            self.bits as usize
+        }
+    }
+
+    pub fn num_of_repetitions(&self) -> usize {
+        num_of_repetitions_given_desired_security(self.soundness_bits())
+    }
+
+    /// Probability that a cheating prover passes all repetitions, (2/3)^r.
+    pub fn soundness_error(&self) -> f64 {
+        (2.0f64 / 3.0).powi(self.num_of_repetitions() as i32)
+    }
+
+    /// Check that the parameters are achievable with digest `D` and that the
+    /// hash and key lengths do not undercut `bits`.
+    pub fn check<D: Digest>(&self) -> Result<(), Error> {
+        let digest_len = <D as Digest>::output_size();
+        if self.hash_len as usize != digest_len {
+            return Err(Error::HashLenError(self.hash_len as usize, digest_len));
+        }
+        if self.key_len == 0 || self.key_len as usize > KEY_LEN {
+            return Err(Error::KeyLenError(KEY_LEN, self.key_len as usize));
+        }
+
+        let (collision_divisor, key_divisor) = if self.post_quantum { (3, 2) } else { (2, 1) };
+        if 8 * self.hash_len / collision_divisor < self.bits
+            || 8 * self.key_len / key_divisor < self.bits
+        {
+            return Err(Error::InsecureParamsError);
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for SecurityParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-bit{} security ({} repetitions, {} byte hash, {} byte keys)",
+            self.bits,
+            if self.post_quantum {
+                " post-quantum"
+            } else {
+                ""
+            },
+            self.num_of_repetitions(),
+            self.hash_len,
+            self.key_len
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_security_params {
+    use sha3::{Keccak256, Sha3_512};
+
+    use super::SecurityParams;
+    use crate::error::Error;
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(SecurityParams::BITS_40.num_of_repetitions(), 69);
+        assert_eq!(SecurityParams::BITS_80.num_of_repetitions(), 137);
+        assert_eq!(SecurityParams::BITS_128.num_of_repetitions(), 219);
+        assert_eq!(SecurityParams::POST_QUANTUM_128.num_of_repetitions(), 438);
+
+        for params in [
+            SecurityParams::BITS_40,
+            SecurityParams::BITS_80,
+            SecurityParams::BITS_128,
+            SecurityParams::POST_QUANTUM_128,
+        ] {
+            assert!(params.soundness_error().log2() <= -(params.soundness_bits() as f64));
+        }
+
+        assert!(SecurityParams::BITS_128.check::<Keccak256>().is_ok());
+        assert!(SecurityParams::POST_QUANTUM_128.check::<Sha3_512>().is_ok());
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(matches!(
+            SecurityParams::POST_QUANTUM_128.check::<Keccak256>(),
+            Err(Error::HashLenError(64, 32))
+        ));
+
+        let mut params = SecurityParams::BITS_128;
+        params.key_len = 33;
+        assert!(matches!(
+            params.check::<Keccak256>(),
+            Err(Error::KeyLenError(32, 33))
+        ));
+
+        params.key_len = 8;
+        assert!(matches!(
+            params.check::<Keccak256>(),
+            Err(Error::InsecureParamsError)
+        ));
+
+        // 32 byte keys and hashes are not enough for 256 bits against quantum adversaries
+        let mut params = SecurityParams::POST_QUANTUM_128;
+        params.bits = 256;
+        assert!(matches!(
+            params.check::<Sha3_512>(),
+            Err(Error::InsecureParamsError)
+        ));
+    }
+}
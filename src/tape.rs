@@ -1,10 +1,19 @@
 use rand::{CryptoRng, RngCore, SeedableRng};
 
 use crate::{
+    error::Error,
     gf2_word::{GF2Word, Value},
     key::Key,
 };
 
+/// Identifies the tape generator `R` by its first output on an all-zero key.
+pub fn tape_id<R: SeedableRng<Seed = Key> + RngCore>() -> [u8; 8] {
+    let mut rng = R::from_seed(Key::default());
+    let mut id = [0u8; 8];
+    rng.fill_bytes(&mut id);
+    id
+}
+
 /// A tape of values that can be read at its current `offset`.
 pub struct Tape<T: Value> {
     offset: usize,
@@ -18,21 +27,50 @@ impl<T: Value> Tape<T> {
         len: usize,
     ) -> Self {
         let mut rng = R::from_seed(key);
+        Self::from_rng(&mut rng, len)
+    }
+
+    /// Derive an input share of `share_len` bytes followed by a tape with `len`
+    /// entries from `key`, as done for the first two parties in ZKB++.
+    pub fn from_key_with_share<R: SeedableRng<Seed = Key> + RngCore + CryptoRng>(
+        key: Key,
+        share_len: usize,
+        len: usize,
+    ) -> (Vec<u8>, Self) {
+        let mut rng = R::from_seed(key);
+
+        let mut share = vec![0u8; share_len];
+        rng.fill_bytes(&mut share);
+
+        (share, Self::from_rng(&mut rng, len))
+    }
+
+    fn from_rng<R: RngCore + CryptoRng>(rng: &mut R, len: usize) -> Self {
         let mut tape = Vec::with_capacity(len);
 
         for _ in 0..len {
-            tape.push(T::gen_rand(&mut rng).into());
+            tape.push(T::gen_rand(rng).into());
         }
 
         Self { offset: 0, tape }
     }
 
     /// Read the next value on the tape.
-    /// TODO: Return error if tape runs out of values.
-    pub fn read_next(&mut self) -> GF2Word<T> {
-        let ri = self.tape[self.offset];
+    pub fn read_next(&mut self) -> Result<GF2Word<T>, Error> {
+        let ri = self.tape.get(self.offset).copied();
+        // a failed read is counted as well, to tell which tape ran out
         self.offset += 1;
-        assert!(self.offset <= self.tape.len());
-        ri
+        ri.ok_or(Error::TapeExhaustedError)
+    }
+
+    /// Number of values read so far, one past the capacity once the tape ran
+    /// out.
+    pub fn values_read(&self) -> usize {
+        self.offset
+    }
+
+    /// Number of values the tape was initialised with.
+    pub fn capacity(&self) -> usize {
+        self.tape.len()
     }
 }
@@ -4,20 +4,25 @@ use rand::{CryptoRng, RngCore, SeedableRng};
 
 use sha3::{digest::FixedOutputReset, Digest};
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 use crate::{
-    circuit::Circuit,
+    circuit::{Circuit, CircuitId, Output},
     commitment::Commitment,
-    config::HASH_LEN,
     data_structures::{PartyExecution, Proof, PublicInput},
     error::Error,
-    fs::SigmaFS,
+    fs::{DomainSeparator, SigmaFS},
     gf2_word::{GF2Word, Value},
-    key::Key,
-    num_of_repetitions_given_desired_security,
+    key::{pad_key, Key},
     party::Party,
+    security::SecurityParams,
     tape::Tape,
 };
 
+/// Commitments and outputs of the three parties of one repetition.
+type OpenedRepetition<T, D> = ([Commitment<D>; 3], [Output<T>; 3]);
+
 pub struct Verifier<T: Value, TapeR, D>(PhantomData<(T, TapeR, D)>)
 where
     D: Digest + FixedOutputReset,
@@ -27,108 +32,59 @@ impl<T, TapeR, D> Verifier<T, TapeR, D>
 where
     T: Value + PartialEq,
     TapeR: SeedableRng<Seed = Key> + RngCore + CryptoRng,
-    D: Clone + Default + Digest + FixedOutputReset,
+    D: Clone + Default + Digest + FixedOutputReset + Send + Sync,
 {
-    pub fn verify<const SIGMA: usize>(
-        proof: &Proof<T, D, SIGMA>,
+    pub fn verify(
+        proof: &Proof<T, D>,
         circuit: &impl Circuit<T>,
         public_output: &Vec<GF2Word<T>>,
+        params: &SecurityParams,
+        dst: &DomainSeparator,
     ) -> Result<(), Error> {
-        let num_of_repetitions = num_of_repetitions_given_desired_security(SIGMA);
+        dst.check::<TapeR, D>(params)?;
+        if proof.params != *params {
+            return Err(Error::SecurityParamsError(*params, proof.params));
+        }
+
+        let circuit_id = CircuitId::new::<T, D>(circuit);
+        if proof.circuit_id != circuit_id {
+            return Err(Error::CircuitMismatchError);
+        }
+
+        let num_of_repetitions = params.num_of_repetitions();
 
         // Based on O3 and O5 of (https://eprint.iacr.org/2017/279.pdf)
-        assert_eq!(proof.party_inputs.len(), num_of_repetitions);
-        assert_eq!(proof.commitments.len(), num_of_repetitions);
-        assert_eq!(proof.views.len(), num_of_repetitions);
-        assert_eq!(proof.claimed_trits.len(), num_of_repetitions);
-        assert_eq!(proof.keys.len(), 2 * num_of_repetitions);
+        Self::check_len("party_inputs", num_of_repetitions, proof.party_inputs.len())?;
+        Self::check_len("commitments", num_of_repetitions, proof.commitments.len())?;
+        Self::check_len("views", num_of_repetitions, proof.views.len())?;
+        Self::check_len(
+            "claimed_trits",
+            num_of_repetitions,
+            proof.claimed_trits.len(),
+        )?;
+        Self::check_len("keys", 2 * num_of_repetitions, proof.keys.len())?;
+        Self::check_key_lens(proof)?;
+
+        let repetitions = cfg_into_iter!(0..num_of_repetitions)
+            .map(|repetition| Self::verify_repetition(proof, repetition, circuit, public_output))
+            .collect::<Result<Vec<_>, Error>>()?;
 
         let mut all_commitments = Vec::<Commitment<D>>::with_capacity(3 * num_of_repetitions);
         let mut outputs = Vec::<Vec<GF2Word<T>>>::with_capacity(3 * num_of_repetitions);
-
-        for (repetition, &party_index) in proof.claimed_trits.iter().enumerate() {
-            let k_i0 = proof.keys[2 * repetition];
-            let mut p = Party::new::<TapeR>(
-                proof.party_inputs[repetition].clone(),
-                k_i0,
-                circuit.num_of_mul_gates(),
-            );
-
-            let k_i1 = proof.keys[2 * repetition + 1];
-            let view_i1 = &proof.views[repetition];
-
-            let tape_i1 = Tape::from_key::<TapeR>(k_i1, circuit.num_of_mul_gates());
-            let mut p_next = Party::from_tape_and_view(view_i1.clone(), tape_i1);
-
-            let (o0, o1) = circuit.simulate_two_parties(&mut p, &mut p_next)?;
-            let o2 = Self::derive_third_output(public_output, circuit, (&o0, &o1));
-
-            /*
-                Based on O6 of (https://eprint.iacr.org/2017/279.pdf)
-                Instead of checking view consistency, full view is computed through simulation
-                then security comes from binding property of H used when committing
-            */
-            let view_i0 = &p.view;
-
-            let pi0_execution = PartyExecution {
-                key: &k_i0,
-                view: view_i0,
-            };
-
-            // Based on O4 of (https://eprint.iacr.org/2017/279.pdf)
-            let cm_i0 = pi0_execution.commit::<D>()?;
-
-            let pi1_execution = PartyExecution {
-                key: &k_i1,
-                view: view_i1,
-            };
-
-            // Based on O4 of (https://eprint.iacr.org/2017/279.pdf)
-            let cm_i1 = pi1_execution.commit::<D>()?;
-
-            let cm_i2 = &proof.commitments[repetition];
-
-            match party_index {
-                0 => {
-                    all_commitments.push(cm_i0);
-                    all_commitments.push(cm_i1);
-                    all_commitments.push(cm_i2.clone());
-
-                    outputs.push(o0);
-                    outputs.push(o1);
-                    outputs.push(o2);
-                }
-                1 => {
-                    all_commitments.push(cm_i2.clone());
-                    all_commitments.push(cm_i0);
-                    all_commitments.push(cm_i1);
-
-                    outputs.push(o2);
-                    outputs.push(o0);
-                    outputs.push(o1);
-                }
-                2 => {
-                    all_commitments.push(cm_i1);
-                    all_commitments.push(cm_i2.clone());
-                    all_commitments.push(cm_i0);
-
-                    outputs.push(o1);
-                    outputs.push(o2);
-                    outputs.push(o0);
-                }
-                _ => panic!("Not trit"),
-            };
+        for (commitments, repetition_outputs) in repetitions {
+            all_commitments.extend(commitments);
+            outputs.extend(repetition_outputs);
         }
 
         let pi = PublicInput {
+            mode: proof.mode,
+            circuit_id: &circuit_id,
             outputs: &outputs,
             public_output,
-            hash_len: HASH_LEN,
-            security_param: SIGMA,
+            params,
         };
 
-        // TODO: remove hardcoded seed
-        let mut fs_oracle = SigmaFS::<D>::initialize(&[0u8]);
+        let mut fs_oracle = SigmaFS::<D>::initialize(dst)?;
         fs_oracle.digest_public_data(&pi)?;
         fs_oracle.digest_prover_message(&all_commitments)?;
 
@@ -140,17 +96,79 @@ where
         Ok(())
     }
 
+    /// Simulate the two parties opened in `repetition` and return the
+    /// commitments and outputs of all three parties, ordered by party index.
+    fn verify_repetition(
+        proof: &Proof<T, D>,
+        repetition: usize,
+        circuit: &impl Circuit<T>,
+        public_output: &[GF2Word<T>],
+    ) -> Result<OpenedRepetition<T, D>, Error> {
+        let party_index = proof.claimed_trits[repetition];
+        if party_index > 2 {
+            return Err(Error::TritError(party_index));
+        }
+        let i0 = party_index as usize;
+
+        let k_i0 = pad_key(&proof.keys[2 * repetition])?;
+        let k_i1 = pad_key(&proof.keys[2 * repetition + 1])?;
+        let view_i1 = &proof.views[repetition];
+        let (mut p, mut p_next) = Self::open_parties(proof, repetition, circuit)?;
+
+        let (o0, o1) = circuit.simulate_two_parties(&mut p, &mut p_next)?;
+        let o2 = Self::derive_third_output(public_output, circuit, (&o0, &o1))?;
+
+        /*
+            Based on O6 of (https://eprint.iacr.org/2017/279.pdf)
+            Instead of checking view consistency, full view is computed through simulation
+            then security comes from binding property of H used when committing
+        */
+        if proof.mode.derives_share(i0) {
+            p.view.input.clear();
+        }
+        let view_i0 = &p.view;
+
+        let pi0_execution = PartyExecution {
+            key: &k_i0,
+            view: view_i0,
+        };
+
+        // Based on O4 of (https://eprint.iacr.org/2017/279.pdf)
+        let cm_i0 = pi0_execution.commit::<D>()?;
+
+        let pi1_execution = PartyExecution {
+            key: &k_i1,
+            view: view_i1,
+        };
+
+        // Based on O4 of (https://eprint.iacr.org/2017/279.pdf)
+        let cm_i1 = pi1_execution.commit::<D>()?;
+
+        let cm_i2 = proof.commitments[repetition].clone();
+
+        let mut commitments = [cm_i0, cm_i1, cm_i2];
+        let mut outputs = [o0, o1, o2];
+
+        // party `i0` was simulated first, rotate it back into place
+        commitments.rotate_right(i0);
+        outputs.rotate_right(i0);
+
+        Ok((commitments, outputs))
+    }
+
     pub fn derive_third_output(
         public_output: &[GF2Word<T>],
         circuit: &impl Circuit<T>,
         circuit_simulation_output: (&Vec<GF2Word<T>>, &Vec<GF2Word<T>>),
-    ) -> Vec<GF2Word<T>> {
+    ) -> Result<Vec<GF2Word<T>>, Error> {
         let party_output_len = circuit.party_output_len();
         let (o1, o2) = circuit_simulation_output;
 
-        // TODO: introduce error here
-        assert_eq!(o1.len(), party_output_len);
-        assert_eq!(o2.len(), party_output_len);
+        for len in [o1.len(), o2.len(), public_output.len()] {
+            if len != party_output_len {
+                return Err(Error::OutputLengthError(party_output_len, len));
+            }
+        }
 
         let mut derived_output = Vec::with_capacity(party_output_len);
 
@@ -158,6 +176,70 @@ where
             derived_output.push(o1[i] ^ o2[i] ^ public_output[i]);
         }
 
-        derived_output
+        Ok(derived_output)
+    }
+
+    /// Rebuild the two parties opened in `repetition` from the keys, input
+    /// share and view sent in `proof`. The trit of `repetition` and the key
+    /// lengths must be checked.
+    pub(crate) fn open_parties(
+        proof: &Proof<T, D>,
+        repetition: usize,
+        circuit: &impl Circuit<T>,
+    ) -> Result<(Party<T>, Party<T>), Error> {
+        let witness_len = circuit.witness_len();
+        let i0 = proof.claimed_trits[repetition] as usize;
+        let i1 = (i0 + 1) % 3;
+
+        let k_i0 = pad_key(&proof.keys[2 * repetition])?;
+        let input_i0 = &proof.party_inputs[repetition];
+        let p = if proof.mode.derives_share(i0) {
+            if !input_i0.is_empty() {
+                return Err(Error::InputLengthError(0, input_i0.len()));
+            }
+            Party::from_key::<TapeR>(k_i0, witness_len, circuit.num_of_mul_gates())
+        } else {
+            if input_i0.len() != witness_len {
+                return Err(Error::InputLengthError(witness_len, input_i0.len()));
+            }
+            Party::new::<TapeR>(input_i0.clone(), k_i0, circuit.num_of_mul_gates())
+        };
+
+        let k_i1 = pad_key(&proof.keys[2 * repetition + 1])?;
+        let view_i1 = &proof.views[repetition];
+
+        let p_next = if proof.mode.derives_share(i1) {
+            if !view_i1.input.is_empty() {
+                return Err(Error::InputLengthError(0, view_i1.input.len()));
+            }
+            let (share, tape_i1) =
+                Tape::from_key_with_share::<TapeR>(k_i1, witness_len, circuit.num_of_mul_gates());
+            let mut view = view_i1.clone();
+            view.input = share;
+            Party::from_tape_and_view(view, tape_i1)
+        } else {
+            if view_i1.input.len() != witness_len {
+                return Err(Error::InputLengthError(witness_len, view_i1.input.len()));
+            }
+            let tape_i1 = Tape::from_key::<TapeR>(k_i1, circuit.num_of_mul_gates());
+            Party::from_tape_and_view(view_i1.clone(), tape_i1)
+        };
+
+        Ok((p, p_next))
+    }
+
+    pub(crate) fn check_key_lens(proof: &Proof<T, D>) -> Result<(), Error> {
+        let key_len = proof.params.key_len as usize;
+        match proof.keys.iter().find(|key| key.len() != key_len) {
+            Some(key) => Err(Error::KeyLenError(key_len, key.len())),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_len(field: &'static str, expected: usize, got: usize) -> Result<(), Error> {
+        if got != expected {
+            return Err(Error::ProofLengthError(field, expected, got));
+        }
+        Ok(())
     }
 }
@@ -1,15 +1,29 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::gf2_word::{GF2Word, Value};
+use crate::{
+    error::Error,
+    gf2_word::{GF2Word, Value},
+};
 
 /// A party's `View` consists of:
 /// - input: the party's initial share of the witness; and
-/// - messages: the messages sent to the party.
+/// - messages: the messages sent to the party; and
+/// - carries: the carry words of modular additions, kept apart from `messages`
+///   since their LSB is always zero and is dropped when encoding.
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct View<T: Value> {
+    #[serde(skip)]
     offset: usize,
+    #[serde(skip)]
+    carry_offset: usize,
     pub input: Vec<u8>,
     pub messages: Vec<GF2Word<T>>,
+    #[serde(
+        serialize_with = "serialize_carries",
+        deserialize_with = "deserialize_carries"
+    )]
+    pub carries: Vec<GF2Word<T>>,
 }
 
 impl<T: Value> View<T> {
@@ -17,7 +31,9 @@ impl<T: Value> View<T> {
         Self {
             input,
             messages: vec![],
+            carries: vec![],
             offset: 0,
+            carry_offset: 0,
         }
     }
 
@@ -25,10 +41,157 @@ impl<T: Value> View<T> {
         self.messages.push(msg);
     }
 
+    pub fn send_carry(&mut self, carry: GF2Word<T>) {
+        self.carries.push(carry);
+    }
+
     /// Read the message at the current `offset`.
-    pub fn read_next(&mut self) -> GF2Word<T> {
-        let msg_i = self.messages[self.offset];
+    pub fn read_next(&mut self) -> Result<GF2Word<T>, Error> {
+        let msg_i = self.messages.get(self.offset).copied();
+        // a failed read is counted as well, to tell which stream ran out
         self.offset += 1;
-        msg_i
+        msg_i.ok_or(Error::ViewExhaustedError)
+    }
+
+    /// Read the carry at the current `carry_offset`.
+    pub fn read_next_carry(&mut self) -> Result<GF2Word<T>, Error> {
+        let carry_i = self.carries.get(self.carry_offset).copied();
+        self.carry_offset += 1;
+        carry_i.ok_or(Error::ViewExhaustedError)
+    }
+
+    /// Number of messages read so far, one past their number once they ran
+    /// out.
+    pub fn messages_read(&self) -> usize {
+        self.offset
+    }
+
+    /// Number of carries read so far, one past their number once they ran out.
+    pub fn carries_read(&self) -> usize {
+        self.carry_offset
+    }
+}
+
+/// Number of bytes needed to pack `num_carries` carries of `T` without their LSB.
+pub fn packed_carries_len<T: Value>(num_carries: usize) -> usize {
+    (num_carries * (T::bits_len() - 1)).div_ceil(8)
+}
+
+/// Pack carries MSB first, dropping the always-zero LSB of each word.
+fn pack_carries<T: Value>(carries: &[GF2Word<T>]) -> Vec<u8> {
+    let mut packed = vec![0u8; packed_carries_len::<T>(carries.len())];
+    let mut pos = 0;
+
+    for carry in carries {
+        for i in (1..T::bits_len()).rev() {
+            if carry.value.get_bit(i).inner() {
+                packed[pos / 8] |= 0x80 >> (pos % 8);
+            }
+            pos += 1;
+        }
+    }
+
+    packed
+}
+
+fn unpack_carries<T: Value>(packed: &[u8], num_carries: usize) -> Vec<GF2Word<T>> {
+    let mut carries = Vec::with_capacity(num_carries);
+    let mut pos = 0;
+
+    for _ in 0..num_carries {
+        let mut carry = T::zero();
+        for i in (1..T::bits_len()).rev() {
+            let bit = (packed[pos / 8] << (pos % 8)) & 0x80 != 0;
+            carry = carry.set_bit(i, bit);
+            pos += 1;
+        }
+        carries.push(carry.into());
+    }
+
+    carries
+}
+
+fn serialize_carries<T: Value, S: Serializer>(
+    carries: &[GF2Word<T>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    (carries.len() as u64, pack_carries(carries)).serialize(serializer)
+}
+
+fn deserialize_carries<'de, T: Value, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<GF2Word<T>>, D::Error> {
+    let (num_carries, packed) = <(u64, Vec<u8>)>::deserialize(deserializer)?;
+    let num_carries = usize::try_from(num_carries).map_err(D::Error::custom)?;
+
+    // checked so that a huge count cannot overflow or outgrow the packed bytes
+    let expected_len = num_carries
+        .checked_mul(T::bits_len() - 1)
+        .map(|bits| bits.div_ceil(8));
+    if expected_len != Some(packed.len()) {
+        return Err(D::Error::custom("packed carries length mismatch"));
+    }
+
+    // the unused low bits of the last byte must be zero, so that a view has a
+    // single encoding
+    let padding_bits = 8 * packed.len() - num_carries * (T::bits_len() - 1);
+    if padding_bits > 0 && packed[packed.len() - 1] & ((1u8 << padding_bits) - 1) != 0 {
+        return Err(D::Error::custom("non-zero padding in packed carries"));
+    }
+
+    Ok(unpack_carries(&packed, num_carries))
+}
+
+#[cfg(test)]
+mod test_view {
+    use super::{packed_carries_len, View};
+
+    #[test]
+    fn test_carries_roundtrip() {
+        let mut view = View::<u32>::new(vec![1, 2, 3]);
+        view.send_msg(0xdeadbeefu32.into());
+        for carry in [0xfffffffeu32, 0x2, 0x80000000, 0x12345678 << 1] {
+            view.send_carry(carry.into());
+        }
+
+        let bytes = bincode::serialize(&view).unwrap();
+        let mut decoded: View<u32> = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(decoded.input, view.input);
+        assert_eq!(decoded.read_next().unwrap(), view.messages[0]);
+        for &carry in view.carries.iter() {
+            assert_eq!(decoded.read_next_carry().unwrap(), carry);
+        }
+        assert!(decoded.read_next().is_err());
+        assert!(decoded.read_next_carry().is_err());
+    }
+
+    #[test]
+    fn test_encoding_size() {
+        let mut view = View::<u32>::new(vec![0u8; 5]);
+        for i in 0..10u32 {
+            view.send_msg(i.into());
+            view.send_carry((i << 1).into());
+        }
+
+        // input, messages and packed carries, each with an 8 byte length prefix,
+        // plus the number of carries
+        let expected = (8 + 5) + (8 + 10 * 4) + (8 + 8 + packed_carries_len::<u32>(10));
+        assert_eq!(bincode::serialize(&view).unwrap().len(), expected);
+        assert_eq!(packed_carries_len::<u32>(10), 39);
+    }
+
+    #[test]
+    fn test_non_zero_padding() {
+        let mut view = View::<u32>::new(vec![]);
+        for i in 0..10u32 {
+            view.send_carry((i << 1).into());
+        }
+
+        // 10 carries take 310 of the 312 bits of the packed carries, which
+        // come last in the encoding
+        let mut bytes = bincode::serialize(&view).unwrap();
+        *bytes.last_mut().unwrap() |= 1;
+        assert!(bincode::deserialize::<View<u32>>(&bytes).is_err());
     }
 }